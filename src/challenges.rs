@@ -0,0 +1,343 @@
+//! Registry of the solved Cryptopals challenges.
+//!
+//! Each challenge knows how to load its input from the `input/` folder, how to solve it with
+//! the library functions and what the correct answer is, so the whole set doubles as a
+//! regression suite for the toolkit (`cryp run --all`).
+
+use std::time::{Duration, Instant};
+
+use crate::input::{load_base64_blob, load_hex_lines};
+use crate::score::LogLikelihood;
+use crate::{
+    aes_ecb_decrypt, base64_encode, bytes_to_hexstr, count_repeated_blocks, decrypt_cbc, detect_single_byte_xor,
+    hexstr_to_bytes, pkcs7_padding, xor_fixed, xor_guess_key, xor_guess_key_len,
+    xor_guess_single_byte, xor_one_byte, xor_repeating, CrypError,
+};
+
+/// A single Cryptopals challenge.
+pub trait Challenge {
+    /// Whatever `input` hands over to `solve`.
+    type Input;
+
+    /// Short identifier such as `s1c6` (set 1, challenge 6).
+    fn id(&self) -> &'static str;
+
+    fn title(&self) -> &'static str;
+
+    /// Loads the challenge input, usually from a file in `input/`.
+    fn input(&self) -> Result<Self::Input, CrypError>;
+
+    /// Solves the challenge and returns the answer in the same form as `expected`.
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError>;
+
+    /// The known correct answer.
+    fn expected(&self) -> &'static str;
+}
+
+/// The result of running a challenge.
+#[derive(Debug)]
+pub struct Outcome {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// The answer produced by `solve`, or the error if loading or solving failed.
+    pub answer: Result<String, CrypError>,
+    pub expected: &'static str,
+    /// Time spent loading the input and solving.
+    pub elapsed: Duration,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        matches!(&self.answer, Ok(answer) if answer == self.expected)
+    }
+}
+
+/// Object-safe view of a [`Challenge`], so challenges with different inputs can share a registry.
+pub trait DynChallenge {
+    fn name(&self) -> &'static str;
+    fn run(&self) -> Outcome;
+}
+
+impl<C: Challenge> DynChallenge for C {
+    fn name(&self) -> &'static str {
+        self.id()
+    }
+
+    fn run(&self) -> Outcome {
+        let start = Instant::now();
+        let answer = self.input().and_then(|input| self.solve(input));
+
+        Outcome {
+            id: self.id(),
+            title: self.title(),
+            answer,
+            expected: self.expected(),
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+/// Returns every registered challenge, in order.
+///
+/// # Example
+/// ```rust
+/// use cryp::challenges;
+///
+/// for challenge in challenges::all() {
+///     let outcome = challenge.run();
+///     assert!(outcome.passed(), "{} failed: {:?}", outcome.id, outcome.answer);
+/// }
+/// ```
+pub fn all() -> Vec<Box<dyn DynChallenge>> {
+    vec![
+        Box::new(S1C1),
+        Box::new(S1C2),
+        Box::new(S1C3),
+        Box::new(S1C4),
+        Box::new(S1C5),
+        Box::new(S1C6),
+        Box::new(S1C7),
+        Box::new(S1C8),
+        Box::new(S2C9),
+        Box::new(S2C10),
+    ]
+}
+
+/// Looks up a challenge by its id (e.g. `s1c6`).
+pub fn find(id: &str) -> Option<Box<dyn DynChallenge>> {
+    all().into_iter().find(|challenge| challenge.name().eq_ignore_ascii_case(id))
+}
+
+fn first_line(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).lines().next().unwrap_or_default().trim_end().to_string()
+}
+
+/// Returns the single-byte key and score of the best scoring decryption of `data`.
+fn best_single_byte_key(data: &[u8]) -> (u8, f64) {
+    xor_guess_single_byte(data, &LogLikelihood)
+}
+
+pub struct S1C1;
+
+impl Challenge for S1C1 {
+    type Input = &'static str;
+
+    fn id(&self) -> &'static str { "s1c1" }
+    fn title(&self) -> &'static str { "Convert hex to base64" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        Ok("49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d")
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        Ok(base64_encode(&hexstr_to_bytes(input)?))
+    }
+
+    fn expected(&self) -> &'static str {
+        "SSdtIGtpbGxpbmcgeW91ciBicmFpbiBsaWtlIGEgcG9pc29ub3VzIG11c2hyb29t"
+    }
+}
+
+pub struct S1C2;
+
+impl Challenge for S1C2 {
+    type Input = (&'static str, &'static str);
+
+    fn id(&self) -> &'static str { "s1c2" }
+    fn title(&self) -> &'static str { "Fixed XOR" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        Ok(("1c0111001f010100061a024b53535009181c", "686974207468652062756c6c277320657965"))
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        let xored = xor_fixed(&hexstr_to_bytes(input.0)?, &hexstr_to_bytes(input.1)?)?;
+        Ok(bytes_to_hexstr(&xored))
+    }
+
+    fn expected(&self) -> &'static str {
+        "746865206b696420646f6e277420706c6179"
+    }
+}
+
+pub struct S1C3;
+
+impl Challenge for S1C3 {
+    type Input = Vec<u8>;
+
+    fn id(&self) -> &'static str { "s1c3" }
+    fn title(&self) -> &'static str { "Single-byte XOR cipher" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        hexstr_to_bytes("1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736")
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        let (key, _) = best_single_byte_key(&input);
+        Ok(String::from_utf8_lossy(&xor_one_byte(&input, key)).into_owned())
+    }
+
+    fn expected(&self) -> &'static str {
+        "Cooking MC's like a pound of bacon"
+    }
+}
+
+pub struct S1C4;
+
+impl Challenge for S1C4 {
+    type Input = Vec<Vec<u8>>;
+
+    fn id(&self) -> &'static str { "s1c4" }
+    fn title(&self) -> &'static str { "Detect single-character XOR" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        load_hex_lines("4.txt")
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        let best = detect_single_byte_xor(&input, &LogLikelihood, Some(1));
+        let detection = best.first().ok_or(CrypError::NoAnswer { reason: "empty input" })?;
+        Ok(first_line(&detection.plaintext))
+    }
+
+    fn expected(&self) -> &'static str {
+        "Now that the party is jumping"
+    }
+}
+
+pub struct S1C5;
+
+impl Challenge for S1C5 {
+    type Input = &'static str;
+
+    fn id(&self) -> &'static str { "s1c5" }
+    fn title(&self) -> &'static str { "Implement repeating-key XOR" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        Ok("Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal")
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        Ok(bytes_to_hexstr(&xor_repeating(input.as_bytes(), b"ICE")?))
+    }
+
+    fn expected(&self) -> &'static str {
+        "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f"
+    }
+}
+
+pub struct S1C6;
+
+impl Challenge for S1C6 {
+    type Input = Vec<u8>;
+
+    fn id(&self) -> &'static str { "s1c6" }
+    fn title(&self) -> &'static str { "Break repeating-key XOR" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        load_base64_blob("6.txt")
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        let key_size = xor_guess_key_len(&input, 2, 40)?.best();
+        let key = xor_guess_key(&input, key_size)?;
+        Ok(String::from_utf8_lossy(&key).into_owned())
+    }
+
+    fn expected(&self) -> &'static str {
+        "Terminator X: Bring the noise"
+    }
+}
+
+pub struct S1C7;
+
+impl Challenge for S1C7 {
+    type Input = Vec<u8>;
+
+    fn id(&self) -> &'static str { "s1c7" }
+    fn title(&self) -> &'static str { "AES in ECB mode" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        load_base64_blob("7.txt")
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        Ok(first_line(&aes_ecb_decrypt(&input, b"YELLOW SUBMARINE")?))
+    }
+
+    fn expected(&self) -> &'static str {
+        "I'm back and I'm ringin' the bell"
+    }
+}
+
+pub struct S1C8;
+
+impl Challenge for S1C8 {
+    type Input = Vec<Vec<u8>>;
+
+    fn id(&self) -> &'static str { "s1c8" }
+    fn title(&self) -> &'static str { "Detect AES in ECB mode" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        load_hex_lines("8.txt")
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        let (line_no, _) = input.iter()
+            .enumerate()
+            .map(|(i, line)| (i + 1, count_repeated_blocks(line, 16)))
+            .filter(|&(_, repeats)| repeats > 0)
+            .max_by_key(|&(_, repeats)| repeats)
+            .ok_or(CrypError::NoAnswer { reason: "no line contains repeated blocks" })?;
+
+        Ok(format!("line {}", line_no))
+    }
+
+    fn expected(&self) -> &'static str {
+        "line 133"
+    }
+}
+
+pub struct S2C9;
+
+impl Challenge for S2C9 {
+    type Input = &'static [u8];
+
+    fn id(&self) -> &'static str { "s2c9" }
+    fn title(&self) -> &'static str { "Implement PKCS#7 padding" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        Ok(b"YELLOW SUBMARINE")
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        let padded = pkcs7_padding(input, 20)?;
+        Ok(format!("{:?}", String::from_utf8_lossy(&padded)))
+    }
+
+    fn expected(&self) -> &'static str {
+        "\"YELLOW SUBMARINE\\u{4}\\u{4}\\u{4}\\u{4}\""
+    }
+}
+
+pub struct S2C10;
+
+impl Challenge for S2C10 {
+    type Input = Vec<u8>;
+
+    fn id(&self) -> &'static str { "s2c10" }
+    fn title(&self) -> &'static str { "Implement CBC mode" }
+
+    fn input(&self) -> Result<Self::Input, CrypError> {
+        load_base64_blob("10.txt")
+    }
+
+    fn solve(&self, input: Self::Input) -> Result<String, CrypError> {
+        let plaintext = decrypt_cbc(b"YELLOW SUBMARINE", &[0u8; 16], &input)?;
+        Ok(first_line(&plaintext))
+    }
+
+    fn expected(&self) -> &'static str {
+        "I'm back and I'm ringin' the bell"
+    }
+}
@@ -0,0 +1,1552 @@
+//! Classical ciphers over the 26-letter alphabet and their automatic solvers.
+//!
+//! The substitution ciphers (Caesar, Vigenère, affine, simple substitution) keep the case of
+//! every letter and pass everything else (spaces, digits, punctuation) through unchanged, so a
+//! ciphertext keeps the word shapes of its plaintext. The transpositions (columnar, rail fence)
+//! move every character. Playfair and Hill work on letter pairs and blocks, so they drop
+//! everything but the letters, writing ciphertext in uppercase and plaintext in lowercase.
+//!
+//! The solvers return a [`Solution`]: Caesar, affine and rail fence ciphers are broken by
+//! trying every key, Vigenère by estimating the period (`estimate_key_len`) and breaking every
+//! column as a Caesar cipher, columnar transposition by searching the column orders, 2 x 2 Hill
+//! row by row, and simple substitution and Playfair by simulated annealing with the fitness of
+//! an [`NgramModel`]. Larger Hill keys can be recovered from known plaintext.
+//!
+//! # Example
+//! ```rust
+//! use cryp::classical::{self, break_vigenere};
+//! use cryp::score::LogLikelihood;
+//!
+//! let plaintext = "It was the best of times, it was the worst of times, it was the age of wisdom, it was \
+//!                  the age of foolishness, it was the epoch of belief, it was the epoch of incredulity, \
+//!                  it was the season of Light, it was the season of Darkness, it was the spring of hope, \
+//!                  it was the winter of despair, we had everything before us, we had nothing before us";
+//! let ciphertext = classical::vigenere_encrypt(plaintext, "DICKENS").unwrap();
+//! assert!(ciphertext.starts_with("Lb ykw gzh jgcx"));
+//!
+//! let solution = break_vigenere(&ciphertext, 2, 12, &LogLikelihood).unwrap();
+//! assert_eq!(solution.key, "DICKENS");
+//! assert_eq!(solution.plaintext, plaintext);
+//! ```
+
+use crate::ngram::{NgramAlphabet, NgramModel};
+use crate::score::Scorer;
+use crate::{estimate_key_len, CrypError};
+
+/// Letters of the alphabet the ciphers work on.
+pub const ALPHABET_SIZE: usize = 26;
+
+/// English letters from most to least frequent, used to seed the substitution solver.
+const ENGLISH_BY_FREQUENCY: &[u8; ALPHABET_SIZE] = b"ETAOINSHRDLCUMWFGYPBVKJXQZ";
+
+/// A recovered key with the plaintext it decrypts to and the plaintext's score (higher is
+/// better; the scale depends on the solver). Byte-oriented solvers return `Vec<u8>`
+/// plaintexts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution<K, P = String> {
+    pub key: K,
+    pub plaintext: P,
+    pub score: f64,
+}
+
+/// The position of `c` in the alphabet and whether it is uppercase, if it is an ASCII letter.
+fn letter_index(c: char) -> Option<(u8, bool)> {
+    match c {
+        'A'..='Z' => Some((c as u8 - b'A', true)),
+        'a'..='z' => Some((c as u8 - b'a', false)),
+        _ => None,
+    }
+}
+
+fn index_letter(index: u8, upper: bool) -> char {
+    (if upper { b'A' } else { b'a' } + index) as char
+}
+
+/// Replaces every letter of `text` by `f(letter index, letter number)`, where the letter number
+/// counts the letters seen before it. Case and non-letters are kept.
+fn map_letters(text: &str, mut f: impl FnMut(u8, usize) -> u8) -> String {
+    let mut n = 0;
+    text.chars()
+        .map(|c| match letter_index(c) {
+            Some((index, upper)) => {
+                let mapped = f(index, n);
+                n += 1;
+                index_letter(mapped, upper)
+            }
+            None => c,
+        })
+        .collect()
+}
+
+/// The letters of `text` as alphabet indices (0 for `A` or `a`), dropping everything else.
+fn letter_indices(text: &str) -> Vec<u8> {
+    text.chars().filter_map(|c| letter_index(c).map(|(index, _)| index)).collect()
+}
+
+/// The modular inverse of `a` modulo `m`, if `a` and `m` are coprime.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::mod_inverse;
+///
+/// assert_eq!(mod_inverse(7, 26), Some(15));
+/// assert_eq!(mod_inverse(-7, 26), Some(11));
+/// assert_eq!(mod_inverse(13, 26), None);
+/// ```
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let (mut old_r, mut r) = (a.rem_euclid(m), m);
+    let (mut old_s, mut s) = (1i64, 0i64);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    (old_r == 1).then(|| old_s.rem_euclid(m))
+}
+
+/// Shifts every letter of `text` forward by `shift` places.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{caesar_decrypt, caesar_encrypt};
+///
+/// assert_eq!(caesar_encrypt("Veni, vidi, vici.", 3), "Yhql, ylgl, ylfl.");
+/// assert_eq!(caesar_decrypt("Yhql, ylgl, ylfl.", 3), "Veni, vidi, vici.");
+/// ```
+pub fn caesar_encrypt(text: &str, shift: u8) -> String {
+    let shift = shift % ALPHABET_SIZE as u8;
+    map_letters(text, |p, _| (p + shift) % ALPHABET_SIZE as u8)
+}
+
+/// Shifts every letter of `text` back by `shift` places.
+pub fn caesar_decrypt(text: &str, shift: u8) -> String {
+    caesar_encrypt(text, ALPHABET_SIZE as u8 - shift % ALPHABET_SIZE as u8)
+}
+
+/// The shifts of a Vigenère key (`A` is 0).
+fn vigenere_shifts(key: &str) -> Result<Vec<u8>, CrypError> {
+    if key.is_empty() {
+        return Err(CrypError::EmptyKey);
+    }
+    key.chars()
+        .enumerate()
+        .map(|(pos, c)| letter_index(c).map(|(index, _)| index).ok_or(CrypError::InvalidEncoding { encoding: "Vigenère key", pos, char: c }))
+        .collect()
+}
+
+/// Encrypts `text` with the Vigenère cipher: the n-th letter is shifted by the n-th letter of
+/// the repeating `key` (`A` shifts by 0). Non-letters don't use up key letters.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `key` is empty.
+/// - `CrypError::InvalidEncoding` if `key` contains anything but ASCII letters.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{vigenere_decrypt, vigenere_encrypt};
+///
+/// assert_eq!(vigenere_encrypt("Attack at dawn!", "LEMON").unwrap(), "Lxfopv ef rnhr!");
+/// assert_eq!(vigenere_decrypt("Lxfopv ef rnhr!", "lemon").unwrap(), "Attack at dawn!");
+/// ```
+pub fn vigenere_encrypt(text: &str, key: &str) -> Result<String, CrypError> {
+    let shifts = vigenere_shifts(key)?;
+    Ok(map_letters(text, |p, n| (p + shifts[n % shifts.len()]) % ALPHABET_SIZE as u8))
+}
+
+/// Decrypts a Vigenère ciphertext; see `vigenere_encrypt`.
+///
+/// # Errors
+/// - As `vigenere_encrypt`.
+pub fn vigenere_decrypt(text: &str, key: &str) -> Result<String, CrypError> {
+    let shifts = vigenere_shifts(key)?;
+    Ok(map_letters(text, |c, n| (c + ALPHABET_SIZE as u8 - shifts[n % shifts.len()]) % ALPHABET_SIZE as u8))
+}
+
+/// Encrypts `text` with the affine cipher, mapping letter `x` to `a * x + b` modulo 26.
+///
+/// # Errors
+/// - `CrypError::NotInvertible` if `a` shares a factor with 26, since the mapping could not be
+///   undone.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{affine_decrypt, affine_encrypt};
+///
+/// assert_eq!(affine_encrypt("Affine cipher", 5, 8).unwrap(), "Ihhwvc swfrcp");
+/// assert_eq!(affine_decrypt("Ihhwvc swfrcp", 5, 8).unwrap(), "Affine cipher");
+/// assert!(affine_encrypt("Affine cipher", 13, 8).is_err());
+/// ```
+pub fn affine_encrypt(text: &str, a: u8, b: u8) -> Result<String, CrypError> {
+    let n = ALPHABET_SIZE as u32;
+    mod_inverse(a as i64, n as i64).ok_or(CrypError::NotInvertible { value: a as i64, modulus: ALPHABET_SIZE })?;
+    Ok(map_letters(text, |x, _| ((a as u32 * x as u32 + b as u32) % n) as u8))
+}
+
+/// Decrypts an affine ciphertext; see `affine_encrypt`.
+///
+/// # Errors
+/// - As `affine_encrypt`.
+pub fn affine_decrypt(text: &str, a: u8, b: u8) -> Result<String, CrypError> {
+    let n = ALPHABET_SIZE as i64;
+    let inverse = mod_inverse(a as i64, n).ok_or(CrypError::NotInvertible { value: a as i64, modulus: ALPHABET_SIZE })?;
+    Ok(map_letters(text, |y, _| (inverse * (y as i64 - b as i64)).rem_euclid(n) as u8))
+}
+
+/// Parses a substitution key: 26 distinct letters, the n-th being the ciphertext letter for
+/// the n-th letter of the alphabet. Returns the forward mapping as alphabet indices.
+fn substitution_key(key: &str) -> Result<[u8; ALPHABET_SIZE], CrypError> {
+    let letters: Vec<char> = key.chars().collect();
+    if letters.len() != ALPHABET_SIZE {
+        return Err(CrypError::InvalidLength { encoding: "substitution key", len: letters.len() });
+    }
+
+    let mut forward = [0; ALPHABET_SIZE];
+    let mut seen = [false; ALPHABET_SIZE];
+    for (pos, &c) in letters.iter().enumerate() {
+        match letter_index(c) {
+            Some((index, _)) if !seen[index as usize] => {
+                seen[index as usize] = true;
+                forward[pos] = index;
+            }
+            _ => return Err(CrypError::InvalidEncoding { encoding: "substitution key", pos, char: c }),
+        }
+    }
+    Ok(forward)
+}
+
+/// Encrypts `text` with a simple substitution: `key` lists the ciphertext letter for `A`,
+/// `B`, ... `Z`.
+///
+/// # Errors
+/// - `CrypError::InvalidLength` if `key` is not 26 characters long.
+/// - `CrypError::InvalidEncoding` at the first character of `key` that is not a letter or
+///   repeats an earlier one.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{substitution_decrypt, substitution_encrypt};
+///
+/// let key = "QWERTYUIOPASDFGHJKLZXCVBNM";
+/// assert_eq!(substitution_encrypt("Hello, world", key).unwrap(), "Itssg, vgksr");
+/// assert_eq!(substitution_decrypt("Itssg, vgksr", key).unwrap(), "Hello, world");
+/// assert!(substitution_encrypt("Hello", "QWERTYUIOPASDFGHJKLZXCVBNQ").is_err());
+/// ```
+pub fn substitution_encrypt(text: &str, key: &str) -> Result<String, CrypError> {
+    let forward = substitution_key(key)?;
+    Ok(map_letters(text, |p, _| forward[p as usize]))
+}
+
+/// Decrypts a simple substitution ciphertext; see `substitution_encrypt`.
+///
+/// # Errors
+/// - As `substitution_encrypt`.
+pub fn substitution_decrypt(text: &str, key: &str) -> Result<String, CrypError> {
+    let forward = substitution_key(key)?;
+    let mut backward = [0; ALPHABET_SIZE];
+    for (plain, &cipher) in forward.iter().enumerate() {
+        backward[cipher as usize] = plain as u8;
+    }
+    Ok(map_letters(text, |c, _| backward[c as usize]))
+}
+
+/// Breaks a Caesar cipher by trying all 26 shifts and keeping the plaintext `scorer` likes
+/// best.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{break_caesar, caesar_encrypt};
+/// use cryp::score::LogLikelihood;
+///
+/// let ciphertext = caesar_encrypt("The die is cast, said Caesar as he crossed the Rubicon", 13);
+/// let solution = break_caesar(&ciphertext, &LogLikelihood);
+/// assert_eq!(solution.key, 13);
+/// assert!(solution.plaintext.starts_with("The die is cast"));
+/// ```
+pub fn break_caesar(text: &str, scorer: &dyn Scorer) -> Solution<u8> {
+    best_solution((0..ALPHABET_SIZE as u8).map(|shift| (shift, caesar_decrypt(text, shift))), scorer)
+}
+
+/// Breaks an affine cipher by trying all 312 keys `(a, b)` and keeping the plaintext `scorer`
+/// likes best.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{affine_encrypt, break_affine};
+/// use cryp::score::LogLikelihood;
+///
+/// let ciphertext = affine_encrypt("Meet me at the usual place at ten rather than eight", 17, 20).unwrap();
+/// assert_eq!(break_affine(&ciphertext, &LogLikelihood).key, (17, 20));
+/// ```
+pub fn break_affine(text: &str, scorer: &dyn Scorer) -> Solution<(u8, u8)> {
+    let keys = (1..ALPHABET_SIZE as u8)
+        .filter(|&a| mod_inverse(a as i64, ALPHABET_SIZE as i64).is_some())
+        .flat_map(|a| (0..ALPHABET_SIZE as u8).map(move |b| (a, b)));
+    best_solution(
+        keys.map(|(a, b)| ((a, b), affine_decrypt(text, a, b).expect("a is invertible"))),
+        scorer,
+    )
+}
+
+/// The candidate whose plaintext scores best, keeping the first one on ties.
+fn best_solution<K>(candidates: impl Iterator<Item = (K, String)>, scorer: &dyn Scorer) -> Solution<K> {
+    candidates
+        .map(|(key, plaintext)| {
+            let score = scorer.score(plaintext.as_bytes());
+            Solution { key, plaintext, score }
+        })
+        .reduce(|best, candidate| if candidate.score > best.score { candidate } else { best })
+        .expect("at least one candidate key")
+}
+
+/// Breaks a Vigenère cipher: estimates the period from the letters of `text` with
+/// `estimate_key_len`, then breaks every column as a Caesar cipher, ranking the shifts with
+/// `scorer` on the lowercased column.
+///
+/// # Errors
+/// - As `estimate_key_len` on the letters of `text`: `CrypError::NotEnoughData` if there are
+///   fewer than `2 * min_period` letters, `CrypError::InvalidRange` for a bad range.
+pub fn break_vigenere(text: &str, min_period: usize, max_period: usize, scorer: &dyn Scorer) -> Result<Solution<String>, CrypError> {
+    let letters = letter_indices(text);
+    let uppercase: Vec<u8> = letters.iter().map(|&index| b'A' + index).collect();
+    let period = estimate_key_len(&uppercase, min_period, max_period)?[0].0;
+
+    let key: String = (0..period)
+        .map(|i| {
+            let column: Vec<u8> = letters.iter().skip(i).step_by(period).cloned().collect();
+            let (shift, _) = (0..ALPHABET_SIZE as u8)
+                .map(|shift| {
+                    let plain: Vec<u8> = column.iter()
+                        .map(|&c| b'a' + (c + ALPHABET_SIZE as u8 - shift) % ALPHABET_SIZE as u8)
+                        .collect();
+                    (shift, scorer.score(&plain))
+                })
+                .fold((0, f64::NEG_INFINITY), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+            index_letter(shift, true)
+        })
+        .collect();
+
+    let plaintext = vigenere_decrypt(text, &key)?;
+    let score = scorer.score(plaintext.as_bytes());
+    Ok(Solution { key, plaintext, score })
+}
+
+/// Settings of the simulated annealing solvers.
+///
+/// Each of `restarts` runs starts from a fresh key and makes `iterations` random changes. A
+/// change that lowers the fitness by `d` is still accepted with probability `exp(-d / T)`,
+/// where the temperature `T` falls linearly from `temperature` to 0 over the run; a
+/// temperature of 0 makes the search plain hill climbing. `seed` makes runs reproducible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnnealOptions {
+    pub restarts: usize,
+    pub iterations: usize,
+    pub temperature: f64,
+    pub seed: u64,
+}
+
+impl AnnealOptions {
+    pub const DEFAULT: AnnealOptions = AnnealOptions { restarts: 6, iterations: 20_000, temperature: 4.0, seed: 0x5eed };
+
+    /// Longer, hotter runs for `break_playfair`, tuned on ciphertexts of about 500 letters.
+    pub const PLAYFAIR: AnnealOptions = AnnealOptions { restarts: 6, iterations: 100_000, temperature: 20.0, seed: 0x5eed };
+
+    /// Returns the same settings with a different seed.
+    pub const fn with_seed(self, seed: u64) -> Self {
+        AnnealOptions { seed, ..self }
+    }
+}
+
+impl Default for AnnealOptions {
+    fn default() -> Self {
+        AnnealOptions::DEFAULT
+    }
+}
+
+/// A small xorshift generator, so that the solvers are reproducible without a dependency.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        // Mix the seed so that small seeds don't start with a run of zero bits.
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A uniform integer below `n`.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A uniform float in `[0, 1)`.
+    pub(crate) fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Shuffles `items` in place (Fisher-Yates).
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Simulated annealing (see `AnnealOptions`): `initial` makes the starting key of every
+/// restart, `neighbour` a random variation of a key, and `fitness` scores a key. Returns the
+/// fittest key seen.
+pub(crate) fn anneal<K: Clone>(
+    options: &AnnealOptions,
+    mut initial: impl FnMut(usize, &mut Rng) -> K,
+    mut neighbour: impl FnMut(&K, &mut Rng) -> K,
+    mut fitness: impl FnMut(&K) -> f64,
+) -> (K, f64) {
+    let mut rng = Rng::new(options.seed);
+    let mut best: Option<(K, f64)> = None;
+
+    for restart in 0..options.restarts.max(1) {
+        let mut key = initial(restart, &mut rng);
+        let mut current = fitness(&key);
+        for i in 0..options.iterations {
+            let temperature = options.temperature * (1.0 - i as f64 / options.iterations as f64);
+            let candidate = neighbour(&key, &mut rng);
+            let score = fitness(&candidate);
+            let accept = score > current
+                || (temperature > 0.0 && rng.unit() < ((score - current) / temperature).exp());
+            if accept {
+                key = candidate;
+                current = score;
+            }
+            if best.as_ref().is_none_or(|(_, best)| current > *best) {
+                best = Some((key.clone(), current));
+            }
+        }
+        if best.as_ref().is_none_or(|(_, best)| current > *best) {
+            best = Some((key, current));
+        }
+    }
+    best.expect("at least one restart")
+}
+
+/// The log-probabilities of every letter n-gram of an `NgramModel`, in a flat table indexed by
+/// the gram's letters read as a base-26 number. Much faster to look up than the model itself.
+pub(crate) struct LetterFitness {
+    order: usize,
+    table: Vec<f64>,
+}
+
+impl LetterFitness {
+    pub(crate) fn new(model: &NgramModel) -> LetterFitness {
+        let order = model.order();
+        let table = (0..ALPHABET_SIZE.pow(order as u32))
+            .map(|mut index| {
+                let mut gram = vec![0; order];
+                for slot in gram.iter_mut().rev() {
+                    *slot = b'A' + (index % ALPHABET_SIZE) as u8;
+                    index /= ALPHABET_SIZE;
+                }
+                model.log_probability(&gram)
+            })
+            .collect();
+        LetterFitness { order, table }
+    }
+
+    /// The summed log-probability of the n-grams of `letters` (alphabet indices).
+    pub(crate) fn fitness(&self, letters: &[u8]) -> f64 {
+        letters.windows(self.order)
+            .map(|gram| self.table[gram.iter().fold(0, |index, &l| index * ALPHABET_SIZE + l as usize)])
+            .sum()
+    }
+}
+
+/// Checks that `model` counts letters, as the letter-based solvers need.
+fn check_letters(model: &NgramModel) -> Result<(), CrypError> {
+    match model.alphabet() {
+        NgramAlphabet::Letters => Ok(()),
+        other => Err(CrypError::WrongAlphabet { expected: NgramAlphabet::Letters.name(), got: other.name() }),
+    }
+}
+
+/// Breaks a simple substitution cipher by simulated annealing over keys, scoring candidate
+/// plaintexts with the letter n-grams of `model` (an order 3 or 4 `NgramAlphabet::Letters`
+/// model trained on text like the plaintext works best).
+///
+/// The first run starts from the key that maps the ciphertext letters by frequency onto
+/// English letters by frequency, the others from random keys; every step swaps two letters of
+/// the key.
+///
+/// # Returns
+/// - The key in the form `substitution_encrypt` takes, the plaintext, and its fitness
+///   (`NgramModel::fitness`).
+///
+/// # Errors
+/// - `CrypError::WrongAlphabet` unless `model` is an `NgramAlphabet::Letters` model.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{break_substitution, substitution_encrypt, AnnealOptions};
+/// use cryp::ngram::{NgramAlphabet, NgramModel};
+/// use cryp::CrypError;
+///
+/// let training = "It is a truth universally acknowledged, that a single man in possession of a good \
+///     fortune, must be in want of a wife. However little known the feelings or views of such a man \
+///     may be on his first entering a neighbourhood, this truth is so well fixed in the minds of the \
+///     surrounding families, that he is considered the rightful property of some one or other of \
+///     their daughters. My dear Mr. Bennet, said his lady to him one day, have you heard that \
+///     Netherfield Park is let at last? Mr. Bennet replied that he had not. But it is, returned she; \
+///     for Mrs. Long has just been here, and she told me all about it. Mr. Bennet made no answer. Do \
+///     you not want to know who has taken it? cried his wife impatiently. You want to tell me, and I \
+///     have no objection to hearing it. This was invitation enough. Why, my dear, you must know, \
+///     Mrs. Long says that Netherfield is taken by a young man of large fortune from the north of \
+///     England; that he came down on Monday in a chaise and four to see the place, and was so much \
+///     delighted with it, that he agreed with Mr. Morris immediately; that he is to take possession \
+///     before Michaelmas, and some of his servants are to be in the house by the end of next week.";
+/// let mut model = NgramModel::new(3, NgramAlphabet::Letters).unwrap().with_smoothing(0.1).unwrap();
+/// model.train(training.as_bytes());
+///
+/// let plaintext = "Whenever I find myself growing grim about the mouth; whenever it is a damp, drizzly \
+///     November in my soul; whenever I find myself involuntarily pausing before coffin warehouses, \
+///     and bringing up the rear of every funeral I meet; then, I account it high time to get to sea \
+///     as soon as I can. This is my substitute for pistol and ball.";
+/// let ciphertext = substitution_encrypt(plaintext, "PHQGIUMEAYLNOFDXJKRCVSTZWB").unwrap();
+///
+/// let solution = break_substitution(&ciphertext, &model, &AnnealOptions::DEFAULT).unwrap();
+/// // A model trained on so little text may prefer a slightly wrong key to the right one, but
+/// // the search finds a key at least as fit as the real one, and most letters are right.
+/// assert!(solution.score >= model.fitness(plaintext.as_bytes()));
+/// let correct = solution.plaintext.chars().zip(plaintext.chars()).filter(|(a, b)| a == b).count();
+/// assert!(correct * 10 >= plaintext.len() * 8);
+///
+/// let bytes = NgramModel::new(3, NgramAlphabet::Bytes).unwrap();
+/// let wrong = break_substitution(&ciphertext, &bytes, &AnnealOptions::DEFAULT);
+/// assert!(matches!(wrong, Err(CrypError::WrongAlphabet { .. })));
+/// ```
+pub fn break_substitution(text: &str, model: &NgramModel, options: &AnnealOptions) -> Result<Solution<String>, CrypError> {
+    check_letters(model)?;
+    let letters = letter_indices(text);
+    let scorer = LetterFitness::new(model);
+
+    // Ciphertext letters from most to least frequent.
+    let mut counts = [0usize; ALPHABET_SIZE];
+    for &l in &letters {
+        counts[l as usize] += 1;
+    }
+    let mut by_frequency: Vec<u8> = (0..ALPHABET_SIZE as u8).collect();
+    by_frequency.sort_by_key(|&l| std::cmp::Reverse(counts[l as usize]));
+
+    // Keys are decryption tables: `key[c]` is the plaintext letter for ciphertext letter `c`.
+    let decrypt = |key: &[u8; ALPHABET_SIZE]| -> Vec<u8> { letters.iter().map(|&c| key[c as usize]).collect() };
+    let (key, score) = anneal(
+        options,
+        |restart, rng| {
+            let mut key = [0; ALPHABET_SIZE];
+            if restart == 0 {
+                for (&cipher, &plain) in by_frequency.iter().zip(ENGLISH_BY_FREQUENCY) {
+                    key[cipher as usize] = plain - b'A';
+                }
+            } else {
+                key = std::array::from_fn(|i| i as u8);
+                rng.shuffle(&mut key);
+            }
+            key
+        },
+        |key, rng| {
+            let mut key = *key;
+            let (a, b) = (rng.below(ALPHABET_SIZE), rng.below(ALPHABET_SIZE));
+            key.swap(a, b);
+            key
+        },
+        |key| scorer.fitness(&decrypt(key)),
+    );
+
+    let mut encryption = [b'A'; ALPHABET_SIZE];
+    for (cipher, &plain) in key.iter().enumerate() {
+        encryption[plain as usize] = b'A' + cipher as u8;
+    }
+    let key = String::from_utf8(encryption.to_vec()).expect("key is ASCII");
+    let plaintext = substitution_decrypt(text, &key).expect("key is a permutation");
+    Ok(Solution { key, plaintext, score })
+}
+
+/// Column widths up to which `break_columnar` tries every column order instead of annealing.
+const EXHAUSTIVE_COLUMNS: usize = 7;
+
+/// The order in which a keyword reads the columns of a columnar transposition: alphabetically
+/// by letter, repeated letters from left to right. Returns the column indices in reading order.
+fn column_order(keyword: &str) -> Result<Vec<usize>, CrypError> {
+    if keyword.is_empty() {
+        return Err(CrypError::EmptyKey);
+    }
+    let letters: Vec<u8> = keyword.chars()
+        .enumerate()
+        .map(|(pos, c)| letter_index(c).map(|(index, _)| index).ok_or(CrypError::InvalidEncoding { encoding: "transposition key", pos, char: c }))
+        .collect::<Result<_, _>>()?;
+    let mut order: Vec<usize> = (0..letters.len()).collect();
+    order.sort_by_key(|&column| letters[column]);
+    Ok(order)
+}
+
+/// The shortest keyword with the column reading `order`: `A` for the first column read, `B`
+/// for the second and so on.
+fn order_keyword(order: &[usize]) -> String {
+    let mut keyword = vec![b'A'; order.len()];
+    for (rank, &column) in order.iter().enumerate() {
+        keyword[column] = b'A' + rank as u8;
+    }
+    String::from_utf8(keyword).expect("keyword is ASCII")
+}
+
+/// The lengths of the columns of `len` characters written in rows of `columns`: the first
+/// `len % columns` columns are one longer.
+fn column_lengths(len: usize, columns: usize) -> Vec<usize> {
+    (0..columns).map(|column| len / columns + usize::from(column < len % columns)).collect()
+}
+
+fn columnar_decrypt_order(text: &[char], order: &[usize]) -> String {
+    let columns = order.len();
+    let lengths = column_lengths(text.len(), columns);
+    let mut starts = vec![0; columns];
+    let mut next = 0;
+    for &column in order {
+        starts[column] = next;
+        next += lengths[column];
+    }
+    (0..text.len()).map(|i| text[starts[i % columns] + i / columns]).collect()
+}
+
+/// Encrypts `text` with a columnar transposition: the text is written in rows as wide as
+/// `keyword`, and the columns are read top to bottom in the alphabetical order of the
+/// keyword's letters (repeated letters from left to right). Every character is moved,
+/// including spaces and punctuation, and a short last row is not padded.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `keyword` is empty.
+/// - `CrypError::InvalidEncoding` if `keyword` contains anything but ASCII letters.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{columnar_decrypt, columnar_encrypt};
+///
+/// let ciphertext = columnar_encrypt("WEAREDISCOVEREDFLEEATONCE", "ZEBRAS").unwrap();
+/// assert_eq!(ciphertext, "EVLNACDTESEAROFODEECWIREE");
+/// assert_eq!(columnar_decrypt(&ciphertext, "ZEBRAS").unwrap(), "WEAREDISCOVEREDFLEEATONCE");
+/// ```
+pub fn columnar_encrypt(text: &str, keyword: &str) -> Result<String, CrypError> {
+    let order = column_order(keyword)?;
+    let text: Vec<char> = text.chars().collect();
+    Ok(order.iter()
+        .flat_map(|&column| text.iter().skip(column).step_by(order.len()))
+        .collect())
+}
+
+/// Decrypts a columnar transposition; see `columnar_encrypt`.
+///
+/// # Errors
+/// - As `columnar_encrypt`.
+pub fn columnar_decrypt(text: &str, keyword: &str) -> Result<String, CrypError> {
+    let order = column_order(keyword)?;
+    Ok(columnar_decrypt_order(&text.chars().collect::<Vec<_>>(), &order))
+}
+
+/// Breaks a columnar transposition with `min_columns` to `max_columns` columns by anagramming:
+/// every width up to 7 columns is solved by trying all column orders, wider ones by simulated
+/// annealing over the order (swapping and moving columns) with `options`.
+///
+/// Transposition keeps the letter frequencies, so `scorer` has to look at more than single
+/// characters: use an `NgramModel` of order 2 or more.
+///
+/// # Returns
+/// - The shortest keyword for the column order (see `columnar_encrypt`), the plaintext and its
+///   score.
+///
+/// # Errors
+/// - `CrypError::InvalidRange` unless `2 <= min_columns <= max_columns <= 26`.
+/// - `CrypError::NotEnoughData` if `text` is shorter than `2 * min_columns` characters.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{break_columnar, columnar_encrypt, AnnealOptions};
+/// use cryp::input::lyrics_model;
+/// use cryp::ngram::NgramAlphabet;
+///
+/// let model = lyrics_model(3, NgramAlphabet::Bytes).unwrap();
+///
+/// let plaintext = "the quick brown fox jumps over the lazy dog while the band plays on";
+/// let ciphertext = columnar_encrypt(plaintext, "CIPHER").unwrap();
+/// let solution = break_columnar(&ciphertext, 2, 8, &model, &AnnealOptions::DEFAULT).unwrap();
+/// assert_eq!(solution.key, "ADECBF");
+/// assert_eq!(solution.plaintext, plaintext);
+/// ```
+pub fn break_columnar(text: &str, min_columns: usize, max_columns: usize, scorer: &dyn Scorer, options: &AnnealOptions) -> Result<Solution<String>, CrypError> {
+    if min_columns < 2 || min_columns > max_columns || max_columns > ALPHABET_SIZE {
+        return Err(CrypError::InvalidRange { min: min_columns, max: max_columns });
+    }
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() < 2 * min_columns {
+        return Err(CrypError::NotEnoughData { needed: 2 * min_columns, got: chars.len() });
+    }
+
+    let fitness = |order: &Vec<usize>| scorer.score(columnar_decrypt_order(&chars, order).as_bytes());
+    let mut best: Option<(Vec<usize>, f64)> = None;
+    for columns in min_columns..=max_columns.min(chars.len() / 2) {
+        let candidate = if columns <= EXHAUSTIVE_COLUMNS {
+            let mut order: Vec<usize> = (0..columns).collect();
+            let mut best = (order.clone(), fitness(&order));
+            while next_permutation(&mut order) {
+                let score = fitness(&order);
+                if score > best.1 {
+                    best = (order.clone(), score);
+                }
+            }
+            best
+        } else {
+            anneal(
+                options,
+                |_, rng| {
+                    let mut order: Vec<usize> = (0..columns).collect();
+                    rng.shuffle(&mut order);
+                    order
+                },
+                |order, rng| {
+                    let mut order = order.clone();
+                    let (from, to) = (rng.below(columns), rng.below(columns));
+                    if rng.below(2) == 0 {
+                        order.swap(from, to);
+                    } else {
+                        let column = order.remove(from);
+                        order.insert(to, column);
+                    }
+                    order
+                },
+                fitness,
+            )
+        };
+        if best.as_ref().is_none_or(|(_, best)| candidate.1 > *best) {
+            best = Some(candidate);
+        }
+    }
+
+    let (order, score) = best.expect("at least one width");
+    Ok(Solution { key: order_keyword(&order), plaintext: columnar_decrypt_order(&chars, &order), score })
+}
+
+/// Rearranges `items` into the next lexicographic permutation, returning false (and leaving
+/// them sorted) after the last one.
+fn next_permutation(items: &mut [usize]) -> bool {
+    let Some(pivot) = (1..items.len()).rev().find(|&i| items[i - 1] < items[i]).map(|i| i - 1) else {
+        items.reverse();
+        return false;
+    };
+    let successor = (pivot + 1..items.len()).rev().find(|&i| items[i] > items[pivot]).expect("a larger item");
+    items.swap(pivot, successor);
+    items[pivot + 1..].reverse();
+    true
+}
+
+/// The positions of `len` characters in rail fence order: rail by rail, left to right.
+fn rail_fence_order(len: usize, rails: usize) -> Vec<usize> {
+    let cycle = 2 * (rails - 1);
+    let rail = |i: usize| {
+        let phase = if cycle == 0 { 0 } else { i % cycle };
+        phase.min(cycle - phase)
+    };
+    let mut order: Vec<usize> = (0..len).collect();
+    order.sort_by_key(|&i| rail(i));
+    order
+}
+
+/// Encrypts `text` with the rail fence cipher: the characters are written in a zigzag over
+/// `rails` rows and read row by row. One rail leaves the text unchanged.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `rails` is 0.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{rail_fence_decrypt, rail_fence_encrypt};
+///
+/// let ciphertext = rail_fence_encrypt("WEAREDISCOVEREDFLEEATONCE", 3).unwrap();
+/// assert_eq!(ciphertext, "WECRLTEERDSOEEFEAOCAIVDEN");
+/// assert_eq!(rail_fence_decrypt(&ciphertext, 3).unwrap(), "WEAREDISCOVEREDFLEEATONCE");
+/// ```
+pub fn rail_fence_encrypt(text: &str, rails: usize) -> Result<String, CrypError> {
+    if rails == 0 {
+        return Err(CrypError::EmptyKey);
+    }
+    let chars: Vec<char> = text.chars().collect();
+    Ok(rail_fence_order(chars.len(), rails).into_iter().map(|i| chars[i]).collect())
+}
+
+/// Decrypts a rail fence ciphertext; see `rail_fence_encrypt`.
+///
+/// # Errors
+/// - As `rail_fence_encrypt`.
+pub fn rail_fence_decrypt(text: &str, rails: usize) -> Result<String, CrypError> {
+    if rails == 0 {
+        return Err(CrypError::EmptyKey);
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut plain = vec![' '; chars.len()];
+    for (&position, &c) in rail_fence_order(chars.len(), rails).iter().zip(&chars) {
+        plain[position] = c;
+    }
+    Ok(plain.into_iter().collect())
+}
+
+/// Breaks a rail fence cipher by trying 2 to `max_rails` rails and keeping the plaintext
+/// `scorer` likes best. Like any transposition this needs an n-gram scorer (see
+/// `break_columnar`).
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{break_rail_fence, rail_fence_encrypt};
+/// use cryp::input::lyrics_model;
+/// use cryp::ngram::NgramAlphabet;
+///
+/// let model = lyrics_model(3, NgramAlphabet::Bytes).unwrap();
+///
+/// let ciphertext = rail_fence_encrypt("we are discovered so flee at once", 4).unwrap();
+/// let solution = break_rail_fence(&ciphertext, 10, &model);
+/// assert_eq!(solution.key, 4);
+/// assert_eq!(solution.plaintext, "we are discovered so flee at once");
+/// ```
+pub fn break_rail_fence(text: &str, max_rails: usize, scorer: &dyn Scorer) -> Solution<usize> {
+    best_solution(
+        (2..=max_rails.max(2)).map(|rails| (rails, rail_fence_decrypt(text, rails).expect("at least two rails"))),
+        scorer,
+    )
+}
+
+/// Side of the Playfair key square.
+const PLAYFAIR_SIDE: usize = 5;
+
+/// The alphabet index of `J`, which Playfair merges into `I`.
+const LETTER_J: u8 = 9;
+
+/// The letter Playfair inserts between doubled letters and pads odd texts with, and the one it
+/// uses instead when that letter itself is doubled.
+const PLAYFAIR_FILLERS: (u8, u8) = (b'X' - b'A', b'Q' - b'A');
+
+/// The alphabet indices of the letters of `text` with `J` read as `I`.
+fn playfair_letters(text: &str) -> Vec<u8> {
+    letter_indices(text).into_iter().map(|l| if l == LETTER_J { LETTER_J - 1 } else { l }).collect()
+}
+
+/// The key square of `keyword`, row by row: its distinct letters followed by the rest of the
+/// alphabet, without `J`. A 25-letter square is its own keyword.
+fn playfair_square(keyword: &str) -> Result<[u8; PLAYFAIR_SIDE * PLAYFAIR_SIDE], CrypError> {
+    if let Some((pos, c)) = keyword.chars().enumerate().find(|&(_, c)| letter_index(c).is_none()) {
+        return Err(CrypError::InvalidEncoding { encoding: "Playfair key", pos, char: c });
+    }
+    let mut seen = [false; ALPHABET_SIZE];
+    seen[LETTER_J as usize] = true;
+    let letters = playfair_letters(keyword).into_iter().chain(0..ALPHABET_SIZE as u8);
+    let square: Vec<u8> = letters.filter(|&l| !std::mem::replace(&mut seen[l as usize], true)).collect();
+    Ok(square.try_into().expect("25 letters"))
+}
+
+/// Encrypts (`shift` 1) or decrypts (`shift` 4) pairs of letters with a key square.
+fn playfair_apply(square: &[u8; PLAYFAIR_SIDE * PLAYFAIR_SIDE], letters: &[u8], shift: usize) -> Vec<u8> {
+    let mut position = [(0, 0); ALPHABET_SIZE];
+    for (i, &l) in square.iter().enumerate() {
+        position[l as usize] = (i / PLAYFAIR_SIDE, i % PLAYFAIR_SIDE);
+    }
+    let at = |row: usize, column: usize| square[row % PLAYFAIR_SIDE * PLAYFAIR_SIDE + column % PLAYFAIR_SIDE];
+
+    letters.chunks_exact(2)
+        .flat_map(|pair| {
+            let ((r1, c1), (r2, c2)) = (position[pair[0] as usize], position[pair[1] as usize]);
+            if r1 == r2 {
+                [at(r1, c1 + shift), at(r2, c2 + shift)]
+            } else if c1 == c2 {
+                [at(r1 + shift, c1), at(r2 + shift, c2)]
+            } else {
+                [at(r1, c2), at(r2, c1)]
+            }
+        })
+        .collect()
+}
+
+fn letters_string(letters: &[u8], upper: bool) -> String {
+    letters.iter().map(|&l| index_letter(l, upper)).collect()
+}
+
+/// Encrypts `text` with the Playfair cipher. The letters of `text` (with `J` as `I`) are split
+/// into pairs, with an `X` between the letters of a doubled pair (`Q` for a doubled `X`) and
+/// at the end of an odd text, and every pair is replaced using the key square of `keyword`:
+/// its letters without repeats, then the rest of the alphabet. Returns uppercase letters only.
+///
+/// # Errors
+/// - `CrypError::InvalidEncoding` if `keyword` contains anything but ASCII letters.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{playfair_decrypt, playfair_encrypt};
+///
+/// let ciphertext = playfair_encrypt("Hide the gold in the tree stump", "playfairexample").unwrap();
+/// assert_eq!(ciphertext, "BMODZBXDNABEKUDMUIXMMOUVIF");
+/// assert_eq!(playfair_decrypt(&ciphertext, "playfairexample").unwrap(), "hidethegoldinthetrexestump");
+/// ```
+pub fn playfair_encrypt(text: &str, keyword: &str) -> Result<String, CrypError> {
+    let square = playfair_square(keyword)?;
+    let mut letters = Vec::new();
+    for l in playfair_letters(text) {
+        if letters.len() % 2 == 1 && letters.last() == Some(&l) {
+            letters.push(if l == PLAYFAIR_FILLERS.0 { PLAYFAIR_FILLERS.1 } else { PLAYFAIR_FILLERS.0 });
+        }
+        letters.push(l);
+    }
+    if letters.len() % 2 == 1 {
+        let last = letters[letters.len() - 1];
+        letters.push(if last == PLAYFAIR_FILLERS.0 { PLAYFAIR_FILLERS.1 } else { PLAYFAIR_FILLERS.0 });
+    }
+    Ok(letters_string(&playfair_apply(&square, &letters, 1), true))
+}
+
+/// Decrypts a Playfair ciphertext; see `playfair_encrypt`. Returns lowercase letters, with the
+/// filler letters left in.
+///
+/// # Errors
+/// - `CrypError::InvalidEncoding` if `keyword` contains anything but ASCII letters.
+/// - `CrypError::InvalidLength` if `text` has an odd number of letters.
+pub fn playfair_decrypt(text: &str, keyword: &str) -> Result<String, CrypError> {
+    let square = playfair_square(keyword)?;
+    let letters = playfair_letters(text);
+    if letters.len() % 2 == 1 {
+        return Err(CrypError::InvalidLength { encoding: "Playfair ciphertext", len: letters.len() });
+    }
+    Ok(letters_string(&playfair_apply(&square, &letters, PLAYFAIR_SIDE - 1), false))
+}
+
+/// Breaks a Playfair cipher by simulated annealing over key squares, scoring candidate
+/// plaintexts with the letter n-grams of `model` as `break_substitution` does. Most steps swap
+/// two letters of the square; the rest swap two rows or columns, or flip or transpose the
+/// whole square. Playfair needs far more work than substitution: use
+/// `AnnealOptions::PLAYFAIR` and a few hundred letters of ciphertext, and more restarts or
+/// another seed when the plaintext still looks garbled.
+///
+/// # Returns
+/// - The key square as 25 letters row by row (a valid keyword for `playfair_decrypt`), the
+///   plaintext and its fitness.
+///
+/// # Errors
+/// - `CrypError::WrongAlphabet` unless `model` is an `NgramAlphabet::Letters` model.
+/// - `CrypError::InvalidLength` if `text` has an odd number of letters.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{break_playfair, playfair_decrypt, playfair_encrypt, AnnealOptions};
+/// use cryp::input::{lyrics, lyrics_model};
+/// use cryp::ngram::{NgramAlphabet, NgramModel};
+/// use cryp::CrypError;
+///
+/// let lyrics = lyrics().unwrap();
+/// let model = lyrics_model(3, NgramAlphabet::Letters).unwrap();
+///
+/// // About 400 letters of the lyrics the model was trained on.
+/// let verse: String = String::from_utf8_lossy(&lyrics).chars().skip(1200).take(500).collect();
+/// let ciphertext = playfair_encrypt(&verse, "funkymusic").unwrap();
+/// let expected = playfair_decrypt(&ciphertext, "funkymusic").unwrap();
+///
+/// // Fewer restarts and iterations than `AnnealOptions::PLAYFAIR`, to keep the example quick.
+/// let options = AnnealOptions { restarts: 2, iterations: 50_000, ..AnnealOptions::PLAYFAIR };
+/// let solution = break_playfair(&ciphertext, &model, &options).unwrap();
+/// assert!(solution.score >= model.fitness(expected.as_bytes()));
+/// let correct = solution.plaintext.chars().zip(expected.chars()).filter(|(a, b)| a == b).count();
+/// assert!(correct * 10 >= expected.len() * 8);
+///
+/// let bytes = NgramModel::new(3, NgramAlphabet::Bytes).unwrap();
+/// assert!(matches!(break_playfair(&ciphertext, &bytes, &options), Err(CrypError::WrongAlphabet { .. })));
+/// ```
+pub fn break_playfair(text: &str, model: &NgramModel, options: &AnnealOptions) -> Result<Solution<String>, CrypError> {
+    const SQUARE: usize = PLAYFAIR_SIDE * PLAYFAIR_SIDE;
+    check_letters(model)?;
+    let letters = playfair_letters(text);
+    if letters.len() % 2 == 1 {
+        return Err(CrypError::InvalidLength { encoding: "Playfair ciphertext", len: letters.len() });
+    }
+    let scorer = LetterFitness::new(model);
+    let decrypt = |square: &[u8; SQUARE]| playfair_apply(square, &letters, PLAYFAIR_SIDE - 1);
+
+    let (square, score) = anneal(
+        options,
+        |_, rng| {
+            let mut square = playfair_square("").expect("empty keyword");
+            rng.shuffle(&mut square);
+            square
+        },
+        |square, rng| {
+            let mut square = *square;
+            let (a, b) = (rng.below(PLAYFAIR_SIDE), rng.below(PLAYFAIR_SIDE));
+            match rng.below(50) {
+                0 => (0..PLAYFAIR_SIDE).for_each(|c| square.swap(a * PLAYFAIR_SIDE + c, b * PLAYFAIR_SIDE + c)),
+                1 => (0..PLAYFAIR_SIDE).for_each(|r| square.swap(r * PLAYFAIR_SIDE + a, r * PLAYFAIR_SIDE + b)),
+                2 => square.reverse(),
+                3 => square = std::array::from_fn(|i| square[i % PLAYFAIR_SIDE * PLAYFAIR_SIDE + i / PLAYFAIR_SIDE]),
+                _ => square.swap(rng.below(SQUARE), rng.below(SQUARE)),
+            }
+            square
+        },
+        |square| scorer.fitness(&decrypt(square)),
+    );
+
+    let key = letters_string(&square, true);
+    Ok(Solution { key, plaintext: letters_string(&decrypt(&square), false), score })
+}
+
+/// Candidate rows of the decryption matrix that `break_hill_2x2` combines.
+const HILL_ROW_CANDIDATES: usize = 26;
+
+/// The side of a square Hill key given row by row, with its entries reduced modulo 26.
+fn hill_matrix(key: &[u8]) -> Result<(Vec<i64>, usize), CrypError> {
+    let n = (1..=key.len()).find(|n| n * n >= key.len()).unwrap_or(0);
+    if n == 0 || n * n != key.len() {
+        return Err(CrypError::InvalidLength { encoding: "Hill key", len: key.len() });
+    }
+    Ok((key.iter().map(|&k| k as i64 % ALPHABET_SIZE as i64).collect(), n))
+}
+
+/// The determinant modulo 26 of the `n` x `n` matrix `m`, by cofactor expansion.
+fn determinant(m: &[i64], n: usize) -> i64 {
+    if n == 1 {
+        return m[0].rem_euclid(ALPHABET_SIZE as i64);
+    }
+    (0..n)
+        .map(|column| {
+            let sign = if column % 2 == 0 { 1 } else { -1 };
+            sign * m[column] * determinant(&minor(m, n, 0, column), n - 1)
+        })
+        .sum::<i64>()
+        .rem_euclid(ALPHABET_SIZE as i64)
+}
+
+/// `m` without row `row` and column `column`.
+fn minor(m: &[i64], n: usize, row: usize, column: usize) -> Vec<i64> {
+    (0..n * n).filter(|i| i / n != row && i % n != column).map(|i| m[i]).collect()
+}
+
+/// The inverse modulo 26 of the `n` x `n` matrix `m` (the adjugate divided by the determinant).
+///
+/// # Errors
+/// - `CrypError::NotInvertible` with the determinant if it shares a factor with 26.
+fn inverse_matrix(m: &[i64], n: usize) -> Result<Vec<i64>, CrypError> {
+    let det = determinant(m, n);
+    let inverse = mod_inverse(det, ALPHABET_SIZE as i64).ok_or(CrypError::NotInvertible { value: det, modulus: ALPHABET_SIZE })?;
+    if n == 1 {
+        return Ok(vec![inverse]);
+    }
+    Ok((0..n * n)
+        .map(|i| {
+            let (row, column) = (i / n, i % n);
+            let sign = if (row + column) % 2 == 0 { 1 } else { -1 };
+            (inverse * sign * determinant(&minor(m, n, column, row), n - 1)).rem_euclid(ALPHABET_SIZE as i64)
+        })
+        .collect())
+}
+
+/// The product modulo 26 of the `n` x `n` matrices `a` and `b`.
+fn multiply(a: &[i64], b: &[i64], n: usize) -> Vec<i64> {
+    (0..n * n)
+        .map(|i| (0..n).map(|k| a[i / n * n + k] * b[k * n + i % n]).sum::<i64>().rem_euclid(ALPHABET_SIZE as i64))
+        .collect()
+}
+
+/// Multiplies every block of `n` letters by the matrix `m`.
+fn hill_apply(m: &[i64], n: usize, letters: &[u8]) -> Vec<u8> {
+    letters.chunks_exact(n)
+        .flat_map(|block| {
+            (0..n).map(move |row| {
+                (0..n).map(|k| m[row * n + k] * block[k] as i64).sum::<i64>().rem_euclid(ALPHABET_SIZE as i64) as u8
+            })
+        })
+        .collect()
+}
+
+/// Encrypts `text` with the Hill cipher: every block of n letters, as a column vector, is
+/// multiplied by the `n` x `n` matrix `key` (given row by row) modulo 26. The letters are
+/// padded with `X` to a whole block; returns uppercase letters only.
+///
+/// # Errors
+/// - `CrypError::InvalidLength` if `key` is not a square matrix.
+/// - `CrypError::NotInvertible` if the determinant of `key` shares a factor with 26, as the
+///   ciphertext could not be decrypted.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{hill_decrypt, hill_encrypt};
+///
+/// let key = [6, 24, 1, 13, 16, 10, 20, 17, 15];
+/// assert_eq!(hill_encrypt("act", &key).unwrap(), "POH");
+/// assert_eq!(hill_decrypt("POH", &key).unwrap(), "act");
+/// assert!(hill_encrypt("act", &[2, 4, 6, 8]).is_err());
+/// ```
+pub fn hill_encrypt(text: &str, key: &[u8]) -> Result<String, CrypError> {
+    let (m, n) = hill_matrix(key)?;
+    inverse_matrix(&m, n)?;
+    let mut letters = letter_indices(text);
+    while !letters.len().is_multiple_of(n) {
+        letters.push(PLAYFAIR_FILLERS.0);
+    }
+    Ok(letters_string(&hill_apply(&m, n, &letters), true))
+}
+
+/// Decrypts a Hill ciphertext with the inverse of `key`; see `hill_encrypt`. Returns lowercase
+/// letters, with any padding left in.
+///
+/// # Errors
+/// - As `hill_encrypt`, and `CrypError::InvalidLength` if the letters of `text` don't fill
+///   whole blocks.
+pub fn hill_decrypt(text: &str, key: &[u8]) -> Result<String, CrypError> {
+    let (m, n) = hill_matrix(key)?;
+    let inverse = inverse_matrix(&m, n)?;
+    let letters = letter_indices(text);
+    if !letters.len().is_multiple_of(n) {
+        return Err(CrypError::InvalidLength { encoding: "Hill ciphertext", len: letters.len() });
+    }
+    Ok(letters_string(&hill_apply(&inverse, n, &letters), false))
+}
+
+/// Recovers an `n` x `n` Hill key from known plaintext: picks `n` plaintext blocks that form
+/// an invertible matrix P, and solves C = K P for the key K, C holding the matching
+/// ciphertext blocks. Only the letters of both texts are used, aligned from the start.
+///
+/// # Errors
+/// - `CrypError::NotEnoughData` if there are fewer than `n * n` letter pairs.
+/// - `CrypError::NotInvertible` if no `n` of the plaintext blocks form an invertible matrix;
+///   more known plaintext is needed.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{hill_encrypt, hill_key_from_known_plaintext};
+///
+/// let key = [3, 3, 2, 5];
+/// let plaintext = "attack at dawn";
+/// let ciphertext = hill_encrypt(plaintext, &key).unwrap();
+/// assert_eq!(hill_key_from_known_plaintext(plaintext, &ciphertext, 2).unwrap(), key);
+/// ```
+pub fn hill_key_from_known_plaintext(plaintext: &str, ciphertext: &str, n: usize) -> Result<Vec<u8>, CrypError> {
+    let (plain, cipher) = (letter_indices(plaintext), letter_indices(ciphertext));
+    let blocks = plain.len().min(cipher.len()) / n.max(1);
+    if n == 0 || blocks < n {
+        return Err(CrypError::NotEnoughData { needed: n * n, got: plain.len().min(cipher.len()) });
+    }
+
+    // Stack the chosen blocks as the columns of P and C.
+    let columns = |letters: &[u8], chosen: &[usize]| -> Vec<i64> {
+        (0..n * n).map(|i| letters[chosen[i % n] * n + i / n] as i64).collect()
+    };
+    let mut chosen: Vec<usize> = (0..n).collect();
+    let first_det = determinant(&columns(&plain, &chosen), n);
+    loop {
+        if let Ok(inverse) = inverse_matrix(&columns(&plain, &chosen), n) {
+            let key = multiply(&columns(&cipher, &chosen), &inverse, n);
+            return Ok(key.into_iter().map(|k| k as u8).collect());
+        }
+        if !next_combination(&mut chosen, blocks) {
+            return Err(CrypError::NotInvertible { value: first_det, modulus: ALPHABET_SIZE });
+        }
+    }
+}
+
+/// Advances `chosen` (increasing indices below `limit`) to the next combination in
+/// lexicographic order, returning false after the last one.
+fn next_combination(chosen: &mut [usize], limit: usize) -> bool {
+    let k = chosen.len();
+    let Some(i) = (0..k).rev().find(|&i| chosen[i] < limit - k + i) else {
+        return false;
+    };
+    chosen[i] += 1;
+    for j in i + 1..k {
+        chosen[j] = chosen[j - 1] + 1;
+    }
+    true
+}
+
+/// Breaks a 2 x 2 Hill cipher from ciphertext alone. Each row of the decryption matrix
+/// produces every other plaintext letter on its own, so all 676 rows are ranked by how well
+/// `scorer` likes the letters they produce, and the invertible pairs of the top rows are
+/// scored on the whole plaintext. A scorer that sees letter pairs (an `NgramModel`) is needed
+/// to tell the two row orders apart.
+///
+/// # Returns
+/// - The encryption key row by row (as `hill_encrypt` takes it), the lowercase plaintext and
+///   its score.
+///
+/// # Errors
+/// - `CrypError::InvalidLength` if `text` has an odd number of letters.
+/// - `CrypError::NotInvertible` if no pair of the top rows forms an invertible matrix.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{break_hill_2x2, hill_encrypt};
+/// use cryp::input::lyrics_model;
+/// use cryp::ngram::NgramAlphabet;
+///
+/// let model = lyrics_model(2, NgramAlphabet::Letters).unwrap();
+///
+/// let plaintext = "sometimes the simplest ciphers are the hardest to break without the right tools";
+/// let ciphertext = hill_encrypt(plaintext, &[7, 8, 11, 11]).unwrap();
+/// let solution = break_hill_2x2(&ciphertext, &model).unwrap();
+/// assert_eq!(solution.key, [7, 8, 11, 11]);
+/// assert!(solution.plaintext.starts_with("sometimesthesimplest"));
+/// ```
+pub fn break_hill_2x2(text: &str, scorer: &dyn Scorer) -> Result<Solution<Vec<u8>>, CrypError> {
+    let letters = letter_indices(text);
+    if letters.len() % 2 == 1 {
+        return Err(CrypError::InvalidLength { encoding: "Hill ciphertext", len: letters.len() });
+    }
+
+    // A row sharing a factor with 26 can't belong to an invertible matrix, and its few
+    // distinct letters would fool the ranking.
+    let mut rows: Vec<([i64; 2], f64)> = (0..ALPHABET_SIZE * ALPHABET_SIZE)
+        .map(|i| [(i / ALPHABET_SIZE) as i64, (i % ALPHABET_SIZE) as i64])
+        .filter(|row| (row[0] % 2 == 1 || row[1] % 2 == 1) && (row[0] % 13 != 0 || row[1] % 13 != 0))
+        .map(|row| {
+            let plain: Vec<u8> = letters.chunks_exact(2)
+                .map(|block| b'a' + (row[0] * block[0] as i64 + row[1] * block[1] as i64).rem_euclid(ALPHABET_SIZE as i64) as u8)
+                .collect();
+            (row, scorer.score(&plain))
+        })
+        .collect();
+    rows.sort_by(|a, b| b.1.total_cmp(&a.1));
+    rows.truncate(HILL_ROW_CANDIDATES);
+
+    let candidates = rows.iter()
+        .flat_map(|(top, _)| rows.iter().map(move |(bottom, _)| vec![top[0], top[1], bottom[0], bottom[1]]))
+        .filter_map(|inverse| {
+            let key = inverse_matrix(&inverse, 2).ok()?;
+            Some((key.into_iter().map(|k| k as u8).collect::<Vec<u8>>(), letters_string(&hill_apply(&inverse, 2, &letters), false)))
+        });
+    let mut candidates = candidates.peekable();
+    if candidates.peek().is_none() {
+        return Err(CrypError::NotInvertible { value: 0, modulus: ALPHABET_SIZE });
+    }
+    Ok(best_solution(candidates, scorer))
+}
+
+/// Encrypts `text` with the autokey cipher: like Vigenère, but the key is `primer` followed by
+/// the plaintext itself, so it never repeats. Non-letters don't use up key letters.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `primer` is empty.
+/// - `CrypError::InvalidEncoding` if `primer` contains anything but ASCII letters.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{autokey_decrypt, autokey_encrypt};
+///
+/// assert_eq!(autokey_encrypt("Attack at dawn", "QUEENLY").unwrap(), "Qnxepv yt wtwp");
+/// assert_eq!(autokey_decrypt("Qnxepv yt wtwp", "QUEENLY").unwrap(), "Attack at dawn");
+/// ```
+pub fn autokey_encrypt(text: &str, primer: &str) -> Result<String, CrypError> {
+    let mut key = vigenere_shifts(primer)?;
+    Ok(map_letters(text, |p, n| {
+        key.push(p);
+        (p + key[n]) % ALPHABET_SIZE as u8
+    }))
+}
+
+/// Decrypts an autokey ciphertext; see `autokey_encrypt`.
+///
+/// # Errors
+/// - As `autokey_encrypt`.
+pub fn autokey_decrypt(text: &str, primer: &str) -> Result<String, CrypError> {
+    let mut key = vigenere_shifts(primer)?;
+    Ok(map_letters(text, |c, n| {
+        let p = (c + ALPHABET_SIZE as u8 - key[n]) % ALPHABET_SIZE as u8;
+        key.push(p);
+        p
+    }))
+}
+
+/// Symbols every chain of an autokey solution needs: shorter chains can be decrypted into
+/// something plausible from any ciphertext, so long primers would always win.
+pub(crate) const AUTOKEY_MIN_CHAIN: usize = 10;
+
+/// Solves an autokey cipher for one primer length. The letters `primer_len` apart form
+/// independent chains: the primer symbol at the head of a chain decrypts its first symbol,
+/// which decrypts the next one and so on. Every chain is decrypted with each of
+/// `candidates` and the one `scorer` likes best (after `render` maps the symbols to text) is
+/// kept. Returns the primer and the plaintext symbols.
+pub(crate) fn solve_autokey(
+    symbols: &[u8],
+    primer_len: usize,
+    candidates: &[u8],
+    decrypt: impl Fn(u8, u8) -> u8,
+    render: impl Fn(u8) -> u8,
+    scorer: &dyn Scorer,
+) -> (Vec<u8>, Vec<u8>) {
+    let mut primer = Vec::with_capacity(primer_len);
+    let mut plain = vec![0; symbols.len()];
+    for column in 0..primer_len {
+        let chain = |head: u8| -> Vec<u8> {
+            let mut key = head;
+            symbols.iter().skip(column).step_by(primer_len)
+                .map(|&c| {
+                    key = decrypt(c, key);
+                    key
+                })
+                .collect()
+        };
+        let (head, _) = candidates.iter()
+            .map(|&head| {
+                let text: Vec<u8> = chain(head).into_iter().map(&render).collect();
+                (head, scorer.score(&text))
+            })
+            .fold((candidates[0], f64::NEG_INFINITY), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+        primer.push(head);
+        for (i, p) in chain(head).into_iter().enumerate() {
+            plain[column + i * primer_len] = p;
+        }
+    }
+    (primer, plain)
+}
+
+/// Breaks an autokey cipher by trying every primer length from `min_primer` to `max_primer`:
+/// for each, the primer letters are chosen one chain at a time (see `solve_autokey`), and the
+/// length whose whole plaintext `scorer` likes best wins. Unlike Vigenère there is no period
+/// to measure, but a wrong length turns the whole plaintext into noise. Lengths that leave
+/// fewer than 10 letters per chain are skipped.
+///
+/// # Errors
+/// - `CrypError::InvalidRange` unless `1 <= min_primer <= max_primer`.
+/// - `CrypError::NotEnoughData` if `text` has fewer than `10 * min_primer` letters.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{autokey_encrypt, break_autokey};
+/// use cryp::score::LogLikelihood;
+/// use cryp::CrypError;
+///
+/// let plaintext = "It was the best of times, it was the worst of times, it was the age of wisdom, it was \
+///                  the age of foolishness, it was the epoch of belief, it was the epoch of incredulity";
+/// let ciphertext = autokey_encrypt(plaintext, "FORTUNE").unwrap();
+/// let solution = break_autokey(&ciphertext, 1, 12, &LogLikelihood).unwrap();
+/// assert_eq!(solution.key, "FORTUNE");
+/// assert_eq!(solution.plaintext, plaintext);
+///
+/// let huge = usize::MAX / 2;
+/// assert!(matches!(break_autokey(&ciphertext, huge, huge, &LogLikelihood), Err(CrypError::NotEnoughData { .. })));
+/// ```
+pub fn break_autokey(text: &str, min_primer: usize, max_primer: usize, scorer: &dyn Scorer) -> Result<Solution<String>, CrypError> {
+    if min_primer == 0 || min_primer > max_primer {
+        return Err(CrypError::InvalidRange { min: min_primer, max: max_primer });
+    }
+    let letters = letter_indices(text);
+    let needed = min_primer.saturating_mul(AUTOKEY_MIN_CHAIN);
+    if letters.len() < needed {
+        return Err(CrypError::NotEnoughData { needed, got: letters.len() });
+    }
+    let max_primer = max_primer.min(letters.len() / AUTOKEY_MIN_CHAIN);
+
+    let candidates: Vec<u8> = (0..ALPHABET_SIZE as u8).collect();
+    let decrypt = |c: u8, k: u8| (c + ALPHABET_SIZE as u8 - k) % ALPHABET_SIZE as u8;
+    let render = |p: u8| b'a' + p;
+    let (primer, score) = (min_primer..=max_primer)
+        .map(|len| {
+            let (primer, plain) = solve_autokey(&letters, len, &candidates, decrypt, render, scorer);
+            let text: Vec<u8> = plain.into_iter().map(render).collect();
+            (primer, scorer.score(&text))
+        })
+        .fold((Vec::new(), f64::NEG_INFINITY), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+
+    let key = letters_string(&primer, true);
+    let plaintext = autokey_decrypt(text, &key)?;
+    Ok(Solution { key, plaintext, score })
+}
+
+/// Encrypts `text` with a running key: like Vigenère, but the key is the letters of a text at
+/// least as long as the plaintext (typically a passage of a book), used once.
+///
+/// # Errors
+/// - `CrypError::NotEnoughData` if `key` has fewer letters than `text`.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{running_key_decrypt, running_key_encrypt};
+///
+/// let key = "Call me Ishmael. Some years ago - never mind how long precisely";
+/// assert_eq!(running_key_encrypt("Flee at once!", key).unwrap(), "Hlpp mx wfjq!");
+/// assert_eq!(running_key_decrypt("Hlpp mx wfjq!", key).unwrap(), "Flee at once!");
+/// ```
+pub fn running_key_encrypt(text: &str, key: &str) -> Result<String, CrypError> {
+    let key = running_key_letters(text, key)?;
+    Ok(map_letters(text, |p, n| (p + key[n]) % ALPHABET_SIZE as u8))
+}
+
+/// Decrypts a running key ciphertext; see `running_key_encrypt`.
+///
+/// # Errors
+/// - As `running_key_encrypt`.
+pub fn running_key_decrypt(text: &str, key: &str) -> Result<String, CrypError> {
+    let key = running_key_letters(text, key)?;
+    Ok(map_letters(text, |c, n| (c + ALPHABET_SIZE as u8 - key[n]) % ALPHABET_SIZE as u8))
+}
+
+fn running_key_letters(text: &str, key: &str) -> Result<Vec<u8>, CrypError> {
+    let key = letter_indices(key);
+    let needed = letter_indices(text).len();
+    if key.len() < needed {
+        return Err(CrypError::NotEnoughData { needed, got: key.len() });
+    }
+    Ok(key)
+}
+
+/// The last `n` symbols of a stream, packed into the low bytes (most recent lowest).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct History(u32);
+
+impl History {
+    fn push(self, symbol: u8, n: usize) -> History {
+        let mask = if n >= 4 { u32::MAX } else { (1u32 << (8 * n)) - 1 };
+        History(((self.0 << 8) | symbol as u32) & mask)
+    }
+
+    /// The `len` most recent symbols, oldest first, followed by `next`.
+    fn gram(self, len: usize, next: u8) -> ([u8; 4], usize) {
+        let mut gram = [0; 4];
+        for (i, slot) in gram[..len].iter_mut().enumerate() {
+            *slot = (self.0 >> (8 * (len - 1 - i))) as u8;
+        }
+        gram[len] = next;
+        (gram, len + 1)
+    }
+}
+
+/// Splits a ciphertext made of two text streams (a plaintext and a running key) with a beam
+/// search: position by position, every candidate symbol of the first stream fixes the second
+/// one through `decrypt`, and both are scored by `model` given the symbols before them.
+/// `candidates` lists the symbols either stream may contain (the second stream may leave them
+/// where nothing else fits), `render` maps symbols to the model's alphabet. States agreeing on
+/// the context the model sees are merged. Returns both streams and their summed
+/// log-probability, or `CrypError::InvalidArgument` for a beam width of 0.
+pub(crate) fn solve_running_key(
+    symbols: &[u8],
+    candidates: &[u8],
+    decrypt: impl Fn(u8, u8) -> u8,
+    render: impl Fn(u8) -> u8,
+    model: &NgramModel,
+    beam_width: usize,
+) -> Result<(Vec<u8>, Vec<u8>, f64), CrypError> {
+    #[derive(Clone, Copy)]
+    struct State {
+        node: usize,
+        first: History,
+        second: History,
+        score: f64,
+    }
+
+    if beam_width == 0 {
+        return Err(CrypError::InvalidArgument { name: "beam width", value: beam_width });
+    }
+    let context = model.order() - 1;
+    let mut allowed = [false; 256];
+    for &symbol in candidates {
+        allowed[symbol as usize] = true;
+    }
+    // The log-probability of `next` after the `seen` symbols of `history`.
+    let conditional = |history: History, seen: usize, next: u8| {
+        let len = seen.min(context);
+        let (gram, _) = history.gram(len, next);
+        model.conditional_log_probability(&gram[..len], next)
+    };
+
+    // Every step's chosen symbols, with a link to the node they extend.
+    let mut nodes: Vec<(usize, u8, u8)> = Vec::new();
+    let mut beam = vec![State { node: usize::MAX, first: History(0), second: History(0), score: 0.0 }];
+    for (seen, &c) in symbols.iter().enumerate() {
+        // A BTreeMap, so that ties (plaintext and key swapped score the same) break the same way
+        // on every run.
+        let mut next: std::collections::BTreeMap<(History, History), (State, u8, u8)> = std::collections::BTreeMap::new();
+        // If no pair of candidates fits this symbol, let the second stream take any value.
+        let strict = candidates.iter().any(|&a| allowed[decrypt(c, a) as usize]);
+        for state in &beam {
+            for &a in candidates {
+                let b = decrypt(c, a);
+                if strict && !allowed[b as usize] {
+                    continue;
+                }
+                let (ra, rb) = (render(a), render(b));
+                let score = state.score + conditional(state.first, seen, ra) + conditional(state.second, seen, rb);
+                let first = state.first.push(ra, context);
+                let second = state.second.push(rb, context);
+                let candidate = State { node: state.node, first, second, score };
+                let entry = next.entry((first, second)).or_insert((candidate, a, b));
+                if score > entry.0.score {
+                    *entry = (candidate, a, b);
+                }
+            }
+        }
+        let mut states: Vec<(State, u8, u8)> = next.into_values().collect();
+        states.sort_by(|x, y| y.0.score.total_cmp(&x.0.score));
+        states.truncate(beam_width);
+        beam = states.into_iter()
+            .map(|(state, a, b)| {
+                nodes.push((state.node, a, b));
+                State { node: nodes.len() - 1, ..state }
+            })
+            .collect();
+    }
+
+    let Some(best) = beam.iter().max_by(|x, y| x.score.total_cmp(&y.score)) else {
+        return Ok((Vec::new(), Vec::new(), f64::NEG_INFINITY));
+    };
+    let (mut first, mut second) = (Vec::new(), Vec::new());
+    let mut node = best.node;
+    while node != usize::MAX {
+        let (parent, a, b) = nodes[node];
+        first.push(a);
+        second.push(b);
+        node = parent;
+    }
+    first.reverse();
+    second.reverse();
+    Ok((first, second, best.score))
+}
+
+/// Breaks a running key cipher from ciphertext alone, assuming both the plaintext and the key
+/// are English: a beam of `beam_width` partial solutions is extended letter by letter, each
+/// scored by the letter n-grams of `model` on both streams (see `solve_running_key`).
+///
+/// Plaintext and key play the same role in the cipher, so the solver can't tell them apart:
+/// `plaintext` keeps the case and punctuation of `text` but may hold the key text, with the
+/// other stream in `key` (lowercase letters). Since the key letters follow from the plaintext
+/// letters, a beam of `26^(order - 1)` states searches exhaustively; any remaining wrong
+/// letters are the model's fault, so train an order 3 or 4 model on plenty of text.
+///
+/// # Errors
+/// - `CrypError::InvalidArgument` if `beam_width` is 0.
+///
+/// # Example
+/// ```rust
+/// use cryp::classical::{break_running_key, running_key_encrypt};
+/// use cryp::input::lyrics_model;
+/// use cryp::ngram::NgramAlphabet;
+/// use cryp::CrypError;
+///
+/// let model = lyrics_model(3, NgramAlphabet::Letters).unwrap();
+///
+/// // Both texts come from the lyrics the model was trained on.
+/// let ciphertext = running_key_encrypt("Play that funky music, white boy", "lay down and boogie and play the").unwrap();
+/// let solution = break_running_key(&ciphertext, &model, 100).unwrap();
+/// assert_eq!(solution.plaintext, "Play that funky music, white boy");
+/// assert_eq!(solution.key, "laydownandboogieandplaythe");
+///
+/// let no_beam = break_running_key(&ciphertext, &model, 0);
+/// assert!(matches!(no_beam, Err(CrypError::InvalidArgument { name: "beam width", value: 0 })));
+/// ```
+pub fn break_running_key(text: &str, model: &NgramModel, beam_width: usize) -> Result<Solution<String>, CrypError> {
+    let letters = letter_indices(text);
+    let candidates: Vec<u8> = (0..ALPHABET_SIZE as u8).collect();
+    let decrypt = |c: u8, p: u8| (c + ALPHABET_SIZE as u8 - p) % ALPHABET_SIZE as u8;
+    let upper = model.alphabet() == crate::ngram::NgramAlphabet::Letters;
+    let (plain, key, score) = solve_running_key(&letters, &candidates, decrypt, |l| index_letter(l, upper) as u8, model, beam_width)?;
+
+    let mut plain = plain.into_iter();
+    let plaintext = map_letters(text, |_, _| plain.next().unwrap_or(0));
+    Ok(Solution { key: letters_string(&key, false), plaintext, score })
+}
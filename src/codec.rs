@@ -0,0 +1,570 @@
+//! Binary-to-text codecs behind a common [`Codec`] trait.
+//!
+//! Besides wrappers for hex and base64 this module implements Base32 (RFC 4648), Base58
+//! (Bitcoin alphabet), Ascii85 (Adobe/btoa flavour, as found in PDF and PostScript), Z85
+//! (ZeroMQ), decimal byte lists and percent-encoding. Decoders skip ASCII whitespace.
+//!
+//! # Example
+//! ```rust
+//! use cryp::codec;
+//!
+//! let data: Vec<u8> = (0..=255).rev().chain(0..=255).collect();
+//! for codec in codec::all() {
+//!     for len in (0..64).chain([255, 256, 512]) {
+//!         if let Ok(encoded) = codec.encode(&data[..len]) {
+//!             assert_eq!(codec.decode(&encoded).unwrap(), &data[..len], "{} len {}", codec.name(), len);
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    base64_decode_config, base64_encode_config, bytes_to_hexstr, hexstr_to_bytes, Base64Config,
+    CrypError,
+};
+
+/// A reversible binary-to-text encoding.
+pub trait Codec {
+    /// Name used on the command line and in reports, e.g. `"base32"`.
+    fn name(&self) -> &'static str;
+
+    fn encode(&self, data: &[u8]) -> Result<String, CrypError>;
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, CrypError>;
+}
+
+pub struct Hex;
+
+impl Codec for Hex {
+    fn name(&self) -> &'static str { "hex" }
+
+    fn encode(&self, data: &[u8]) -> Result<String, CrypError> {
+        Ok(bytes_to_hexstr(data))
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, CrypError> {
+        let digits: String = text.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+        hexstr_to_bytes(&digits)
+    }
+}
+
+pub struct Base64(pub Base64Config);
+
+impl Codec for Base64 {
+    fn name(&self) -> &'static str {
+        match self.0.alphabet {
+            crate::Base64Alphabet::Standard => "base64",
+            crate::Base64Alphabet::UrlSafe => "base64url",
+        }
+    }
+
+    fn encode(&self, data: &[u8]) -> Result<String, CrypError> {
+        Ok(base64_encode_config(data, &self.0))
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, CrypError> {
+        base64_decode_config(text, &self.0)
+    }
+}
+
+pub struct Base32 {
+    pub padding: bool,
+}
+
+impl Codec for Base32 {
+    fn name(&self) -> &'static str { "base32" }
+
+    fn encode(&self, data: &[u8]) -> Result<String, CrypError> {
+        let encoded = base32_encode(data);
+        Ok(if self.padding { encoded } else { encoded.trim_end_matches('=').to_string() })
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, CrypError> {
+        base32_decode(text)
+    }
+}
+
+pub struct Base58;
+
+impl Codec for Base58 {
+    fn name(&self) -> &'static str { "base58" }
+
+    fn encode(&self, data: &[u8]) -> Result<String, CrypError> {
+        Ok(base58_encode(data))
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, CrypError> {
+        base58_decode(text)
+    }
+}
+
+pub struct Ascii85;
+
+impl Codec for Ascii85 {
+    fn name(&self) -> &'static str { "ascii85" }
+
+    fn encode(&self, data: &[u8]) -> Result<String, CrypError> {
+        Ok(ascii85_encode(data))
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, CrypError> {
+        ascii85_decode(text)
+    }
+}
+
+pub struct Z85;
+
+impl Codec for Z85 {
+    fn name(&self) -> &'static str { "z85" }
+
+    fn encode(&self, data: &[u8]) -> Result<String, CrypError> {
+        z85_encode(data)
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, CrypError> {
+        z85_decode(text)
+    }
+}
+
+pub struct Decimal;
+
+impl Codec for Decimal {
+    fn name(&self) -> &'static str { "decimal" }
+
+    fn encode(&self, data: &[u8]) -> Result<String, CrypError> {
+        Ok(decimal_encode(data))
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, CrypError> {
+        decimal_decode(text)
+    }
+}
+
+pub struct Percent;
+
+impl Codec for Percent {
+    fn name(&self) -> &'static str { "percent" }
+
+    fn encode(&self, data: &[u8]) -> Result<String, CrypError> {
+        Ok(percent_encode(data))
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, CrypError> {
+        percent_decode(text)
+    }
+}
+
+/// Every codec in this module, with the default settings of each.
+pub fn all() -> Vec<Box<dyn Codec>> {
+    vec![
+        Box::new(Hex),
+        Box::new(Base64(Base64Config::STANDARD)),
+        Box::new(Base64(Base64Config::URL_SAFE_NO_PAD)),
+        Box::new(Base32 { padding: true }),
+        Box::new(Base58),
+        Box::new(Ascii85),
+        Box::new(Z85),
+        Box::new(Decimal),
+        Box::new(Percent),
+    ]
+}
+
+/// Looks up a codec by its name (see [`Codec::name`]).
+///
+/// # Example
+/// ```rust
+/// use cryp::codec;
+///
+/// let base58 = codec::by_name("base58").unwrap();
+/// let encoded = base58.encode(b"hello world").unwrap();
+/// assert_eq!(encoded, "StV1DL6CwTryKyV");
+/// assert_eq!(base58.decode(&encoded).unwrap(), b"hello world");
+/// ```
+pub fn by_name(name: &str) -> Option<Box<dyn Codec>> {
+    all().into_iter().find(|codec| codec.name().eq_ignore_ascii_case(name))
+}
+
+const BASE32_CHARSET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE58_CHARSET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const Z85_CHARSET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// Non-whitespace bytes of `text` with their positions.
+fn significant(text: &str) -> impl Iterator<Item = (usize, u8)> + '_ {
+    text.bytes().enumerate().filter(|(_, byte)| !byte.is_ascii_whitespace())
+}
+
+fn invalid(encoding: &'static str, text: &str, pos: usize) -> CrypError {
+    let char = text[pos..].chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+    CrypError::InvalidEncoding { encoding, pos, char }
+}
+
+/// Encodes `data` as padded Base32 (RFC 4648 section 6).
+///
+/// # Example
+/// ```rust
+/// use cryp::{base32_decode, base32_encode};
+///
+/// assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI======");
+/// assert_eq!(base32_decode("mzxw6ytboi").unwrap(), b"foobar"); // case and padding are optional
+/// ```
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_CHARSET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        encoded.push(BASE32_CHARSET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    while !encoded.len().is_multiple_of(8) {
+        encoded.push('=');
+    }
+    encoded
+}
+
+/// Decodes Base32, ignoring case, whitespace and (optional) trailing `=` padding.
+///
+/// # Errors
+/// - `CrypError::InvalidEncoding` for a character outside the alphabet or data after padding.
+/// - `CrypError::InvalidLength` if the last group has an impossible number of characters.
+pub fn base32_decode(text: &str) -> Result<Vec<u8>, CrypError> {
+    let mut decoded = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    let mut symbols = 0usize;
+    let mut padded = false;
+
+    for (pos, byte) in significant(text) {
+        if byte == b'=' {
+            padded = true;
+            continue;
+        }
+        let value = BASE32_CHARSET.iter()
+            .position(|&c| c == byte.to_ascii_uppercase())
+            .filter(|_| !padded)
+            .ok_or_else(|| invalid("base32", text, pos))?;
+
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+        symbols += 1;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    // 1, 3 or 6 characters in the last group of 8 can't carry whole bytes.
+    if matches!(symbols % 8, 1 | 3 | 6) {
+        return Err(CrypError::InvalidLength { encoding: "base32", len: symbols });
+    }
+    Ok(decoded)
+}
+
+/// Encodes `data` in Base58 with the Bitcoin alphabet. Leading zero bytes become `1`s.
+///
+/// # Example
+/// ```rust
+/// use cryp::{base58_decode, base58_encode};
+///
+/// assert_eq!(base58_encode(&[0, 0, 0x28, 0x7f, 0xb4, 0xcd]), "11233QC4");
+/// assert_eq!(base58_decode("11233QC4").unwrap(), [0, 0, 0x28, 0x7f, 0xb4, 0xcd]);
+/// ```
+pub fn base58_encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&byte| byte == 0).count();
+
+    // Base-58 digits, least significant first.
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut encoded = "1".repeat(zeros);
+    encoded.extend(digits.iter().rev().map(|&digit| BASE58_CHARSET[digit as usize] as char));
+    encoded
+}
+
+/// Decodes Bitcoin-alphabet Base58. Leading `1`s become zero bytes.
+///
+/// # Errors
+/// - `CrypError::InvalidEncoding` for a character outside the alphabet (e.g. `0`, `O`, `I`, `l`).
+pub fn base58_decode(text: &str) -> Result<Vec<u8>, CrypError> {
+    let mut zeros = 0;
+    let mut leading = true;
+    // Bytes, least significant first.
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len() * 733 / 1000 + 1);
+
+    for (pos, byte) in significant(text) {
+        let value = BASE58_CHARSET.iter()
+            .position(|&c| c == byte)
+            .ok_or_else(|| invalid("base58", text, pos))?;
+        if leading && value == 0 {
+            zeros += 1;
+            continue;
+        }
+        leading = false;
+
+        let mut carry = value as u32;
+        for b in bytes.iter_mut() {
+            carry += *b as u32 * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut decoded = vec![0u8; zeros];
+    decoded.extend(bytes.iter().rev());
+    Ok(decoded)
+}
+
+/// Writes the 5 base-85 digits of `value`, most significant first.
+fn base85_digits(mut value: u32) -> [u8; 5] {
+    let mut digits = [0u8; 5];
+    for digit in digits.iter_mut().rev() {
+        *digit = (value % 85) as u8;
+        value /= 85;
+    }
+    digits
+}
+
+/// Folds 5 base-85 digits back into a 32-bit value, or `None` on overflow.
+fn base85_value(digits: &[u8]) -> Option<u32> {
+    digits.iter().try_fold(0u32, |acc, &digit| acc.checked_mul(85)?.checked_add(digit as u32))
+}
+
+/// Encodes `data` in Ascii85 (Adobe/btoa), without the `<~ ~>` delimiters. Groups of four
+/// zero bytes are shortened to `z`.
+///
+/// # Example
+/// ```rust
+/// use cryp::{ascii85_decode, ascii85_encode};
+///
+/// assert_eq!(ascii85_encode(b"Man \0\0\0\0sure"), "9jqo^zF*2M7");
+/// assert_eq!(ascii85_decode("<~9jqo^zF*2M7~>").unwrap(), b"Man \0\0\0\0sure");
+/// ```
+pub fn ascii85_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(4) * 5);
+
+    for chunk in data.chunks(4) {
+        if chunk == [0, 0, 0, 0] {
+            encoded.push('z');
+            continue;
+        }
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let digits = base85_digits(u32::from_be_bytes(group));
+        encoded.extend(digits[..chunk.len() + 1].iter().map(|&digit| (digit + b'!') as char));
+    }
+    encoded
+}
+
+/// Decodes Ascii85, with or without the `<~ ~>` delimiters, ignoring whitespace.
+///
+/// # Errors
+/// - `CrypError::InvalidEncoding` for a character outside `!`..=`u`, a `z` inside a group or a
+///   group that overflows 32 bits.
+/// - `CrypError::InvalidLength` if the final group has a single character.
+pub fn ascii85_decode(text: &str) -> Result<Vec<u8>, CrypError> {
+    let trimmed = text.trim();
+    let start = if trimmed.starts_with("<~") { text.find("<~").unwrap_or(0) + 2 } else { 0 };
+    let end = if trimmed.ends_with("~>") { text.rfind("~>").unwrap_or(text.len()) } else { text.len() };
+
+    let mut decoded = Vec::with_capacity(text.len() * 4 / 5);
+    let mut group = Vec::with_capacity(5);
+    let mut group_start = start;
+
+    for (pos, byte) in significant(&text[..end]).filter(|&(pos, _)| pos >= start) {
+        match byte {
+            b'z' if group.is_empty() => decoded.extend([0u8; 4]),
+            b'!'..=b'u' => {
+                if group.is_empty() {
+                    group_start = pos;
+                }
+                group.push(byte - b'!');
+                if group.len() == 5 {
+                    let value = base85_value(&group).ok_or_else(|| invalid("ascii85", text, group_start))?;
+                    decoded.extend(value.to_be_bytes());
+                    group.clear();
+                }
+            }
+            _ => return Err(invalid("ascii85", text, pos)),
+        }
+    }
+
+    if group.len() == 1 {
+        return Err(CrypError::InvalidLength { encoding: "ascii85", len: decoded.len() / 4 * 5 + 1 });
+    }
+    if !group.is_empty() {
+        let kept = group.len() - 1;
+        group.resize(5, b'u' - b'!');
+        let value = base85_value(&group).ok_or_else(|| invalid("ascii85", text, group_start))?;
+        decoded.extend(&value.to_be_bytes()[..kept]);
+    }
+    Ok(decoded)
+}
+
+/// Encodes `data` in Z85 (ZeroMQ RFC 32).
+///
+/// # Errors
+/// - `CrypError::InvalidLength` if the length of `data` is not a multiple of 4.
+///
+/// # Example
+/// ```rust
+/// use cryp::{z85_decode, z85_encode};
+///
+/// let data = [0x86, 0x4f, 0xd2, 0x6f, 0xb5, 0x59, 0xf7, 0x5b];
+/// assert_eq!(z85_encode(&data).unwrap(), "HelloWorld");
+/// assert_eq!(z85_decode("HelloWorld").unwrap(), data);
+/// assert!(z85_encode(b"abc").is_err());
+/// ```
+pub fn z85_encode(data: &[u8]) -> Result<String, CrypError> {
+    if !data.len().is_multiple_of(4) {
+        return Err(CrypError::InvalidLength { encoding: "z85", len: data.len() });
+    }
+
+    Ok(data.chunks(4)
+        .flat_map(|chunk| base85_digits(u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])))
+        .map(|digit| Z85_CHARSET[digit as usize] as char)
+        .collect())
+}
+
+/// Decodes Z85, ignoring whitespace.
+///
+/// # Errors
+/// - `CrypError::InvalidEncoding` for a character outside the alphabet or a group that
+///   overflows 32 bits.
+/// - `CrypError::InvalidLength` if the number of characters is not a multiple of 5.
+pub fn z85_decode(text: &str) -> Result<Vec<u8>, CrypError> {
+    let mut digits = Vec::with_capacity(text.len());
+    let mut positions = Vec::with_capacity(text.len());
+    for (pos, byte) in significant(text) {
+        let digit = Z85_CHARSET.iter()
+            .position(|&c| c == byte)
+            .ok_or_else(|| invalid("z85", text, pos))?;
+        digits.push(digit as u8);
+        positions.push(pos);
+    }
+    if !digits.len().is_multiple_of(5) {
+        return Err(CrypError::InvalidLength { encoding: "z85", len: digits.len() });
+    }
+
+    let mut decoded = Vec::with_capacity(digits.len() / 5 * 4);
+    for (i, group) in digits.chunks(5).enumerate() {
+        let value = base85_value(group).ok_or_else(|| invalid("z85", text, positions[i * 5]))?;
+        decoded.extend(value.to_be_bytes());
+    }
+    Ok(decoded)
+}
+
+/// Writes `data` as space-separated decimal byte values.
+///
+/// # Example
+/// ```rust
+/// use cryp::{decimal_decode, decimal_encode};
+///
+/// assert_eq!(decimal_encode(b"Hi!"), "72 105 33");
+/// assert_eq!(decimal_decode("[72, 105, 33]").unwrap(), b"Hi!");
+/// ```
+pub fn decimal_encode(data: &[u8]) -> String {
+    data.iter().map(|byte| byte.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// Parses a list of decimal byte values separated by whitespace and/or commas, optionally
+/// enclosed in `[]`, `()` or `{}`.
+///
+/// # Errors
+/// - `CrypError::InvalidEncoding` for a non-digit or a value above 255 (at the token's start).
+pub fn decimal_decode(text: &str) -> Result<Vec<u8>, CrypError> {
+    let trimmed = text.trim();
+    let inner = match (trimmed.chars().next(), trimmed.chars().last()) {
+        (Some('['), Some(']')) | (Some('('), Some(')')) | (Some('{'), Some('}')) => &trimmed[1..trimmed.len() - 1],
+        _ => trimmed,
+    };
+    let offset = inner.as_ptr() as usize - text.as_ptr() as usize;
+
+    let mut decoded = Vec::new();
+    let mut token_start = None;
+    for (pos, ch) in inner.char_indices().chain([(inner.len(), ' ')]) {
+        if ch.is_ascii_digit() {
+            token_start.get_or_insert(pos);
+        } else if ch.is_whitespace() || ch == ',' {
+            if let Some(start) = token_start.take() {
+                let byte = inner[start..pos].parse::<u8>()
+                    .map_err(|_| invalid("decimal", text, offset + start))?;
+                decoded.push(byte);
+            }
+        } else {
+            return Err(invalid("decimal", text, offset + pos));
+        }
+    }
+    Ok(decoded)
+}
+
+/// Percent-encodes `data` (RFC 3986), leaving only the unreserved characters as they are.
+///
+/// # Example
+/// ```rust
+/// use cryp::{percent_decode, percent_encode};
+///
+/// assert_eq!(percent_encode(b"a b/c"), "a%20b%2Fc");
+/// assert_eq!(percent_decode("a%20b%2fc").unwrap(), b"a b/c");
+/// assert!(percent_decode("%+f").is_err());
+/// ```
+pub fn percent_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len() * 3);
+    for &byte in data {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Decodes `%XX` escapes. Other characters, including `+`, are kept as they are.
+///
+/// # Errors
+/// - `CrypError::InvalidEncoding` for a `%` not followed by two hex digits.
+pub fn percent_decode(text: &str) -> Result<Vec<u8>, CrypError> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let escape = bytes.get(i + 1..i + 3)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| invalid("percent", text, i))?;
+            decoded.push(escape);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(decoded)
+}
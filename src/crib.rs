@@ -0,0 +1,447 @@
+//! Crib dragging and statistical breaking for many-time pads: messages encrypted with the same
+//! keystream (a reused one-time pad, a stream cipher with a fixed nonce or a static key).
+//!
+//! XORing two such ciphertexts cancels the keystream and leaves the XOR of the two plaintexts.
+//! Guessing a word in one message (the crib) and XORing it in at some offset then reveals the
+//! other message at that offset, which reads as text only where the guess is right. Accepted
+//! guesses also reveal the keystream, and with it every other message at those positions.
+//!
+//! # Example
+//! ```rust
+//! use cryp::crib::{self, CribSession};
+//! use cryp::xor_fixed;
+//!
+//! let keystream = b"\x3a\x91\x07\xee\x5c\x28\xb3\x6d\xf0\x14\x82\x4b\xc9\x75\x1e\xa6\x58\x0d\xe3\x99";
+//! let messages: [&[u8]; 3] = [b"attack the east wall", b"retreat to the river", b"hold the north gate"];
+//! let ciphertexts: Vec<Vec<u8>> = messages.iter()
+//!     .map(|m| xor_fixed(m, &keystream[..m.len()]).unwrap())
+//!     .collect();
+//!
+//! // Dragging " the " over the XOR of the first two messages finds it at offset 6.
+//! let xored = crib::xor_pair(&ciphertexts[0], &ciphertexts[1]);
+//! let hits: Vec<usize> = crib::drag(&xored, b" the ").iter().filter(|m| m.is_text).map(|m| m.offset).collect();
+//! assert!(hits.contains(&6));
+//!
+//! let mut session = CribSession::new(ciphertexts);
+//! session.accept(0, 0, b"attack the east wall").unwrap();
+//! assert_eq!(session.plaintext(1), b"retreat to the river".map(Some));
+//! assert_eq!(session.plaintext(2)[..4], [Some(b'h'), Some(b'o'), Some(b'l'), Some(b'd')]);
+//! ```
+
+use std::fmt::Write;
+
+use colored::Colorize;
+
+use crate::score::Scorer;
+use crate::{score_text, xor_one_byte, CrypError};
+
+/// The minimum `score_text` percentage of a fragment that counts as text.
+pub const TEXT_THRESHOLD: f32 = 70.0;
+
+/// XORs `a` and `b` up to the length of the shorter one.
+///
+/// # Example
+/// ```rust
+/// use cryp::crib::xor_pair;
+///
+/// assert_eq!(xor_pair(b"\x01\x02\x03", b"\x10\x20"), [0x11, 0x22]);
+/// ```
+pub fn xor_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+/// Whether a decrypted fragment reads as text: printable ASCII or whitespace only, and at least
+/// `TEXT_THRESHOLD` percent letters, digits and spaces.
+///
+/// # Example
+/// ```rust
+/// use cryp::crib::looks_like_text;
+///
+/// assert!(looks_like_text(b"the e"));
+/// assert!(!looks_like_text(b"t\x07e e"));
+/// assert!(!looks_like_text(b"),.'!"));
+/// ```
+pub fn looks_like_text(fragment: &[u8]) -> bool {
+    !fragment.is_empty()
+        && fragment.iter().all(|&b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+        && score_text(fragment) >= TEXT_THRESHOLD
+}
+
+/// The result of placing a crib at one offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CribMatch {
+    pub offset: usize,
+    /// The XOR of the crib and the data at `offset`: the other plaintext, if the crib is right.
+    pub text: Vec<u8>,
+    /// The `score_text` percentage of `text`.
+    pub score: f32,
+    /// Whether `text` passes `looks_like_text`.
+    pub is_text: bool,
+}
+
+/// Slides `crib` across `xored` (the XOR of two ciphertexts, see `xor_pair`) and XORs it in at
+/// every offset where it fits.
+///
+/// # Returns
+/// - One match per offset, in order; none if `crib` is empty or longer than `xored`.
+pub fn drag(xored: &[u8], crib: &[u8]) -> Vec<CribMatch> {
+    if crib.is_empty() || crib.len() > xored.len() {
+        return Vec::new();
+    }
+
+    xored.windows(crib.len())
+        .enumerate()
+        .map(|(offset, window)| {
+            let text = xor_pair(window, crib);
+            CribMatch { offset, score: score_text(&text), is_text: looks_like_text(&text), text }
+        })
+        .collect()
+}
+
+/// Renders `matches` one per line as `offset: text`, with the matches that read as text in
+/// bold green. Bytes that are not printable ASCII are shown as `.`.
+///
+/// # Example
+/// ```rust
+/// use cryp::crib::{drag, highlight, xor_pair};
+///
+/// colored::control::set_override(false);
+/// let xored = xor_pair(b"see the cat", b"hit the dog");
+/// let rendered = highlight(&drag(&xored, b"see"));
+/// assert!(rendered.starts_with("     0: hit\n"));
+/// ```
+pub fn highlight(matches: &[CribMatch]) -> String {
+    let mut out = String::new();
+    for m in matches {
+        let text = printable(&m.text.iter().map(|&b| Some(b)).collect::<Vec<_>>());
+        let text = if m.is_text { text.green().bold().to_string() } else { text };
+        writeln!(out, "{:6}: {}", m.offset, text).unwrap();
+    }
+    out
+}
+
+/// Renders partially known bytes as ASCII, with `_` for unknown bytes and `.` for unprintable
+/// ones.
+///
+/// # Example
+/// ```rust
+/// use cryp::crib::printable;
+///
+/// assert_eq!(printable(&[Some(b'h'), None, Some(0x07), Some(b'!')]), "h_.!");
+/// ```
+pub fn printable(bytes: &[Option<u8>]) -> String {
+    bytes.iter().map(|&b| match b {
+            Some(b) if b == b' ' || b.is_ascii_graphic() => b as char,
+            Some(_) => '.',
+            None => '_',
+        })
+        .collect()
+}
+
+/// The result of guessing a crib in one message of a `CribSession`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionMatch {
+    pub offset: usize,
+    /// The keystream bytes the guess implies at `offset`.
+    pub keystream: Vec<u8>,
+    /// What every message decrypts to under that keystream, cut short where a message ends.
+    /// The guessed message itself decrypts to the crib.
+    pub fragments: Vec<Vec<u8>>,
+    /// The average `score_text` percentage of the other messages' non-empty fragments.
+    pub score: f32,
+    /// Whether all of the other messages' non-empty fragments pass `looks_like_text`.
+    pub is_text: bool,
+}
+
+/// Builds up the keystream shared by several ciphertexts from accepted crib guesses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CribSession {
+    ciphertexts: Vec<Vec<u8>>,
+    keystream: Vec<Option<u8>>,
+}
+
+impl CribSession {
+    /// Starts a session with nothing known about the keystream, which is as long as the
+    /// longest ciphertext.
+    pub fn new(ciphertexts: Vec<Vec<u8>>) -> CribSession {
+        let len = ciphertexts.iter().map(Vec::len).max().unwrap_or(0);
+        CribSession { ciphertexts, keystream: vec![None; len] }
+    }
+
+    pub fn ciphertexts(&self) -> &[Vec<u8>] {
+        &self.ciphertexts
+    }
+
+    /// The keystream recovered so far.
+    pub fn keystream(&self) -> &[Option<u8>] {
+        &self.keystream
+    }
+
+    /// Replaces the known keystream, e.g. with `ReusedKeystream::keystream` to refine an
+    /// automatic break by hand. Bytes past the longest ciphertext are ignored.
+    pub fn set_keystream(&mut self, keystream: &[Option<u8>]) {
+        let len = self.keystream.len();
+        self.keystream = keystream.iter().cloned().chain(std::iter::repeat(None)).take(len).collect();
+    }
+
+    /// Checks that `crib` fits in `message` at `offset`.
+    fn check(&self, message: usize, offset: usize, crib: &[u8]) -> Result<&[u8], CrypError> {
+        let ciphertext = self.ciphertexts.get(message)
+            .ok_or(CrypError::InvalidIndex { index: message, len: self.ciphertexts.len() })?;
+        if offset.checked_add(crib.len()).is_none_or(|end| end > ciphertext.len()) {
+            return Err(CrypError::NotEnoughData { needed: offset.saturating_add(crib.len()), got: ciphertext.len() });
+        }
+        Ok(ciphertext)
+    }
+
+    /// Guesses that `message` contains `crib`, at every offset where it fits, and decrypts the
+    /// other messages under the keystream each guess implies.
+    ///
+    /// # Errors
+    /// - `CrypError::InvalidIndex` if there is no message `message`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::crib::CribSession;
+    ///
+    /// let keystream = [0x51, 0x0e, 0xa7, 0x3c, 0x90, 0x2b, 0x66, 0xd4, 0x19];
+    /// let ciphertexts = [b"the quiet", b"she knows"]
+    ///     .map(|m| m.iter().zip(keystream).map(|(a, b)| a ^ b).collect::<Vec<u8>>());
+    /// let session = CribSession::new(ciphertexts.to_vec());
+    ///
+    /// let matches = session.drag(0, b"the ").unwrap();
+    /// assert_eq!(matches.len(), 6);
+    /// assert_eq!(matches[0].fragments[1], b"she ");
+    /// assert!(matches[0].is_text);
+    /// ```
+    pub fn drag(&self, message: usize, crib: &[u8]) -> Result<Vec<SessionMatch>, CrypError> {
+        let ciphertext = self.check(message, 0, &[])?;
+        if crib.is_empty() || crib.len() > ciphertext.len() {
+            return Ok(Vec::new());
+        }
+
+        Ok((0..=ciphertext.len() - crib.len())
+            .map(|offset| {
+                let keystream = xor_pair(&ciphertext[offset..], crib);
+                let fragments: Vec<Vec<u8>> = self.ciphertexts.iter()
+                    .map(|other| xor_pair(other.get(offset..).unwrap_or_default(), &keystream))
+                    .collect();
+
+                let others: Vec<&Vec<u8>> = fragments.iter()
+                    .enumerate()
+                    .filter(|&(i, fragment)| i != message && !fragment.is_empty())
+                    .map(|(_, fragment)| fragment)
+                    .collect();
+                let score = if others.is_empty() {
+                    0.0
+                } else {
+                    others.iter().map(|fragment| score_text(fragment)).sum::<f32>() / others.len() as f32
+                };
+                let is_text = !others.is_empty() && others.iter().all(|fragment| looks_like_text(fragment));
+
+                SessionMatch { offset, keystream, fragments, score, is_text }
+            })
+            .collect())
+    }
+
+    /// Accepts that `message` reads `plaintext` at `offset`, fixing the keystream under it.
+    /// Later guesses overwrite earlier ones.
+    ///
+    /// # Errors
+    /// - `CrypError::InvalidIndex` if there is no message `message`.
+    /// - `CrypError::NotEnoughData` if `plaintext` runs past the end of the message.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::crib::CribSession;
+    /// use cryp::CrypError;
+    ///
+    /// let mut session = CribSession::new(vec![vec![0x00; 6]]);
+    /// session.accept(0, 4, b"ab").unwrap();
+    /// assert_eq!(session.plaintext(0)[4..], [Some(b'a'), Some(b'b')]);
+    ///
+    /// assert!(matches!(session.accept(0, 5, b"ab"), Err(CrypError::NotEnoughData { needed: 7, got: 6 })));
+    /// // Offsets near usize::MAX are rejected, not overflowed.
+    /// assert!(matches!(session.accept(0, usize::MAX, b"ab"), Err(CrypError::NotEnoughData { .. })));
+    /// ```
+    pub fn accept(&mut self, message: usize, offset: usize, plaintext: &[u8]) -> Result<(), CrypError> {
+        let ciphertext = self.check(message, offset, plaintext)?;
+        let keystream = xor_pair(&ciphertext[offset..], plaintext);
+        for (slot, byte) in self.keystream[offset..].iter_mut().zip(keystream) {
+            *slot = Some(byte);
+        }
+        Ok(())
+    }
+
+    /// Forgets the keystream from `offset` for `len` bytes. Bytes past the end of the longest
+    /// message are ignored.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::crib::CribSession;
+    ///
+    /// let mut session = CribSession::new(vec![vec![0x00; 6]]);
+    /// session.accept(0, 0, b"abcdef").unwrap();
+    /// session.reject(1, 2);
+    /// assert_eq!(session.render(), "0: a__def\n");
+    ///
+    /// // A length reaching past usize::MAX clears to the end instead of overflowing.
+    /// session.reject(4, usize::MAX);
+    /// assert_eq!(session.render(), "0: a__d__\n");
+    /// ```
+    pub fn reject(&mut self, offset: usize, len: usize) {
+        let end = offset.saturating_add(len).min(self.keystream.len());
+        for slot in self.keystream.iter_mut().take(end).skip(offset) {
+            *slot = None;
+        }
+    }
+
+    /// Message `message` decrypted as far as the keystream is known (`None` elsewhere), or an
+    /// empty vector if there is no such message.
+    pub fn plaintext(&self, message: usize) -> Vec<Option<u8>> {
+        self.ciphertexts.get(message)
+            .map(|ciphertext| {
+                ciphertext.iter().zip(&self.keystream).map(|(c, k)| k.map(|k| c ^ k)).collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Renders every message as far as it is known, one per line, prefixed with its index.
+    /// Unknown bytes are shown as `_`, unprintable ones as `.`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::crib::CribSession;
+    ///
+    /// let mut session = CribSession::new(vec![vec![0x00; 6], vec![0x20; 4]]);
+    /// session.accept(0, 1, b"ab").unwrap();
+    /// assert_eq!(session.render(), "0: _ab___\n1: _AB_\n");
+    /// ```
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for message in 0..self.ciphertexts.len() {
+            writeln!(out, "{}: {}", message, printable(&self.plaintext(message))).unwrap();
+        }
+        out
+    }
+}
+
+/// Keystream columns decrypting fewer messages than this are flagged as sparse by default.
+pub const DEFAULT_MIN_SAMPLES: usize = 5;
+
+/// One keystream byte recovered by `break_reused_keystream`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeystreamColumn {
+    /// The best-scoring keystream byte.
+    pub byte: u8,
+    /// How many ciphertexts are long enough to reach this column.
+    pub samples: usize,
+    /// How far `byte` stands out from the other 255 candidates, from 0 to 1. Under
+    /// `score::LogLikelihood` this is the posterior probability of `byte` (the scores are
+    /// average log-likelihoods, so `samples * score` is the log-likelihood of the column);
+    /// other scorers are ranked the same way but not calibrated.
+    pub confidence: f64,
+    /// Fewer than `min_samples` ciphertexts reach this column, too few to trust `byte`.
+    pub sparse: bool,
+}
+
+/// A keystream shared by several ciphertexts, recovered column by column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReusedKeystream {
+    pub columns: Vec<KeystreamColumn>,
+    /// Every message decrypted with the recovered keystream, `None` in sparse columns.
+    pub plaintexts: Vec<Vec<Option<u8>>>,
+}
+
+impl ReusedKeystream {
+    /// The recovered keystream, `None` in sparse columns.
+    pub fn keystream(&self) -> Vec<Option<u8>> {
+        self.columns.iter().map(|column| (!column.sparse).then_some(column.byte)).collect()
+    }
+}
+
+/// Breaks ciphertexts that were encrypted with the same keystream, such as fixed-nonce CTR.
+///
+/// The ciphertexts are aligned at their first byte. Column `i` holds byte `i` of every
+/// ciphertext long enough to have one, all XORed with the same keystream byte, so each column
+/// is a single-byte XOR problem: every byte is tried with `xor_one_byte` and ranked with
+/// `scorer`. Columns reached by fewer than `min_samples` ciphertexts (the tails of the longest
+/// messages) are still guessed but flagged as sparse and left undecrypted in `plaintexts`.
+///
+/// # Arguments
+/// - `ciphertexts`: The messages, encrypted with the same keystream from its start.
+/// - `scorer`: Ranks the candidate plaintext columns (see the `score` module).
+/// - `min_samples`: The fewest ciphertexts a column needs to be trusted
+///   (`DEFAULT_MIN_SAMPLES` is a reasonable choice).
+///
+/// # Example
+/// ```rust
+/// use cryp::crib::break_reused_keystream;
+/// use cryp::score::LogLikelihood;
+///
+/// let messages: [&[u8]; 8] = [
+///     b"i have met them at close of day",
+///     b"coming with vivid faces",
+///     b"from counter or desk among grey",
+///     b"eighteenth-century houses",
+///     b"i have passed with a nod of the head",
+///     b"or polite meaningless words",
+///     b"or have lingered awhile and said",
+///     b"polite meaningless words, and thought before I had done",
+/// ];
+/// let mut state = 0x2545f491u32;
+/// let keystream: Vec<u8> = (0..64).map(|_| { state ^= state << 13; state ^= state >> 17; state ^= state << 5; state as u8 }).collect();
+/// let ciphertexts: Vec<Vec<u8>> = messages.iter()
+///     .map(|m| m.iter().zip(&keystream).map(|(a, b)| a ^ b).collect())
+///     .collect();
+///
+/// let broken = break_reused_keystream(&ciphertexts, &LogLikelihood, 5);
+/// let recovered = &broken.plaintexts[4];
+/// let correct = recovered.iter().zip(messages[4]).filter(|&(r, m)| *r == Some(*m)).count();
+/// assert!(correct >= 24);
+///
+/// // With eight samples per column some guesses are wrong, but not the confident ones.
+/// for (column, &byte) in broken.columns.iter().zip(&keystream) {
+///     if column.confidence > 0.9 {
+///         assert_eq!(column.byte, byte);
+///     }
+/// }
+///
+/// // Only the last message reaches past byte 35: its tail is flagged, not guessed.
+/// assert!(broken.columns[40].sparse);
+/// assert_eq!(broken.plaintexts[7][40], None);
+/// ```
+pub fn break_reused_keystream(ciphertexts: &[Vec<u8>], scorer: &dyn Scorer, min_samples: usize) -> ReusedKeystream {
+    let len = ciphertexts.iter().map(Vec::len).max().unwrap_or(0);
+
+    let columns: Vec<KeystreamColumn> = (0..len)
+        .map(|i| {
+            let column: Vec<u8> = ciphertexts.iter().filter_map(|c| c.get(i).cloned()).collect();
+            let weight = column.len() as f64;
+            let scores: Vec<f64> = (0..=255).map(|key| scorer.score(&xor_one_byte(&column, key))).collect();
+
+            let (byte, best) = scores.iter()
+                .enumerate()
+                .fold((0, f64::NEG_INFINITY), |best, (key, &score)| if score > best.1 { (key, score) } else { best });
+            let total: f64 = scores.iter().map(|&score| ((score - best) * weight).exp()).sum();
+
+            KeystreamColumn {
+                byte: byte as u8,
+                samples: column.len(),
+                confidence: 1.0 / total,
+                sparse: column.len() < min_samples,
+            }
+        })
+        .collect();
+
+    let plaintexts = ciphertexts.iter()
+        .map(|ciphertext| {
+            ciphertext.iter()
+                .zip(&columns)
+                .map(|(c, column)| (!column.sparse).then_some(c ^ column.byte))
+                .collect()
+        })
+        .collect();
+
+    ReusedKeystream { columns, plaintexts }
+}
@@ -0,0 +1,426 @@
+//! A minimal DER (ASN.1 Distinguished Encoding Rules) reader and writer.
+//!
+//! Only the types needed for keys and signatures are understood: INTEGER, BIT STRING,
+//! OCTET STRING, NULL, OBJECT IDENTIFIER, SEQUENCE, SET and constructed context-specific tags
+//! (`[0]`, `[1]`, ...). Anything else is kept as raw contents so it can be written back
+//! unchanged. Integers are stored as their big-endian two's complement contents; use
+//! [`Der::uint`] and [`Der::as_uint`] for the unsigned numbers found in keys.
+//!
+//! # Example
+//! ```rust
+//! use cryp::der::{self, Der, Oid};
+//!
+//! let value = Der::Sequence(vec![
+//!     Der::Sequence(vec![Der::Oid(Oid::new(der::oid::SHA256).unwrap()), Der::Null]),
+//!     Der::OctetString(vec![0xab; 32]),
+//! ]);
+//! let encoded = value.to_der();
+//! assert_eq!(&encoded[..4], [0x30, 0x31, 0x30, 0x0d]);
+//! assert_eq!(der::parse(&encoded).unwrap(), value);
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::CrypError;
+
+/// Universal tag numbers (with the constructed bit for SEQUENCE and SET).
+pub mod tag {
+    pub const INTEGER: u8 = 0x02;
+    pub const BIT_STRING: u8 = 0x03;
+    pub const OCTET_STRING: u8 = 0x04;
+    pub const NULL: u8 = 0x05;
+    pub const OID: u8 = 0x06;
+    pub const SEQUENCE: u8 = 0x30;
+    pub const SET: u8 = 0x31;
+    /// Class bits of a constructed context-specific tag; the low five bits hold the number.
+    pub const CONTEXT_CONSTRUCTED: u8 = 0xa0;
+}
+
+/// Object identifiers used by RSA and EC keys and PKCS#1 signatures.
+pub mod oid {
+    pub const RSA_ENCRYPTION: &[u64] = &[1, 2, 840, 113549, 1, 1, 1];
+    pub const EC_PUBLIC_KEY: &[u64] = &[1, 2, 840, 10045, 2, 1];
+    /// NIST P-256, also known as prime256v1 and secp256r1.
+    pub const SECP256R1: &[u64] = &[1, 2, 840, 10045, 3, 1, 7];
+    pub const SECP384R1: &[u64] = &[1, 3, 132, 0, 34];
+    pub const SECP521R1: &[u64] = &[1, 3, 132, 0, 35];
+    pub const SECP256K1: &[u64] = &[1, 3, 132, 0, 10];
+    pub const MD5: &[u64] = &[1, 2, 840, 113549, 2, 5];
+    pub const SHA1: &[u64] = &[1, 3, 14, 3, 2, 26];
+    pub const SHA224: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 4];
+    pub const SHA256: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 1];
+    pub const SHA384: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 2];
+    pub const SHA512: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 3];
+}
+
+/// An OBJECT IDENTIFIER such as `1.2.840.113549.1.1.1`.
+///
+/// # Example
+/// ```rust
+/// use cryp::der::{oid, Oid};
+///
+/// let rsa: Oid = "1.2.840.113549.1.1.1".parse().unwrap();
+/// assert_eq!(rsa.arcs(), oid::RSA_ENCRYPTION);
+/// assert_eq!(rsa.to_string(), "1.2.840.113549.1.1.1");
+/// assert!("3.1".parse::<Oid>().is_err());
+/// assert!("2.18446744073709551615".parse::<Oid>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Oid(Vec<u64>);
+
+impl Oid {
+    /// Builds an OID from its arcs. There must be at least two, the first at most 2 and, when
+    /// the first is 0 or 1, the second below 40. When the first is 2, the second must leave
+    /// room for the 80 it is encoded with in a `u64`.
+    pub fn new(arcs: &[u64]) -> Result<Oid, CrypError> {
+        match arcs {
+            [0 | 1, second, ..] if *second < 40 => Ok(Oid(arcs.to_vec())),
+            [2, second, ..] if *second <= u64::MAX - 80 => Ok(Oid(arcs.to_vec())),
+            _ => Err(CrypError::InvalidOid),
+        }
+    }
+
+    pub fn arcs(&self) -> &[u64] {
+        &self.0
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let first = self.0[0] * 40 + self.0[1];
+        for &arc in std::iter::once(&first).chain(&self.0[2..]) {
+            let mut digits = vec![(arc & 0x7f) as u8];
+            let mut rest = arc >> 7;
+            while rest > 0 {
+                digits.push((rest & 0x7f) as u8 | 0x80);
+                rest >>= 7;
+            }
+            out.extend(digits.iter().rev());
+        }
+        out
+    }
+
+    fn decode(contents: &[u8], pos: usize) -> Result<Oid, CrypError> {
+        let invalid = |reason| CrypError::InvalidDer { pos, reason };
+        if contents.is_empty() || contents[contents.len() - 1] & 0x80 != 0 {
+            return Err(invalid("truncated object identifier"));
+        }
+
+        let mut values = Vec::new();
+        let mut value: u64 = 0;
+        let mut started = false;
+        for &b in contents {
+            if !started && b == 0x80 {
+                return Err(invalid("non-minimal object identifier arc"));
+            }
+            if value > u64::MAX >> 7 {
+                return Err(invalid("object identifier arc too large"));
+            }
+            value = (value << 7) | (b & 0x7f) as u64;
+            started = b & 0x80 != 0;
+            if !started {
+                values.push(value);
+                value = 0;
+            }
+        }
+
+        let first = values[0];
+        let (a, b) = if first < 80 { (first / 40, first % 40) } else { (2, first - 80) };
+        let mut arcs = vec![a, b];
+        arcs.extend_from_slice(&values[1..]);
+        Ok(Oid(arcs))
+    }
+}
+
+impl fmt::Display for Oid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arcs: Vec<String> = self.0.iter().map(u64::to_string).collect();
+        write!(f, "{}", arcs.join("."))
+    }
+}
+
+impl FromStr for Oid {
+    type Err = CrypError;
+
+    fn from_str(s: &str) -> Result<Oid, CrypError> {
+        let arcs = s.split('.')
+            .map(|arc| arc.parse::<u64>().map_err(|_| CrypError::InvalidOid))
+            .collect::<Result<Vec<u64>, CrypError>>()?;
+        Oid::new(&arcs)
+    }
+}
+
+/// A decoded DER value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Der {
+    /// Big-endian two's complement contents.
+    Integer(Vec<u8>),
+    BitString { unused_bits: u8, data: Vec<u8> },
+    OctetString(Vec<u8>),
+    Null,
+    Oid(Oid),
+    Sequence(Vec<Der>),
+    Set(Vec<Der>),
+    /// A constructed context-specific tag `[number]`, e.g. an explicitly tagged optional field.
+    ContextSpecific { number: u8, contents: Vec<Der> },
+    /// Any other tag, kept as its raw contents.
+    Other { tag: u8, contents: Vec<u8> },
+}
+
+impl Der {
+    /// An INTEGER holding the unsigned big-endian number `magnitude`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::der::Der;
+    ///
+    /// assert_eq!(Der::uint(&[0x80]).to_der(), [0x02, 0x02, 0x00, 0x80]);
+    /// assert_eq!(Der::uint(&[0x00, 0x00, 0x01]).to_der(), [0x02, 0x01, 0x01]);
+    /// assert_eq!(Der::uint(&[]).to_der(), [0x02, 0x01, 0x00]);
+    /// assert_eq!(Der::uint(&[0x80]).as_uint(), Some(&[0x80][..]));
+    /// ```
+    pub fn uint(magnitude: &[u8]) -> Der {
+        let start = magnitude.iter().position(|&b| b != 0).unwrap_or(magnitude.len());
+        let magnitude = &magnitude[start..];
+        let mut contents = Vec::with_capacity(magnitude.len() + 1);
+        if magnitude.first().is_none_or(|&b| b & 0x80 != 0) {
+            contents.push(0);
+        }
+        contents.extend_from_slice(magnitude);
+        Der::Integer(contents)
+    }
+
+    /// An INTEGER holding a small unsigned number such as a version field.
+    pub fn small_uint(value: u64) -> Der {
+        Der::uint(&value.to_be_bytes())
+    }
+
+    /// The magnitude of a non-negative INTEGER, without leading zero bytes (`[]` for zero).
+    pub fn as_uint(&self) -> Option<&[u8]> {
+        match self {
+            Der::Integer(contents) if contents.first().is_some_and(|&b| b & 0x80 == 0) => {
+                let start = contents.iter().position(|&b| b != 0).unwrap_or(contents.len());
+                Some(&contents[start..])
+            }
+            _ => None,
+        }
+    }
+
+    /// The value of a non-negative INTEGER that fits in a `u64`.
+    pub fn as_small_uint(&self) -> Option<u64> {
+        let magnitude = self.as_uint()?;
+        (magnitude.len() <= 8).then(|| magnitude.iter().fold(0, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    pub fn as_sequence(&self) -> Option<&[Der]> {
+        match self {
+            Der::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_octet_string(&self) -> Option<&[u8]> {
+        match self {
+            Der::OctetString(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// The contents of a BIT STRING made of whole bytes.
+    pub fn as_bit_string(&self) -> Option<&[u8]> {
+        match self {
+            Der::BitString { unused_bits: 0, data } => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn as_oid(&self) -> Option<&Oid> {
+        match self {
+            Der::Oid(oid) => Some(oid),
+            _ => None,
+        }
+    }
+
+    /// The identifier octet of this value.
+    pub fn tag(&self) -> u8 {
+        match self {
+            Der::Integer(_) => tag::INTEGER,
+            Der::BitString { .. } => tag::BIT_STRING,
+            Der::OctetString(_) => tag::OCTET_STRING,
+            Der::Null => tag::NULL,
+            Der::Oid(_) => tag::OID,
+            Der::Sequence(_) => tag::SEQUENCE,
+            Der::Set(_) => tag::SET,
+            Der::ContextSpecific { number, .. } => tag::CONTEXT_CONSTRUCTED | number,
+            Der::Other { tag, .. } => *tag,
+        }
+    }
+
+    /// Encodes this value as DER.
+    pub fn to_der(&self) -> Vec<u8> {
+        let contents = match self {
+            Der::Integer(contents) | Der::OctetString(contents) | Der::Other { contents, .. } => contents.clone(),
+            Der::BitString { unused_bits, data } => [&[*unused_bits][..], data].concat(),
+            Der::Null => Vec::new(),
+            Der::Oid(oid) => oid.encode(),
+            Der::Sequence(items) | Der::Set(items) | Der::ContextSpecific { contents: items, .. } => {
+                items.iter().flat_map(Der::to_der).collect()
+            }
+        };
+
+        let mut out = vec![self.tag()];
+        encode_length(contents.len(), &mut out);
+        out.extend_from_slice(&contents);
+        out
+    }
+}
+
+fn encode_length(len: usize, out: &mut Vec<u8>) {
+    if len < 0x80 {
+        out.push(len as u8);
+        return;
+    }
+    let bytes = len.to_be_bytes();
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len() - 1);
+    out.push(0x80 | (bytes.len() - start) as u8);
+    out.extend_from_slice(&bytes[start..]);
+}
+
+/// Deepest nesting of constructed values `parse` accepts, so hostile input can't exhaust the
+/// stack.
+const MAX_DEPTH: usize = 64;
+
+/// Reads consecutive DER values from a slice. `base` is the slice's offset in the whole input,
+/// used for error positions, and `depth` the number of constructed values it is nested in.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    base: usize,
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, reason: &'static str) -> CrypError {
+        CrypError::InvalidDer { pos: self.base + self.pos, reason }
+    }
+
+    fn byte(&mut self) -> Result<u8, CrypError> {
+        let b = *self.data.get(self.pos).ok_or_else(|| self.error("unexpected end of input"))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn length(&mut self) -> Result<usize, CrypError> {
+        let first = self.byte()?;
+        if first < 0x80 {
+            return Ok(first as usize);
+        }
+
+        let count = (first & 0x7f) as usize;
+        if count == 0 {
+            return Err(self.error("indefinite length is not allowed in DER"));
+        }
+        if count > std::mem::size_of::<usize>() {
+            return Err(self.error("length too large"));
+        }
+        let mut len = 0usize;
+        for i in 0..count {
+            let b = self.byte()?;
+            if i == 0 && b == 0 {
+                return Err(self.error("non-minimal length"));
+            }
+            len = (len << 8) | b as usize;
+        }
+        if len < 0x80 {
+            return Err(self.error("non-minimal length"));
+        }
+        Ok(len)
+    }
+
+    fn read(&mut self) -> Result<Der, CrypError> {
+        let start = self.pos;
+        let tag = self.byte()?;
+        if tag & 0x1f == 0x1f {
+            return Err(self.error("high tag numbers are not supported"));
+        }
+        let len = self.length()?;
+        let contents_pos = self.pos;
+        let contents = self.pos.checked_add(len)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or_else(|| self.error("length exceeds input"))?;
+        self.pos += len;
+
+        let base = self.base + contents_pos;
+        let depth = self.depth + 1;
+        let invalid = |reason| CrypError::InvalidDer { pos: self.base + start, reason };
+        Ok(match tag {
+            tag::INTEGER => {
+                match contents {
+                    [] => return Err(invalid("empty integer")),
+                    [0x00, next, ..] if next & 0x80 == 0 => return Err(invalid("non-minimal integer")),
+                    [0xff, next, ..] if next & 0x80 != 0 => return Err(invalid("non-minimal integer")),
+                    _ => Der::Integer(contents.to_vec()),
+                }
+            }
+            tag::BIT_STRING => match contents {
+                [unused_bits, data @ ..] if *unused_bits < 8 && (*unused_bits == 0 || !data.is_empty()) => {
+                    Der::BitString { unused_bits: *unused_bits, data: data.to_vec() }
+                }
+                _ => return Err(invalid("malformed bit string")),
+            },
+            tag::OCTET_STRING => Der::OctetString(contents.to_vec()),
+            tag::NULL if contents.is_empty() => Der::Null,
+            tag::NULL => return Err(invalid("null with contents")),
+            tag::OID => Der::Oid(Oid::decode(contents, base)?),
+            tag::SEQUENCE => Der::Sequence(read_all(contents, base, depth)?),
+            tag::SET => Der::Set(read_all(contents, base, depth)?),
+            t if t & 0xe0 == tag::CONTEXT_CONSTRUCTED => {
+                Der::ContextSpecific { number: t & 0x1f, contents: read_all(contents, base, depth)? }
+            }
+            t => Der::Other { tag: t, contents: contents.to_vec() },
+        })
+    }
+}
+
+fn read_all(data: &[u8], base: usize, depth: usize) -> Result<Vec<Der>, CrypError> {
+    if depth > MAX_DEPTH {
+        return Err(CrypError::InvalidDer { pos: base, reason: "nesting too deep" });
+    }
+    let mut reader = Reader { data, pos: 0, base, depth };
+    let mut items = Vec::new();
+    while reader.pos < data.len() {
+        items.push(reader.read()?);
+    }
+    Ok(items)
+}
+
+/// Parses a single DER value that spans all of `data`.
+///
+/// # Returns
+/// The value, or `CrypError::InvalidDer` with the byte offset of the problem. Encodings that
+/// BER allows but DER forbids (indefinite or non-minimal lengths, non-minimal integers) are
+/// rejected, as are values nested more than 64 levels deep.
+///
+/// # Example
+/// ```rust
+/// use cryp::der::{parse, Der};
+///
+/// assert_eq!(parse(&[0x02, 0x01, 0x05]).unwrap(), Der::small_uint(5));
+/// assert!(parse(&[0x02, 0x02, 0x00, 0x05]).is_err()); // non-minimal integer
+/// assert!(parse(&[0x30, 0x80, 0x00, 0x00]).is_err()); // indefinite length
+/// assert!(parse(&[0x05, 0x00, 0x05, 0x00]).is_err()); // trailing data
+/// // A length far beyond the input, close to usize::MAX.
+/// assert!(parse(&[0x04, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]).is_err());
+///
+/// let nest = |depth| (0..depth).fold(Der::Null, |inner, _| Der::Sequence(vec![inner]));
+/// assert_eq!(parse(&nest(64).to_der()).unwrap(), nest(64));
+/// assert!(parse(&nest(65).to_der()).is_err());
+/// ```
+pub fn parse(data: &[u8]) -> Result<Der, CrypError> {
+    let mut reader = Reader { data, pos: 0, base: 0, depth: 0 };
+    let value = reader.read()?;
+    if reader.pos != data.len() {
+        return Err(reader.error("trailing data after value"));
+    }
+    Ok(value)
+}
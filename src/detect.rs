@@ -0,0 +1,211 @@
+//! Guessing the encoding of an unknown blob ("what is this?").
+//!
+//! [`detect_encoding`] tries every decoder that accepts the input and ranks the results by a
+//! confidence built from two parts: how specific the input looks for the encoding (a string of
+//! hex digits is far more likely hex than base64, even though it is valid base64 too) and how
+//! much the decoded bytes look like plaintext, judged with `score_text` and `is_valid_text`.
+//! [`peel`] applies the best guess repeatedly to unwrap layered encodings.
+
+use crate::codec::{self, Codec};
+use crate::{is_valid_text, score_text, Base64Config, CrypError};
+
+/// An encoding `detect_encoding` can recognize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Hex,
+    Base64,
+    Base64Url,
+    Base32,
+    Base58,
+    Ascii85,
+    /// Decimal byte values such as `72 101 108` or `[72, 101, 108]`.
+    Decimal,
+    /// URL percent-encoding (`%2F`).
+    Percent,
+    /// Not encoded: the input bytes themselves.
+    Raw,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 9] = [
+        Encoding::Hex,
+        Encoding::Base64,
+        Encoding::Base64Url,
+        Encoding::Base32,
+        Encoding::Base58,
+        Encoding::Ascii85,
+        Encoding::Decimal,
+        Encoding::Percent,
+        Encoding::Raw,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Raw => "raw",
+            other => other.codec().map(|codec| codec.name()).unwrap_or_default(),
+        }
+    }
+
+    fn codec(self) -> Option<Box<dyn Codec>> {
+        Some(match self {
+            Encoding::Hex => Box::new(codec::Hex),
+            Encoding::Base64 => Box::new(codec::Base64(Base64Config::STANDARD)),
+            Encoding::Base64Url => Box::new(codec::Base64(Base64Config::URL_SAFE)),
+            Encoding::Base32 => Box::new(codec::Base32 { padding: true }),
+            Encoding::Base58 => Box::new(codec::Base58),
+            Encoding::Ascii85 => Box::new(codec::Ascii85),
+            Encoding::Decimal => Box::new(codec::Decimal),
+            Encoding::Percent => Box::new(codec::Percent),
+            Encoding::Raw => return None,
+        })
+    }
+
+    /// Decodes `text` with this encoding (`Raw` returns the bytes unchanged).
+    pub fn decode(self, text: &str) -> Result<Vec<u8>, CrypError> {
+        match self.codec() {
+            Some(codec) => codec.decode(text),
+            None => Ok(text.as_bytes().to_vec()),
+        }
+    }
+
+    /// How specific `text` looks for this encoding, in `0.0..=1.0`, assuming it decodes.
+    fn prior(self, text: &str) -> f64 {
+        let has = |chars: &str| text.chars().any(|c| chars.contains(c));
+        let has_lower = text.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = text.chars().any(|c| c.is_ascii_uppercase());
+        let symbols = text.chars().filter(|c| !c.is_whitespace()).count();
+        let tokens = text.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()).count();
+
+        match self {
+            Encoding::Decimal if tokens > 1 => 0.95,
+            Encoding::Decimal => 0.3,
+            Encoding::Percent if text.contains('%') => 0.9,
+            Encoding::Percent => 0.0,
+            Encoding::Hex if has_lower && has_upper => 0.6,
+            Encoding::Hex => 0.9,
+            Encoding::Base32 if has_lower && has_upper => 0.3,
+            Encoding::Base32 if symbols % 8 == 0 => 0.85,
+            Encoding::Base32 => 0.65,
+            Encoding::Base64 if has("+/") => 0.85,
+            Encoding::Base64 if symbols % 4 == 0 => 0.75,
+            Encoding::Base64 => 0.6,
+            Encoding::Base64Url if has("-_") => 0.85,
+            Encoding::Base64Url => 0.5,
+            Encoding::Base58 => 0.55,
+            Encoding::Ascii85 if text.starts_with("<~") && text.ends_with("~>") => 0.95,
+            Encoding::Ascii85 => 0.3,
+            Encoding::Raw => 0.1,
+        }
+    }
+}
+
+/// How much `data` looks like human-readable text, in `0.0..=1.0`.
+///
+/// Combines the share of printable bytes, `score_text` (letters, digits and spaces), whether
+/// spaces occur at a word-like rate, and `is_valid_text`.
+pub fn text_likeness(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let len = data.len() as f64;
+    let printable = data.iter().filter(|b| b.is_ascii_graphic() || b.is_ascii_whitespace()).count() as f64 / len;
+    let words = score_text(data) as f64 / 100.0;
+    let spaces = data.iter().filter(|&&b| b == b' ').count() as f64 / len;
+    let prose = if (0.05..=0.35).contains(&spaces) { 1.0 } else { 0.5 };
+    let valid = if is_valid_text(data) { 1.0 } else { 0.0 };
+
+    printable * (0.4 * words + 0.4 * prose + 0.2 * valid)
+}
+
+/// A possible decoding of the input, as returned by [`detect_candidates`].
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub encoding: Encoding,
+    pub confidence: f64,
+    pub decoded: Vec<u8>,
+}
+
+/// Decodes `text` with every encoding that accepts it, most likely first.
+pub fn detect_candidates(text: &str) -> Vec<Candidate> {
+    let text = text.trim();
+    let mut candidates: Vec<Candidate> = Encoding::ALL.iter()
+        .filter_map(|&encoding| {
+            let decoded = encoding.decode(text).ok().filter(|decoded| !decoded.is_empty())?;
+            let confidence = match encoding {
+                Encoding::Raw => encoding.prior(text) + 0.5 * text_likeness(&decoded),
+                _ => encoding.prior(text) * (0.5 + 0.5 * text_likeness(&decoded)),
+            };
+            (confidence > 0.0).then_some(Candidate { encoding, confidence, decoded })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    candidates
+}
+
+/// Ranks the encodings that `text` could be in, most likely first, with a confidence in
+/// `0.0..=1.0`. `Encoding::Raw` is always a candidate for non-empty input.
+///
+/// # Example
+/// ```rust
+/// use cryp::detect::{detect_encoding, Encoding};
+///
+/// assert_eq!(detect_encoding("48656c6c6f2c20776f726c6421")[0].0, Encoding::Hex);
+/// assert_eq!(detect_encoding("SGVsbG8sIHdvcmxkIQ==")[0].0, Encoding::Base64);
+/// assert_eq!(detect_encoding("JBSWY3DPFQQHO33SNRSCC===")[0].0, Encoding::Base32);
+/// assert_eq!(detect_encoding("72, 101, 108, 108, 111")[0].0, Encoding::Decimal);
+/// assert_eq!(detect_encoding("Hello%2C%20world%21")[0].0, Encoding::Percent);
+/// assert_eq!(detect_encoding("Hello, world!")[0].0, Encoding::Raw);
+/// ```
+pub fn detect_encoding(text: &str) -> Vec<(Encoding, f64)> {
+    detect_candidates(text)
+        .into_iter()
+        .map(|candidate| (candidate.encoding, candidate.confidence))
+        .collect()
+}
+
+/// One decoding step applied by [`peel`].
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub encoding: Encoding,
+    pub confidence: f64,
+    /// The bytes after removing this layer.
+    pub data: Vec<u8>,
+}
+
+/// Repeatedly decodes `text` with its most likely encoding, up to `max_depth` times.
+///
+/// Stops when the best candidate is `Raw` (the data already looks like plaintext) or when a
+/// layer decodes to bytes that are not UTF-8 text. The last layer holds the final result; an
+/// empty list means `text` did not look encoded at all.
+///
+/// # Example
+/// ```rust
+/// use cryp::detect::{peel, Encoding};
+/// use cryp::{base64_encode, bytes_to_hexstr};
+///
+/// let layered = base64_encode(bytes_to_hexstr(base64_encode(b"attack at dawn").as_bytes()).as_bytes());
+/// let layers = peel(&layered, 10);
+/// let encodings: Vec<Encoding> = layers.iter().map(|layer| layer.encoding).collect();
+/// assert_eq!(encodings, [Encoding::Base64, Encoding::Hex, Encoding::Base64]);
+/// assert_eq!(layers.last().unwrap().data, b"attack at dawn");
+/// ```
+pub fn peel(text: &str, max_depth: usize) -> Vec<Layer> {
+    let mut layers: Vec<Layer> = Vec::new();
+    let mut current = text.to_string();
+
+    for _ in 0..max_depth {
+        let best = match detect_candidates(&current).into_iter().next() {
+            Some(best) if best.encoding != Encoding::Raw => best,
+            _ => break,
+        };
+
+        let next = String::from_utf8(best.decoded.clone()).ok();
+        layers.push(Layer { encoding: best.encoding, confidence: best.confidence, data: best.decoded });
+        match next {
+            Some(text) => current = text,
+            None => break,
+        }
+    }
+    layers
+}
@@ -0,0 +1,421 @@
+//! Byte-level statistics for telling text, structured data, obfuscation and encryption apart.
+//!
+//! [`ByteStats`] summarizes a whole buffer the way `ent` does (entropy, chi-squared against
+//! uniform bytes, serial correlation, mean, compressibility), and [`entropy_profile`] slides a
+//! window over a larger blob to find the encrypted or compressed regions inside it.
+//!
+//! # Example
+//! ```rust
+//! use cryp::entropy::{ByteStats, DataKind};
+//! use cryp::xor_repeating;
+//!
+//! let text = b"It was the best of times, it was the worst of times, it was the age of wisdom, \
+//!              it was the age of foolishness, it was the epoch of belief, it was the epoch of \
+//!              incredulity, it was the season of Light, it was the season of Darkness";
+//! let stats = ByteStats::new(text);
+//! assert_eq!(stats.kind(), DataKind::Text);
+//! assert!(stats.entropy > 3.5 && stats.entropy < 4.5);
+//!
+//! let obfuscated = xor_repeating(text, b"secret").unwrap();
+//! assert_eq!(ByteStats::new(&obfuscated).kind(), DataKind::Obfuscated);
+//! ```
+
+use std::ops::Range;
+
+use crate::CrypError;
+
+/// Bytes the LZ77 estimate looks back over.
+const LZ_WINDOW: usize = 32 * 1024;
+
+/// Shortest and longest match the LZ77 estimate encodes.
+const LZ_MIN_MATCH: usize = 3;
+const LZ_MAX_MATCH: usize = 258;
+
+/// Estimated cost in bits of a literal byte and of a (distance, length) match.
+const LZ_LITERAL_BITS: usize = 9;
+const LZ_MATCH_BITS: usize = 24;
+
+/// Share of printable bytes above which data counts as text.
+const TEXT_THRESHOLD: f64 = 0.95;
+
+/// Entropy, as a share of the most a buffer of its length can have, above which data looks
+/// random.
+const RANDOM_ENTROPY: f64 = 0.95;
+
+/// Compression ratio below which high-entropy data is not random.
+const RANDOM_COMPRESSION: f64 = 0.98;
+
+/// Standard deviations from its expected value the chi-squared statistic may stray before
+/// high-entropy data counts as compressed rather than random.
+const RANDOM_CHI_SQUARED_SIGMAS: f64 = 4.0;
+
+/// Expected count per byte value needed for the chi-squared test to mean anything.
+const CHI_SQUARED_MIN_EXPECTED: usize = 5;
+
+/// Statistics of the byte distribution of a buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ByteStats {
+    /// How often each byte value occurs.
+    pub histogram: [u64; 256],
+    pub len: usize,
+    /// Shannon entropy in bits per byte: 0 for constant data, 8 for uniform random bytes.
+    pub entropy: f64,
+    /// Pearson's chi-squared statistic against uniformly distributed bytes (255 degrees of
+    /// freedom, so about 255 for random data).
+    pub chi_squared: f64,
+    /// Correlation between each byte and the next (wrapping around): near 0 for random data,
+    /// positive for smooth data such as audio or text, 0 if every byte is the same.
+    pub serial_correlation: f64,
+    /// The arithmetic mean of the bytes, 127.5 for random data.
+    pub mean: f64,
+    /// Estimated compressed size over the original size (see `compression_ratio`).
+    pub compression_ratio: f64,
+    /// Share of printable ASCII and whitespace bytes.
+    pub printable: f64,
+}
+
+/// What a buffer most likely holds, judging by its `ByteStats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataKind {
+    /// No bytes at all.
+    Empty,
+    /// Mostly printable ASCII.
+    Text,
+    /// Indistinguishable from uniform random bytes: encrypted, or very well compressed.
+    Random,
+    /// High entropy, but measurably not uniform, as compressed data usually is.
+    Compressed,
+    /// Text run through a simple transformation such as XOR with a short key: 7-bit bytes that
+    /// aren't printable, text-like entropy over unusual byte values, or repetition without
+    /// correlation between neighbouring bytes.
+    Obfuscated,
+    /// Anything else, such as binary file formats and executables.
+    Structured,
+}
+
+impl DataKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            DataKind::Empty => "empty",
+            DataKind::Text => "text",
+            DataKind::Random => "random (encrypted?)",
+            DataKind::Compressed => "compressed",
+            DataKind::Obfuscated => "obfuscated text (XOR?)",
+            DataKind::Structured => "structured binary",
+        }
+    }
+}
+
+impl ByteStats {
+    /// Computes the statistics of `data`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::entropy::ByteStats;
+    ///
+    /// let all_bytes: Vec<u8> = (0..=255).collect();
+    /// let stats = ByteStats::new(&all_bytes);
+    /// assert_eq!(stats.entropy, 8.0);
+    /// assert_eq!(stats.chi_squared, 0.0);
+    /// assert_eq!(stats.mean, 127.5);
+    ///
+    /// let stats = ByteStats::new(&[0x41; 100]);
+    /// assert_eq!(stats.entropy, 0.0);
+    /// assert_eq!(stats.serial_correlation, 0.0);
+    /// assert!(stats.compression_ratio < 0.2);
+    /// ```
+    pub fn new(data: &[u8]) -> ByteStats {
+        let histogram = histogram(data);
+        let n = data.len() as f64;
+
+        let expected = n / 256.0;
+        let chi_squared = if data.is_empty() {
+            0.0
+        } else {
+            histogram.iter().map(|&count| (count as f64 - expected).powi(2) / expected).sum()
+        };
+        let mean = if data.is_empty() { 0.0 } else { data.iter().map(|&b| b as f64).sum::<f64>() / n };
+        let printable = histogram.iter()
+            .enumerate()
+            .filter(|&(b, _)| is_printable(b as u8))
+            .map(|(_, &count)| count as f64)
+            .sum::<f64>() / n.max(1.0);
+
+        ByteStats {
+            histogram,
+            len: data.len(),
+            entropy: histogram_entropy(&histogram, data.len()),
+            chi_squared,
+            serial_correlation: serial_correlation(data),
+            mean,
+            compression_ratio: compression_ratio(data),
+            printable,
+        }
+    }
+
+    /// The most entropy a buffer of this length can have: 8 bits, or fewer when there are
+    /// fewer than 256 bytes to spread over the byte values.
+    pub fn max_entropy(&self) -> f64 {
+        (self.len as f64).log2().clamp(0.0, 8.0)
+    }
+
+    /// How far `chi_squared` lies from its expected value for random bytes, in standard
+    /// deviations; `None` when there are too few bytes for the test to be meaningful.
+    pub fn chi_squared_sigmas(&self) -> Option<f64> {
+        (self.len >= 256 * CHI_SQUARED_MIN_EXPECTED).then(|| (self.chi_squared - 255.0) / 510f64.sqrt())
+    }
+
+    /// A rough guess at what the data is, from the statistics alone.
+    pub fn kind(&self) -> DataKind {
+        if self.len == 0 {
+            return DataKind::Empty;
+        }
+        if self.printable >= TEXT_THRESHOLD {
+            return DataKind::Text;
+        }
+        if self.entropy >= RANDOM_ENTROPY * self.max_entropy() {
+            let uniform = self.chi_squared_sigmas().is_none_or(|sigmas| sigmas.abs() <= RANDOM_CHI_SQUARED_SIGMAS);
+            return if uniform && self.compression_ratio >= RANDOM_COMPRESSION { DataKind::Random } else { DataKind::Compressed };
+        }
+
+        // XOR of text with a printable key never sets the high bit; XOR with a single byte
+        // keeps the entropy and the few distinct values of text while moving them; XOR with a
+        // longer binary key still repeats with the key, yet breaks the correlation between
+        // neighbouring bytes that file formats and executables have.
+        let high_bit: u64 = self.histogram[0x80..].iter().sum();
+        let seven_bit = (high_bit as f64) < 0.01 * self.len as f64;
+        let distinct = self.histogram.iter().filter(|&&count| count > 0).count();
+        let text_like = (3.5..=5.5).contains(&self.entropy) && distinct <= 100;
+        let keyed = self.compression_ratio < 0.95 && self.serial_correlation.abs() < 0.15;
+        if seven_bit || text_like || keyed {
+            DataKind::Obfuscated
+        } else {
+            DataKind::Structured
+        }
+    }
+}
+
+fn is_printable(b: u8) -> bool {
+    (0x20..0x7f).contains(&b) || matches!(b, b'\t' | b'\n' | b'\r')
+}
+
+fn histogram(data: &[u8]) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    for &b in data {
+        histogram[b as usize] += 1;
+    }
+    histogram
+}
+
+/// The Shannon entropy in bits per byte of `len` bytes counted in `histogram`.
+fn histogram_entropy(histogram: &[u64; 256], len: usize) -> f64 {
+    let n = len as f64;
+    histogram.iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / n;
+            -p * p.log2()
+        })
+        .sum::<f64>()
+        .max(0.0)
+}
+
+/// The Shannon entropy of `data` in bits per byte.
+///
+/// # Example
+/// ```rust
+/// use cryp::entropy::shannon_entropy;
+///
+/// assert_eq!(shannon_entropy(b""), 0.0);
+/// assert_eq!(shannon_entropy(b"aaaa"), 0.0);
+/// assert_eq!(shannon_entropy(b"abab"), 1.0);
+/// assert_eq!(shannon_entropy(b"abcd"), 2.0);
+/// ```
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    histogram_entropy(&histogram(data), data.len())
+}
+
+fn serial_correlation(data: &[u8]) -> f64 {
+    let n = data.len() as f64;
+    let (mut sum, mut squares, mut products) = (0.0, 0.0, 0.0);
+    for (i, &b) in data.iter().enumerate() {
+        let (x, next) = (b as f64, data[(i + 1) % data.len()] as f64);
+        sum += x;
+        squares += x * x;
+        products += x * next;
+    }
+    let denominator = n * squares - sum * sum;
+    if denominator == 0.0 {
+        0.0
+    } else {
+        (n * products - sum * sum) / denominator
+    }
+}
+
+/// Estimates how well `data` compresses: the size of a greedy LZ77 encoding (a 32 KiB window,
+/// 9 bits per literal and 24 per match of 3 to 258 bytes) over the original size. Random data
+/// comes out slightly above 1; text about 0.5 to 0.8 depending on length; repetitive data far
+/// below. Real compressors do better, but the ordering is the same.
+///
+/// # Example
+/// ```rust
+/// use cryp::entropy::compression_ratio;
+///
+/// assert_eq!(compression_ratio(b""), 0.0);
+/// let repetitive = b"abcabcabcabcabcabcabcabcabcabcabcabc";
+/// assert!(compression_ratio(repetitive) < 0.3);
+/// let varied: Vec<u8> = (0..=255).collect();
+/// assert!(compression_ratio(&varied) > 1.0);
+/// ```
+pub fn compression_ratio(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    // The last position every 3-byte prefix was seen at.
+    let mut last_seen = vec![usize::MAX; 1 << 16];
+    let hash = |i: usize| {
+        let key = (data[i] as usize) << 16 | (data[i + 1] as usize) << 8 | data[i + 2] as usize;
+        (key.wrapping_mul(2_654_435_761) >> 8) & 0xffff
+    };
+
+    let mut bits = 0;
+    let mut i = 0;
+    while i < data.len() {
+        let mut matched = 0;
+        if i + LZ_MIN_MATCH <= data.len() {
+            let h = hash(i);
+            let candidate = last_seen[h];
+            last_seen[h] = i;
+            if candidate != usize::MAX && i - candidate <= LZ_WINDOW {
+                matched = data[i..].iter()
+                    .zip(&data[candidate..])
+                    .take(LZ_MAX_MATCH)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+            }
+        }
+        if matched >= LZ_MIN_MATCH {
+            bits += LZ_MATCH_BITS;
+            // Remember the positions inside the match too, so later repeats can find them.
+            for j in i + 1..(i + matched).min(data.len().saturating_sub(LZ_MIN_MATCH - 1)) {
+                last_seen[hash(j)] = j;
+            }
+            i += matched;
+        } else {
+            bits += LZ_LITERAL_BITS;
+            i += 1;
+        }
+    }
+    bits as f64 / (8 * data.len()) as f64
+}
+
+/// The entropy of one window of an `entropy_profile`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowEntropy {
+    pub offset: usize,
+    pub len: usize,
+    /// Bits per byte.
+    pub entropy: f64,
+}
+
+/// The entropy of every `window` bytes of `data`, moving `step` bytes at a time, to spot
+/// encrypted or compressed regions inside a larger blob. The last window is shortened to end
+/// at the end of the data; data shorter than a window gives a single window.
+///
+/// Keep in mind that a window can't have more than `log2(window)` bits of entropy: use
+/// windows of a few hundred bytes or more to tell random data (close to 8) from text (about
+/// 4.5).
+///
+/// # Errors
+/// - `CrypError::InvalidArgument` if `window` or `step` is 0.
+///
+/// # Example
+/// ```rust
+/// use cryp::entropy::entropy_profile;
+/// use cryp::CrypError;
+///
+/// let mut blob = vec![0u8; 1024];
+/// blob.extend((0..1024u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8));
+/// let profile = entropy_profile(&blob, 512, 512).unwrap();
+/// assert_eq!(profile.len(), 4);
+/// assert_eq!(profile[0].entropy, 0.0);
+/// assert!(profile[3].entropy > 7.0);
+///
+/// let zero_step = entropy_profile(&blob, 512, 0);
+/// assert!(matches!(zero_step, Err(CrypError::InvalidArgument { name: "step", value: 0 })));
+/// ```
+pub fn entropy_profile(data: &[u8], window: usize, step: usize) -> Result<Vec<WindowEntropy>, CrypError> {
+    if window == 0 {
+        return Err(CrypError::InvalidArgument { name: "window", value: window });
+    }
+    if step == 0 {
+        return Err(CrypError::InvalidArgument { name: "step", value: step });
+    }
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut profile = Vec::new();
+    let mut counts = histogram(&data[..window.min(data.len())]);
+    let mut offset = 0;
+    loop {
+        let len = window.min(data.len() - offset);
+        profile.push(WindowEntropy { offset, len, entropy: histogram_entropy(&counts, len) });
+        let next = offset.saturating_add(step);
+        if offset + len >= data.len() || next >= data.len() {
+            return Ok(profile);
+        }
+
+        // Slide the counts along instead of recounting every window.
+        let (old_end, new_end) = (offset + len, next.saturating_add(window).min(data.len()));
+        for &b in &data[offset..next.min(old_end)] {
+            counts[b as usize] -= 1;
+        }
+        if next >= old_end {
+            counts = histogram(&data[next..new_end]);
+        } else {
+            for &b in &data[old_end..new_end] {
+                counts[b as usize] += 1;
+            }
+        }
+        offset = next;
+    }
+}
+
+/// The byte ranges of `data` covered by windows of an `entropy_profile` whose entropy is at
+/// least `threshold` bits per byte, with overlapping or adjacent windows merged.
+///
+/// # Errors
+/// - As `entropy_profile`.
+///
+/// # Example
+/// ```rust
+/// use cryp::entropy::high_entropy_regions;
+///
+/// let random: Vec<u8> = (0..2048u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8).collect();
+/// let mut blob = b"header ".repeat(300);
+/// let start = blob.len();
+/// blob.extend(&random);
+/// blob.extend(b"trailer ".repeat(300));
+///
+/// let regions = high_entropy_regions(&blob, 256, 64, 7.0).unwrap();
+/// assert_eq!(regions.len(), 1);
+/// assert!(regions[0].start.abs_diff(start) <= 256);
+/// assert!(regions[0].end.abs_diff(start + random.len()) <= 256);
+/// ```
+pub fn high_entropy_regions(
+    data: &[u8],
+    window: usize,
+    step: usize,
+    threshold: f64,
+) -> Result<Vec<Range<usize>>, CrypError> {
+    let mut regions: Vec<Range<usize>> = Vec::new();
+    for w in entropy_profile(data, window, step)?.into_iter().filter(|w| w.entropy >= threshold) {
+        match regions.last_mut() {
+            Some(last) if w.offset <= last.end => last.end = last.end.max(w.offset + w.len),
+            _ => regions.push(w.offset..w.offset + w.len),
+        }
+    }
+    Ok(regions)
+}
@@ -0,0 +1,154 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use openssl::error::ErrorStack;
+
+/// The error type returned by every fallible function in this crate.
+#[derive(Debug)]
+pub enum CrypError {
+    /// A character that is not a hex digit, at byte offset `pos` of the input.
+    InvalidHex { pos: usize, char: char },
+    /// Hex input with an odd number of digits.
+    OddHexLength { len: usize },
+    /// A byte outside the base64 alphabet, at byte offset `pos` of the input.
+    InvalidBase64 { pos: usize, byte: u8 },
+    /// Base64 input that does not end on a byte boundary or has misplaced `=` padding.
+    InvalidBase64Padding { pos: usize },
+    /// Base64 whose last character has unused bits set, rejected by strict decoding.
+    NonCanonicalBase64 { pos: usize },
+    /// A character that is not part of `encoding`, at byte offset `pos` of the input.
+    InvalidEncoding { encoding: &'static str, pos: usize, char: char },
+    /// Input whose length (in bytes or characters) is impossible for `encoding`.
+    InvalidLength { encoding: &'static str, len: usize },
+    /// Padding that does not follow PKCS#7.
+    BadPadding,
+    /// A key of the wrong size.
+    BadKeyLength { expected: usize, got: usize },
+    /// Data whose length is not compatible with the block size.
+    BadBlockLength { block_size: usize, len: usize },
+    /// Two inputs that must have equal length don't.
+    LengthMismatch { left: usize, right: usize },
+    /// An empty key where at least one byte is required.
+    EmptyKey,
+    /// Not enough input for the requested analysis, counted in what it works on: bytes,
+    /// letters or bits.
+    NotEnoughData { needed: usize, got: usize },
+    /// An invalid `min..=max` range of key sizes.
+    InvalidRange { min: usize, max: usize },
+    /// A numeric argument with a value the function can't work with, such as a beam width of 0.
+    InvalidArgument { name: &'static str, value: usize },
+    /// A real-valued argument the function can't work with, such as a negative smoothing
+    /// constant.
+    InvalidFloatArgument { name: &'static str, value: f64 },
+    /// A key value with no inverse modulo `modulus`, e.g. an affine multiplier sharing a factor
+    /// with the alphabet size.
+    NotInvertible { value: i64, modulus: usize },
+    /// A hexdump line whose offset lies before data that was already read, or too far past it.
+    BadOffset { expected: usize, got: usize },
+    /// An object identifier with fewer than two arcs or an out-of-range first or second arc.
+    InvalidOid,
+    /// Malformed DER at byte offset `pos`.
+    InvalidDer { pos: usize, reason: &'static str },
+    /// DER that is well formed but not the expected structure, e.g. not an RSA key.
+    UnexpectedDer { expected: &'static str },
+    /// Malformed PEM armor at the 1-based line `line`.
+    InvalidPem { line: usize, reason: &'static str },
+    /// No PEM block with a supported label.
+    MissingPem { expected: &'static str },
+    /// A malformed serialized n-gram model, at the 1-based line `line`.
+    InvalidModel { line: usize, reason: &'static str },
+    /// An n-gram model over the wrong alphabet for the cipher, e.g. a byte model given to a
+    /// solver that scores letters.
+    WrongAlphabet { expected: &'static str, got: &'static str },
+    /// An index past the end of a list of `len` items, e.g. a message number.
+    InvalidIndex { index: usize, len: usize },
+    /// The crib at `index` of a list of known plaintexts that can not be placed.
+    InvalidCrib { index: usize, reason: &'static str },
+    /// A search that ran to completion without finding an answer, e.g. no ciphertext in a list
+    /// that looks like ECB.
+    NoAnswer { reason: &'static str },
+    /// An input file that exists but could not be read.
+    Io { path: PathBuf, source: io::Error },
+    /// An input file found neither on disk nor in the embedded copy.
+    InputNotFound(String),
+    /// An error in a line-oriented input, with its 1-based line number.
+    Line { line: usize, source: Box<CrypError> },
+    /// A failure reported by OpenSSL.
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for CrypError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrypError::InvalidHex { pos, char } => write!(f, "invalid hex digit {:?} at position {}", char, pos),
+            CrypError::OddHexLength { len } => write!(f, "hex input has odd length {}", len),
+            CrypError::InvalidBase64 { pos, byte } => {
+                write!(f, "invalid base64 byte {:?} at position {}", *byte as char, pos)
+            }
+            CrypError::InvalidBase64Padding { pos } => write!(f, "invalid base64 padding at position {}", pos),
+            CrypError::NonCanonicalBase64 { pos } => {
+                write!(f, "non-canonical base64: unused bits set at position {}", pos)
+            }
+            CrypError::InvalidEncoding { encoding, pos, char } => {
+                write!(f, "invalid {} character {:?} at position {}", encoding, char, pos)
+            }
+            CrypError::InvalidLength { encoding, len } => write!(f, "invalid {} length {}", encoding, len),
+            CrypError::BadPadding => write!(f, "invalid PKCS#7 padding"),
+            CrypError::BadKeyLength { expected, got } => {
+                write!(f, "expected a {} byte key, got {} bytes", expected, got)
+            }
+            CrypError::BadBlockLength { block_size, len } => {
+                write!(f, "length {} is not compatible with block size {}", len, block_size)
+            }
+            CrypError::LengthMismatch { left, right } => {
+                write!(f, "inputs should have equal length, got {} and {}", left, right)
+            }
+            CrypError::EmptyKey => write!(f, "key must not be empty"),
+            CrypError::NotEnoughData { needed, got } => {
+                write!(f, "not enough data: need at least {}, got {}", needed, got)
+            }
+            CrypError::InvalidRange { min, max } => write!(f, "invalid range {}..={}", min, max),
+            CrypError::InvalidArgument { name, value } => write!(f, "invalid {} {}", name, value),
+            CrypError::InvalidFloatArgument { name, value } => write!(f, "invalid {} {}", name, value),
+            CrypError::NotInvertible { value, modulus } => write!(f, "{} is not invertible modulo {}", value, modulus),
+            CrypError::BadOffset { expected, got } => {
+                write!(f, "unexpected offset {:#x} after {:#x} bytes", got, expected)
+            }
+            CrypError::InvalidOid => write!(f, "invalid object identifier"),
+            CrypError::InvalidDer { pos, reason } => write!(f, "invalid DER at position {}: {}", pos, reason),
+            CrypError::UnexpectedDer { expected } => write!(f, "DER value is not {}", expected),
+            CrypError::InvalidPem { line, reason } => write!(f, "invalid PEM at line {}: {}", line, reason),
+            CrypError::MissingPem { expected } => write!(f, "no PEM block found for {}", expected),
+            CrypError::InvalidModel { line, reason } => write!(f, "invalid model at line {}: {}", line, reason),
+            CrypError::WrongAlphabet { expected, got } => {
+                write!(f, "expected a model over {}, got one over {}", expected, got)
+            }
+            CrypError::InvalidIndex { index, len } => write!(f, "index {} out of range for {} items", index, len),
+            CrypError::InvalidCrib { index, reason } => write!(f, "crib {}: {}", index, reason),
+            CrypError::NoAnswer { reason } => write!(f, "no answer found: {}", reason),
+            CrypError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            CrypError::InputNotFound(name) => write!(f, "input file not found: {}", name),
+            CrypError::Line { line, source } => write!(f, "line {}: {}", line, source),
+            CrypError::OpenSsl(e) => write!(f, "openssl: {}", e),
+        }
+    }
+}
+
+impl Error for CrypError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CrypError::Io { source, .. } => Some(source),
+            CrypError::Line { source, .. } => Some(source.as_ref()),
+            CrypError::OpenSsl(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorStack> for CrypError {
+    fn from(e: ErrorStack) -> Self {
+        CrypError::OpenSsl(e)
+    }
+}
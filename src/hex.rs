@@ -0,0 +1,296 @@
+//! Tolerant hex parsing and `xxd`/`hexdump -C` style dumps.
+//!
+//! `hexstr_to_bytes` only accepts a bare string of hex digits. [`parse_hex`] also takes the
+//! shapes hex usually arrives in when pasted from Wireshark, debuggers or C sources
+//! (`0x41 0x42`, `41:42`, `\x41\x42`, one byte per line, ...), controlled by [`HexOptions`].
+//! [`hexdump`] and [`xxd`] render bytes with offsets and an ASCII column, and
+//! [`parse_hexdump`] turns either format back into bytes like `xxd -r`.
+
+use std::fmt::Write;
+
+use crate::{hex_digit, CrypError};
+
+/// Bytes shown on each line of a dump.
+pub const DUMP_WIDTH: usize = 16;
+
+/// Largest jump between two offsets `parse_hexdump` fills in (16 MiB), so a corrupt offset
+/// can't make it allocate gigabytes of zeros.
+pub const MAX_OFFSET_GAP: usize = 1 << 24;
+
+/// What `parse_hex` accepts besides hex digits.
+///
+/// # Example
+/// ```rust
+/// use cryp::hex::{parse_hex, HexOptions};
+///
+/// assert_eq!(parse_hex("0x41, 0x42", HexOptions::LENIENT).unwrap(), b"AB");
+/// assert_eq!(parse_hex("\\x41\\x42", HexOptions::LENIENT).unwrap(), b"AB");
+/// assert_eq!(parse_hex("de:ad:be:ef", HexOptions::LENIENT).unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+/// assert!(parse_hex("0x41", HexOptions::STRICT).is_err());
+///
+/// let padded = HexOptions::LENIENT.with_odd_padding(true);
+/// assert_eq!(parse_hex("0xA 0xB 0x10", padded).unwrap(), [0x0a, 0x0b, 0x10]);
+/// assert!(parse_hex("0xA 0xB", HexOptions::LENIENT).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexOptions {
+    /// Skip `0x`/`0X` at the start of a group and `\x` anywhere.
+    pub prefixes: bool,
+    /// Treat whitespace, `:`, `-`, `,` and `;` as separators between groups.
+    pub separators: bool,
+    /// Left-pad groups with an odd number of digits with a zero nibble (`0xA` is `0x0A`).
+    /// Without it such groups are an error.
+    pub pad_odd: bool,
+}
+
+impl HexOptions {
+    /// Bare hex digits only, like `hexstr_to_bytes`.
+    pub const STRICT: HexOptions = HexOptions { prefixes: false, separators: false, pad_odd: false };
+    /// Prefixes and separators allowed, odd-length groups rejected.
+    pub const LENIENT: HexOptions = HexOptions { prefixes: true, separators: true, pad_odd: false };
+
+    /// Returns the same options with odd-length padding turned on or off.
+    pub const fn with_odd_padding(self, pad_odd: bool) -> Self {
+        HexOptions { pad_odd, ..self }
+    }
+}
+
+impl Default for HexOptions {
+    fn default() -> Self {
+        HexOptions::LENIENT
+    }
+}
+
+fn is_separator(ch: char) -> bool {
+    ch.is_whitespace() || matches!(ch, ':' | '-' | ',' | ';')
+}
+
+/// Moves the digits of one group into `bytes`.
+fn flush_group(group: &mut Vec<u8>, bytes: &mut Vec<u8>, options: HexOptions) -> Result<(), CrypError> {
+    if group.len() % 2 == 1 {
+        if !options.pad_odd {
+            return Err(CrypError::OddHexLength { len: group.len() });
+        }
+        group.insert(0, 0);
+    }
+    bytes.extend(group.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    group.clear();
+    Ok(())
+}
+
+/// Parses hex text according to `options`.
+///
+/// # Arguments
+/// * `text` - The hex text.
+/// * `options` - Which prefixes and separators to accept.
+///
+/// # Returns
+/// The decoded bytes, or `CrypError::InvalidHex` with the position of the first unexpected
+/// character, or `CrypError::OddHexLength` with the length of a group that has an odd number of
+/// digits.
+///
+/// # Example
+/// ```rust
+/// use cryp::hex::{parse_hex, HexOptions};
+///
+/// assert_eq!(parse_hex("48 65 6c 6c 6f\n", HexOptions::LENIENT).unwrap(), b"Hello");
+/// assert_eq!(parse_hex("48656C6C6F", HexOptions::STRICT).unwrap(), b"Hello");
+/// assert!(parse_hex("48 65", HexOptions::STRICT).is_err());
+/// ```
+pub fn parse_hex(text: &str, options: HexOptions) -> Result<Vec<u8>, CrypError> {
+    let mut bytes = Vec::new();
+    let mut group = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((pos, ch)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        let escape = ch == '\\' && next == Some('x');
+        let prefix = ch == '0' && matches!(next, Some('x' | 'X')) && group.is_empty();
+
+        if options.prefixes && (escape || prefix) {
+            flush_group(&mut group, &mut bytes, options)?;
+            chars.next();
+        } else if options.separators && is_separator(ch) {
+            flush_group(&mut group, &mut bytes, options)?;
+        } else {
+            group.push(hex_digit(pos, ch)?);
+        }
+    }
+    flush_group(&mut group, &mut bytes, options)?;
+    Ok(bytes)
+}
+
+fn ascii_column(chunk: &[u8]) -> String {
+    chunk.iter()
+        .map(|&b| if (0x20..0x7f).contains(&b) { b as char } else { '.' })
+        .collect()
+}
+
+/// Renders `data` like `hexdump -C` (without squeezing repeated lines).
+///
+/// # Example
+/// ```rust
+/// use cryp::hex::hexdump;
+///
+/// assert_eq!(
+///     hexdump(b"Hello, world!\n"),
+///     "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a        |Hello, world!.|\n\
+///      0000000e\n"
+/// );
+/// ```
+pub fn hexdump(data: &[u8]) -> String {
+    let mut out = String::new();
+    for (line, chunk) in data.chunks(DUMP_WIDTH).enumerate() {
+        write!(out, "{:08x}  ", line * DUMP_WIDTH).unwrap();
+        for i in 0..DUMP_WIDTH {
+            match chunk.get(i) {
+                Some(b) => write!(out, "{:02x} ", b).unwrap(),
+                None => out.push_str("   "),
+            }
+            if i % 8 == 7 {
+                out.push(' ');
+            }
+        }
+        writeln!(out, "|{}|", ascii_column(chunk)).unwrap();
+    }
+    if !data.is_empty() {
+        writeln!(out, "{:08x}", data.len()).unwrap();
+    }
+    out
+}
+
+/// Renders `data` like `xxd`.
+///
+/// # Example
+/// ```rust
+/// use cryp::hex::xxd;
+///
+/// assert_eq!(
+///     xxd(b"Hello, world!\n"),
+///     "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a       Hello, world!.\n"
+/// );
+/// ```
+pub fn xxd(data: &[u8]) -> String {
+    let mut out = String::new();
+    for (line, chunk) in data.chunks(DUMP_WIDTH).enumerate() {
+        write!(out, "{:08x}: ", line * DUMP_WIDTH).unwrap();
+        for i in 0..DUMP_WIDTH {
+            match chunk.get(i) {
+                Some(b) => write!(out, "{:02x}", b).unwrap(),
+                None => out.push_str("  "),
+            }
+            if i % 2 == 1 {
+                out.push(' ');
+            }
+        }
+        writeln!(out, " {}", ascii_column(chunk)).unwrap();
+    }
+    out
+}
+
+/// Parses a hex offset. The digit that makes it overflow `usize` is reported as invalid.
+fn parse_offset(token: &str) -> Result<usize, CrypError> {
+    token.char_indices().try_fold(0usize, |offset, (pos, ch)| {
+        let digit = hex_digit(pos, ch)? as usize;
+        offset.checked_mul(16)
+            .and_then(|offset| offset.checked_add(digit))
+            .ok_or(CrypError::InvalidHex { pos, char: ch })
+    })
+}
+
+/// Shifts the position of an `InvalidHex` error by `by` characters.
+fn shift_position(error: CrypError, by: usize) -> CrypError {
+    match error {
+        CrypError::InvalidHex { pos, char } => CrypError::InvalidHex { pos: pos + by, char },
+        other => other,
+    }
+}
+
+/// Parses one dump line into its offset and bytes.
+fn parse_dump_line(line: &str) -> Result<(usize, Vec<u8>), CrypError> {
+    let offset_end = line.find(|c: char| c == ':' || c.is_whitespace()).unwrap_or(line.len());
+    let offset = parse_offset(&line[..offset_end])?;
+
+    let rest = &line[offset_end..];
+    let hex = match rest.strip_prefix(':') {
+        // xxd: the hex columns end at the first double space, before the ASCII column.
+        Some(rest) => rest.trim_start_matches(' ').split("  ").next().unwrap_or(""),
+        // hexdump -C: the ASCII column is enclosed in `|`.
+        None => rest.split('|').next().unwrap_or(""),
+    };
+    let start = hex.as_ptr() as usize - line.as_ptr() as usize;
+    let bytes = parse_hex(hex, HexOptions::LENIENT).map_err(|e| shift_position(e, start))?;
+    Ok((offset, bytes))
+}
+
+/// Parses `xxd` or `hexdump -C` output back into bytes, like `xxd -r`.
+///
+/// Lines start with an offset; gaps between offsets are filled with zeros, and a `*` line
+/// (squeezed output of `hexdump -C`) repeats the previous line up to the next offset. The
+/// trailing offset-only line of `hexdump -C` is accepted.
+///
+/// # Returns
+/// The bytes, or the first error wrapped in `CrypError::Line`. Offsets that go backwards or
+/// skip more than [`MAX_OFFSET_GAP`] bytes are reported as `CrypError::BadOffset`, and offsets
+/// too large for `usize` as `CrypError::InvalidHex`.
+///
+/// # Example
+/// ```rust
+/// use cryp::hex::{hexdump, parse_hexdump, xxd};
+/// use cryp::CrypError;
+///
+/// let data: Vec<u8> = (0..=255).collect();
+/// assert_eq!(parse_hexdump(&hexdump(&data)).unwrap(), data);
+/// assert_eq!(parse_hexdump(&xxd(&data)).unwrap(), data);
+///
+/// let squeezed = "\
+/// 00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|
+/// *
+/// 00000030  41 42                                             |AB|
+/// 00000032
+/// ";
+/// let bytes = parse_hexdump(squeezed).unwrap();
+/// assert_eq!(bytes.len(), 0x32);
+/// assert_eq!(&bytes[0x30..], b"AB");
+///
+/// let overflow = parse_hexdump("fffffffffffffffffffff  41\n").unwrap_err();
+/// assert!(matches!(overflow, CrypError::Line { line: 1, source } if matches!(*source, CrypError::InvalidHex { .. })));
+/// let gap = parse_hexdump("00000000  41\n*\n7fffffffffffffff  41\n").unwrap_err();
+/// assert!(matches!(gap, CrypError::Line { line: 3, source } if matches!(*source, CrypError::BadOffset { .. })));
+/// ```
+pub fn parse_hexdump(text: &str) -> Result<Vec<u8>, CrypError> {
+    let mut bytes = Vec::new();
+    let mut previous: Vec<u8> = Vec::new();
+    let mut squeezed = false;
+
+    for (i, line) in text.lines().enumerate() {
+        let at_line = |source: CrypError| CrypError::Line { line: i + 1, source: Box::new(source) };
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "*" {
+            squeezed = true;
+            continue;
+        }
+
+        let (offset, data) = parse_dump_line(line).map_err(at_line)?;
+        if offset < bytes.len() || offset - bytes.len() > MAX_OFFSET_GAP {
+            return Err(at_line(CrypError::BadOffset { expected: bytes.len(), got: offset }));
+        }
+        if squeezed && !previous.is_empty() {
+            while bytes.len() < offset {
+                let take = previous.len().min(offset - bytes.len());
+                bytes.extend_from_slice(&previous[..take]);
+            }
+        }
+        bytes.resize(offset, 0);
+        bytes.extend_from_slice(&data);
+
+        squeezed = false;
+        if !data.is_empty() {
+            previous = data;
+        }
+    }
+    Ok(bytes)
+}
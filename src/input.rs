@@ -0,0 +1,145 @@
+//! Loaders for the Cryptopals input files.
+//!
+//! Files are looked up by name (e.g. `"6.txt"`) in this order:
+//! 1. the directory named by the `CRYP_INPUT_DIR` environment variable,
+//! 2. the `input/` folder at the crate root,
+//! 3. a copy of `input/` embedded in the binary at compile time.
+//!
+//! so the loaders work no matter which directory the program is started from.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::ngram::{NgramAlphabet, NgramModel};
+use crate::{base64_decode, hexstr_to_bytes, xor_repeating, CrypError};
+
+/// Environment variable that overrides the input directory.
+pub const INPUT_DIR_VAR: &str = "CRYP_INPUT_DIR";
+
+const EMBEDDED: &[(&str, &str)] = &[
+    ("4.txt", include_str!("../input/4.txt")),
+    ("6.txt", include_str!("../input/6.txt")),
+    ("7.txt", include_str!("../input/7.txt")),
+    ("8.txt", include_str!("../input/8.txt")),
+    ("10.txt", include_str!("../input/10.txt")),
+];
+
+/// Returns the on-disk locations checked for `name`, in lookup order.
+pub fn candidate_paths(name: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(dir) = env::var_os(INPUT_DIR_VAR) {
+        paths.push(PathBuf::from(dir).join(name));
+    }
+    paths.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("input").join(name));
+    paths
+}
+
+/// Reads an input file as text, falling back to the embedded copy.
+pub fn read_input(name: &str) -> Result<String, CrypError> {
+    for path in candidate_paths(name) {
+        match fs::read_to_string(&path) {
+            Ok(text) => return Ok(text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(source) => return Err(CrypError::Io { path, source }),
+        }
+    }
+
+    EMBEDDED.iter()
+        .find(|(embedded, _)| *embedded == name)
+        .map(|(_, text)| text.to_string())
+        .ok_or_else(|| CrypError::InputNotFound(name.to_string()))
+}
+
+/// Parses text with one hex string per line. Blank lines are skipped.
+///
+/// # Example
+/// ```rust
+/// use cryp::input::parse_hex_lines;
+///
+/// let lines = parse_hex_lines("4142\n\n434445\n").unwrap();
+/// assert_eq!(lines, vec![b"AB".to_vec(), b"CDE".to_vec()]);
+/// assert!(parse_hex_lines("41\nzz\n").is_err());
+/// ```
+pub fn parse_hex_lines(text: &str) -> Result<Vec<Vec<u8>>, CrypError> {
+    numbered_lines(text)
+        .map(|(line, hex)| {
+            hexstr_to_bytes(hex).map_err(|e| CrypError::Line { line, source: Box::new(e) })
+        })
+        .collect()
+}
+
+/// Parses a single base64 blob wrapped over several lines.
+///
+/// # Example
+/// ```rust
+/// use cryp::input::parse_base64_blob;
+///
+/// assert_eq!(parse_base64_blob("SGVs\nbG8=\n").unwrap(), b"Hello");
+/// ```
+pub fn parse_base64_blob(text: &str) -> Result<Vec<u8>, CrypError> {
+    let joined: String = text.split_whitespace().collect();
+    base64_decode(&joined)
+}
+
+/// Parses text with one independent base64 string per line. Blank lines are skipped.
+///
+/// # Example
+/// ```rust
+/// use cryp::input::parse_base64_lines;
+///
+/// let lines = parse_base64_lines("SGVsbG8=\nd29ybGQ=\n").unwrap();
+/// assert_eq!(lines, vec![b"Hello".to_vec(), b"world".to_vec()]);
+/// ```
+pub fn parse_base64_lines(text: &str) -> Result<Vec<Vec<u8>>, CrypError> {
+    numbered_lines(text)
+        .map(|(line, b64)| {
+            base64_decode(b64).map_err(|e| CrypError::Line { line, source: Box::new(e) })
+        })
+        .collect()
+}
+
+/// Loads an input file with one hex string per line (e.g. `4.txt`, `8.txt`).
+pub fn load_hex_lines(name: &str) -> Result<Vec<Vec<u8>>, CrypError> {
+    parse_hex_lines(&read_input(name)?)
+}
+
+/// Loads an input file holding one line-wrapped base64 blob (e.g. `6.txt`, `7.txt`, `10.txt`).
+///
+/// # Example
+/// ```rust
+/// use cryp::input::load_base64_blob;
+///
+/// let ciphertext = load_base64_blob("7.txt").unwrap();
+/// assert_eq!(ciphertext.len() % 16, 0);
+/// ```
+pub fn load_base64_blob(name: &str) -> Result<Vec<u8>, CrypError> {
+    parse_base64_blob(&read_input(name)?)
+}
+
+/// Loads an input file with one base64 string per line.
+pub fn load_base64_lines(name: &str) -> Result<Vec<Vec<u8>>, CrypError> {
+    parse_base64_lines(&read_input(name)?)
+}
+
+/// The plaintext of `6.txt` (challenge 6): a few thousand characters of song lyrics, handy
+/// as training text.
+pub fn lyrics() -> Result<Vec<u8>, CrypError> {
+    xor_repeating(&load_base64_blob("6.txt")?, b"Terminator X: Bring the noise")
+}
+
+/// An n-gram model trained on [`lyrics`].
+pub fn lyrics_model(order: usize, alphabet: NgramAlphabet) -> Result<NgramModel, CrypError> {
+    let mut model = NgramModel::new(order, alphabet)?;
+    model.train(&lyrics()?);
+    Ok(model)
+}
+
+/// Non-blank, trimmed lines paired with their 1-based line numbers.
+fn numbered_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
@@ -0,0 +1,437 @@
+//! RSA and EC keys and PKCS#1 `DigestInfo`, loaded from and saved to DER and PEM.
+//!
+//! Numbers are unsigned big-endian byte strings without leading zeros, ready for a bignum
+//! library. Supported formats:
+//!
+//! | type            | DER structure                   | PEM label          |
+//! |-----------------|---------------------------------|--------------------|
+//! | `RsaPublicKey`  | PKCS#1 `RSAPublicKey`           | `RSA PUBLIC KEY`   |
+//! |                 | X.509 `SubjectPublicKeyInfo`    | `PUBLIC KEY`       |
+//! | `RsaPrivateKey` | PKCS#1 `RSAPrivateKey`          | `RSA PRIVATE KEY`  |
+//! |                 | PKCS#8 `PrivateKeyInfo`         | `PRIVATE KEY`      |
+//! | `EcPublicKey`   | X.509 `SubjectPublicKeyInfo`    | `PUBLIC KEY`       |
+//! | `EcPrivateKey`  | SEC 1 `ECPrivateKey`            | `EC PRIVATE KEY`   |
+//! |                 | PKCS#8 `PrivateKeyInfo`         | `PRIVATE KEY`      |
+//!
+//! Encrypted PEM keys are not supported.
+//!
+//! # Example
+//! ```rust
+//! use cryp::keys::{RsaPrivateKey, RsaPublicKey};
+//! use cryp::pem;
+//! use openssl::pkey::PKey;
+//! use openssl::rsa::Rsa;
+//!
+//! let rsa = Rsa::generate(1024).unwrap();
+//! let private_pem = String::from_utf8(rsa.private_key_to_pem().unwrap()).unwrap();
+//! let key = RsaPrivateKey::from_pem(&private_pem).unwrap();
+//! assert_eq!(key.n, rsa.n().to_vec());
+//! assert_eq!(key.d, rsa.d().to_vec());
+//! assert_eq!(key.to_pkcs1_der(), rsa.private_key_to_der().unwrap());
+//!
+//! let pkcs8 = PKey::from_rsa(rsa.clone()).unwrap().private_key_to_pem_pkcs8().unwrap();
+//! assert_eq!(RsaPrivateKey::from_pem(std::str::from_utf8(&pkcs8).unwrap()).unwrap(), key);
+//! assert_eq!(pem::encode("PRIVATE KEY", &key.to_pkcs8_der()).as_bytes(), pkcs8);
+//!
+//! let public_pem = String::from_utf8(rsa.public_key_to_pem().unwrap()).unwrap();
+//! let public = RsaPublicKey::from_pem(&public_pem).unwrap();
+//! assert_eq!(public, key.public_key());
+//! assert_eq!(public.e, [0x01, 0x00, 0x01]);
+//! assert_eq!(public.to_pem(), public_pem);
+//! assert_eq!(public.to_pkcs1_der(), rsa.public_key_to_der_pkcs1().unwrap());
+//! ```
+
+use crate::der::{self, oid, Der, Oid};
+use crate::{pem, CrypError};
+
+fn unexpected(expected: &'static str) -> CrypError {
+    CrypError::UnexpectedDer { expected }
+}
+
+/// Returns the items of a SEQUENCE with at least `min_len` items.
+fn sequence<'a>(value: &'a Der, min_len: usize, expected: &'static str) -> Result<&'a [Der], CrypError> {
+    value.as_sequence()
+        .filter(|items| items.len() >= min_len)
+        .ok_or(unexpected(expected))
+}
+
+fn uint(value: &Der, expected: &'static str) -> Result<Vec<u8>, CrypError> {
+    value.as_uint().map(<[u8]>::to_vec).ok_or(unexpected(expected))
+}
+
+/// An `AlgorithmIdentifier`: `SEQUENCE { OID, parameters }`.
+fn algorithm(algorithm: &[u64], parameters: Der) -> Der {
+    Der::Sequence(vec![Der::Oid(Oid::new(algorithm).expect("known OID")), parameters])
+}
+
+/// Splits an `AlgorithmIdentifier` into its OID and optional parameters.
+fn parse_algorithm<'a>(value: &'a Der, expected: &'static str) -> Result<(&'a Oid, Option<&'a Der>), CrypError> {
+    let items = sequence(value, 1, expected)?;
+    let oid = items[0].as_oid().ok_or(unexpected(expected))?;
+    Ok((oid, items.get(1)))
+}
+
+/// `SubjectPublicKeyInfo`: `SEQUENCE { AlgorithmIdentifier, BIT STRING }`.
+fn spki(algorithm: Der, key: Vec<u8>) -> Vec<u8> {
+    Der::Sequence(vec![algorithm, Der::BitString { unused_bits: 0, data: key }]).to_der()
+}
+
+fn parse_spki(data: &[u8], expected: &'static str) -> Result<(Oid, Option<Der>, Vec<u8>), CrypError> {
+    let value = der::parse(data)?;
+    let items = sequence(&value, 2, expected)?;
+    let (oid, parameters) = parse_algorithm(&items[0], expected)?;
+    let key = items[1].as_bit_string().ok_or(unexpected(expected))?;
+    Ok((oid.clone(), parameters.cloned(), key.to_vec()))
+}
+
+/// PKCS#8 `PrivateKeyInfo`: `SEQUENCE { version, AlgorithmIdentifier, OCTET STRING, ... }`.
+fn pkcs8(algorithm: Der, key: Vec<u8>) -> Vec<u8> {
+    Der::Sequence(vec![Der::small_uint(0), algorithm, Der::OctetString(key)]).to_der()
+}
+
+fn parse_pkcs8(data: &[u8], expected: &'static str) -> Result<(Oid, Option<Der>, Vec<u8>), CrypError> {
+    let value = der::parse(data)?;
+    let items = sequence(&value, 3, expected)?;
+    if !matches!(items[0].as_small_uint(), Some(0 | 1)) {
+        return Err(unexpected(expected));
+    }
+    let (oid, parameters) = parse_algorithm(&items[1], expected)?;
+    let key = items[2].as_octet_string().ok_or(unexpected(expected))?;
+    Ok((oid.clone(), parameters.cloned(), key.to_vec()))
+}
+
+const RSA_PUBLIC: &str = "an RSA public key";
+const RSA_PRIVATE: &str = "an RSA private key";
+const EC_PUBLIC: &str = "an EC public key";
+const EC_PRIVATE: &str = "an EC private key";
+const DIGEST_INFO: &str = "a DigestInfo";
+
+/// An RSA public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaPublicKey {
+    pub n: Vec<u8>,
+    pub e: Vec<u8>,
+}
+
+impl RsaPublicKey {
+    /// Parses a PKCS#1 `RSAPublicKey`: `SEQUENCE { n, e }`.
+    pub fn from_pkcs1_der(data: &[u8]) -> Result<RsaPublicKey, CrypError> {
+        let value = der::parse(data)?;
+        let items = sequence(&value, 2, RSA_PUBLIC)?;
+        Ok(RsaPublicKey { n: uint(&items[0], RSA_PUBLIC)?, e: uint(&items[1], RSA_PUBLIC)? })
+    }
+
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        Der::Sequence(vec![Der::uint(&self.n), Der::uint(&self.e)]).to_der()
+    }
+
+    /// Parses a `SubjectPublicKeyInfo` holding an RSA key.
+    pub fn from_spki_der(data: &[u8]) -> Result<RsaPublicKey, CrypError> {
+        let (algorithm, _, key) = parse_spki(data, RSA_PUBLIC)?;
+        if algorithm.arcs() != oid::RSA_ENCRYPTION {
+            return Err(unexpected(RSA_PUBLIC));
+        }
+        RsaPublicKey::from_pkcs1_der(&key)
+    }
+
+    pub fn to_spki_der(&self) -> Vec<u8> {
+        spki(algorithm(oid::RSA_ENCRYPTION, Der::Null), self.to_pkcs1_der())
+    }
+
+    /// Parses either DER format.
+    pub fn from_der(data: &[u8]) -> Result<RsaPublicKey, CrypError> {
+        RsaPublicKey::from_spki_der(data).or_else(|_| RsaPublicKey::from_pkcs1_der(data))
+    }
+
+    /// Loads the first `PUBLIC KEY` or `RSA PUBLIC KEY` block.
+    pub fn from_pem(text: &str) -> Result<RsaPublicKey, CrypError> {
+        let block = pem::find(text, &["PUBLIC KEY", "RSA PUBLIC KEY"], RSA_PUBLIC)?;
+        RsaPublicKey::from_der(&block.data)
+    }
+
+    /// Saves the key as a `PUBLIC KEY` block, the format written by `openssl rsa -pubout`.
+    pub fn to_pem(&self) -> String {
+        pem::encode("PUBLIC KEY", &self.to_spki_der())
+    }
+}
+
+/// An RSA private key with its CRT parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaPrivateKey {
+    pub n: Vec<u8>,
+    pub e: Vec<u8>,
+    pub d: Vec<u8>,
+    pub p: Vec<u8>,
+    pub q: Vec<u8>,
+    /// `d mod (p - 1)`
+    pub dp: Vec<u8>,
+    /// `d mod (q - 1)`
+    pub dq: Vec<u8>,
+    /// `q^-1 mod p`
+    pub qinv: Vec<u8>,
+}
+
+impl RsaPrivateKey {
+    /// Parses a two-prime PKCS#1 `RSAPrivateKey`.
+    pub fn from_pkcs1_der(data: &[u8]) -> Result<RsaPrivateKey, CrypError> {
+        let value = der::parse(data)?;
+        let items = sequence(&value, 9, RSA_PRIVATE)?;
+        if items[0].as_small_uint() != Some(0) {
+            return Err(unexpected(RSA_PRIVATE));
+        }
+        let field = |i: usize| uint(&items[i], RSA_PRIVATE);
+        Ok(RsaPrivateKey {
+            n: field(1)?,
+            e: field(2)?,
+            d: field(3)?,
+            p: field(4)?,
+            q: field(5)?,
+            dp: field(6)?,
+            dq: field(7)?,
+            qinv: field(8)?,
+        })
+    }
+
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        let fields = [&self.n, &self.e, &self.d, &self.p, &self.q, &self.dp, &self.dq, &self.qinv];
+        let items = std::iter::once(Der::small_uint(0))
+            .chain(fields.iter().map(|field| Der::uint(field)))
+            .collect();
+        Der::Sequence(items).to_der()
+    }
+
+    /// Parses a PKCS#8 `PrivateKeyInfo` holding an RSA key.
+    pub fn from_pkcs8_der(data: &[u8]) -> Result<RsaPrivateKey, CrypError> {
+        let (algorithm, _, key) = parse_pkcs8(data, RSA_PRIVATE)?;
+        if algorithm.arcs() != oid::RSA_ENCRYPTION {
+            return Err(unexpected(RSA_PRIVATE));
+        }
+        RsaPrivateKey::from_pkcs1_der(&key)
+    }
+
+    pub fn to_pkcs8_der(&self) -> Vec<u8> {
+        pkcs8(algorithm(oid::RSA_ENCRYPTION, Der::Null), self.to_pkcs1_der())
+    }
+
+    /// Parses either DER format.
+    pub fn from_der(data: &[u8]) -> Result<RsaPrivateKey, CrypError> {
+        RsaPrivateKey::from_pkcs8_der(data).or_else(|_| RsaPrivateKey::from_pkcs1_der(data))
+    }
+
+    /// Loads the first `RSA PRIVATE KEY` or `PRIVATE KEY` block.
+    pub fn from_pem(text: &str) -> Result<RsaPrivateKey, CrypError> {
+        let block = pem::find(text, &["RSA PRIVATE KEY", "PRIVATE KEY"], RSA_PRIVATE)?;
+        RsaPrivateKey::from_der(&block.data)
+    }
+
+    /// Saves the key as an `RSA PRIVATE KEY` (PKCS#1) block.
+    pub fn to_pem(&self) -> String {
+        pem::encode("RSA PRIVATE KEY", &self.to_pkcs1_der())
+    }
+
+    pub fn public_key(&self) -> RsaPublicKey {
+        RsaPublicKey { n: self.n.clone(), e: self.e.clone() }
+    }
+}
+
+/// An EC public key on a named curve.
+///
+/// # Example
+/// ```rust
+/// use cryp::der::oid;
+/// use cryp::keys::{EcPrivateKey, EcPublicKey};
+/// use openssl::bn::BigNumContext;
+/// use openssl::ec::{EcGroup, EcKey, PointConversionForm};
+/// use openssl::nid::Nid;
+///
+/// let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+/// let ec = EcKey::generate(&group).unwrap();
+/// let mut ctx = BigNumContext::new().unwrap();
+/// let point = ec.public_key().to_bytes(&group, PointConversionForm::UNCOMPRESSED, &mut ctx).unwrap();
+///
+/// let public_pem = String::from_utf8(ec.public_key_to_pem().unwrap()).unwrap();
+/// let public = EcPublicKey::from_pem(&public_pem).unwrap();
+/// assert_eq!(public.curve.arcs(), oid::SECP256R1);
+/// assert_eq!(public.point, point);
+/// assert_eq!(public.to_pem(), public_pem);
+///
+/// let private_pem = String::from_utf8(ec.private_key_to_pem().unwrap()).unwrap();
+/// let private = EcPrivateKey::from_pem(&private_pem).unwrap();
+/// assert_eq!(private.secret, ec.private_key().to_vec_padded(32).unwrap());
+/// assert_eq!(private.public_key(), Some(public));
+/// assert_eq!(private.to_pem(), private_pem);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcPublicKey {
+    pub curve: Oid,
+    /// The SEC 1 encoded point, usually uncompressed (`04 || x || y`).
+    pub point: Vec<u8>,
+}
+
+impl EcPublicKey {
+    /// Parses a `SubjectPublicKeyInfo` holding an EC key on a named curve.
+    pub fn from_spki_der(data: &[u8]) -> Result<EcPublicKey, CrypError> {
+        let (algorithm, parameters, point) = parse_spki(data, EC_PUBLIC)?;
+        if algorithm.arcs() != oid::EC_PUBLIC_KEY {
+            return Err(unexpected(EC_PUBLIC));
+        }
+        let curve = parameters.as_ref().and_then(Der::as_oid).ok_or(unexpected(EC_PUBLIC))?;
+        Ok(EcPublicKey { curve: curve.clone(), point })
+    }
+
+    pub fn to_spki_der(&self) -> Vec<u8> {
+        spki(algorithm(oid::EC_PUBLIC_KEY, Der::Oid(self.curve.clone())), self.point.clone())
+    }
+
+    /// Loads the first `PUBLIC KEY` block.
+    pub fn from_pem(text: &str) -> Result<EcPublicKey, CrypError> {
+        EcPublicKey::from_spki_der(&pem::find(text, &["PUBLIC KEY"], EC_PUBLIC)?.data)
+    }
+
+    pub fn to_pem(&self) -> String {
+        pem::encode("PUBLIC KEY", &self.to_spki_der())
+    }
+}
+
+/// An EC private key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcPrivateKey {
+    /// The named curve, absent when a PKCS#8 wrapper carries it instead.
+    pub curve: Option<Oid>,
+    /// The private scalar, left-padded to the size of the curve order.
+    pub secret: Vec<u8>,
+    /// The SEC 1 encoded public point, if stored with the key.
+    pub public_point: Option<Vec<u8>>,
+}
+
+impl EcPrivateKey {
+    /// Parses a SEC 1 `ECPrivateKey`:
+    /// `SEQUENCE { 1, OCTET STRING, [0] curve OPTIONAL, [1] BIT STRING OPTIONAL }`.
+    pub fn from_sec1_der(data: &[u8]) -> Result<EcPrivateKey, CrypError> {
+        let value = der::parse(data)?;
+        let items = sequence(&value, 2, EC_PRIVATE)?;
+        if items[0].as_small_uint() != Some(1) {
+            return Err(unexpected(EC_PRIVATE));
+        }
+        let secret = items[1].as_octet_string().ok_or(unexpected(EC_PRIVATE))?.to_vec();
+
+        let mut key = EcPrivateKey { curve: None, secret, public_point: None };
+        for item in &items[2..] {
+            match item {
+                Der::ContextSpecific { number: 0, contents } => {
+                    key.curve = contents.first().and_then(Der::as_oid).cloned();
+                }
+                Der::ContextSpecific { number: 1, contents } => {
+                    key.public_point = contents.first().and_then(Der::as_bit_string).map(<[u8]>::to_vec);
+                }
+                _ => return Err(unexpected(EC_PRIVATE)),
+            }
+        }
+        Ok(key)
+    }
+
+    pub fn to_sec1_der(&self) -> Vec<u8> {
+        let mut items = vec![Der::small_uint(1), Der::OctetString(self.secret.clone())];
+        if let Some(curve) = &self.curve {
+            items.push(Der::ContextSpecific { number: 0, contents: vec![Der::Oid(curve.clone())] });
+        }
+        if let Some(point) = &self.public_point {
+            let point = Der::BitString { unused_bits: 0, data: point.clone() };
+            items.push(Der::ContextSpecific { number: 1, contents: vec![point] });
+        }
+        Der::Sequence(items).to_der()
+    }
+
+    /// Parses a PKCS#8 `PrivateKeyInfo` holding an EC key. The curve is taken from the
+    /// algorithm parameters when the inner key does not name it.
+    pub fn from_pkcs8_der(data: &[u8]) -> Result<EcPrivateKey, CrypError> {
+        let (algorithm, parameters, key) = parse_pkcs8(data, EC_PRIVATE)?;
+        if algorithm.arcs() != oid::EC_PUBLIC_KEY {
+            return Err(unexpected(EC_PRIVATE));
+        }
+        let mut key = EcPrivateKey::from_sec1_der(&key)?;
+        if key.curve.is_none() {
+            key.curve = parameters.as_ref().and_then(Der::as_oid).cloned();
+        }
+        Ok(key)
+    }
+
+    /// Encodes as PKCS#8; the curve is required.
+    pub fn to_pkcs8_der(&self) -> Result<Vec<u8>, CrypError> {
+        let curve = self.curve.clone().ok_or(unexpected("an EC private key with a named curve"))?;
+        let inner = EcPrivateKey { curve: None, ..self.clone() };
+        Ok(pkcs8(algorithm(oid::EC_PUBLIC_KEY, Der::Oid(curve)), inner.to_sec1_der()))
+    }
+
+    /// Parses either DER format.
+    pub fn from_der(data: &[u8]) -> Result<EcPrivateKey, CrypError> {
+        EcPrivateKey::from_pkcs8_der(data).or_else(|_| EcPrivateKey::from_sec1_der(data))
+    }
+
+    /// Loads the first `EC PRIVATE KEY` or `PRIVATE KEY` block.
+    pub fn from_pem(text: &str) -> Result<EcPrivateKey, CrypError> {
+        let block = pem::find(text, &["EC PRIVATE KEY", "PRIVATE KEY"], EC_PRIVATE)?;
+        EcPrivateKey::from_der(&block.data)
+    }
+
+    /// Saves the key as an `EC PRIVATE KEY` (SEC 1) block.
+    pub fn to_pem(&self) -> String {
+        pem::encode("EC PRIVATE KEY", &self.to_sec1_der())
+    }
+
+    /// The public key, when both the curve and the point are known.
+    pub fn public_key(&self) -> Option<EcPublicKey> {
+        Some(EcPublicKey { curve: self.curve.clone()?, point: self.public_point.clone()? })
+    }
+}
+
+/// The PKCS#1 v1.5 `DigestInfo` that RSA signatures wrap around a hash:
+/// `SEQUENCE { SEQUENCE { OID, NULL }, OCTET STRING }`.
+///
+/// # Example
+/// ```rust
+/// use cryp::der::oid;
+/// use cryp::keys::DigestInfo;
+/// use cryp::CrypError;
+///
+/// let info = DigestInfo::new(oid::SHA256, vec![0; 32]).unwrap();
+/// let der = info.to_der();
+/// // The well-known SHA-256 prefix from RFC 8017, section 9.2.
+/// let prefix = [0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
+///               0x05, 0x00, 0x04, 0x20];
+/// assert_eq!(der[..19], prefix);
+/// assert_eq!(DigestInfo::from_der(&der).unwrap(), info);
+///
+/// assert!(matches!(DigestInfo::new(&[3, 1], vec![0; 32]), Err(CrypError::InvalidOid)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestInfo {
+    pub algorithm: Oid,
+    pub digest: Vec<u8>,
+}
+
+impl DigestInfo {
+    /// # Errors
+    /// - `CrypError::InvalidOid` if `algorithm` is not a valid OID; the constants in `der::oid`
+    ///   always are.
+    pub fn new(algorithm: &[u64], digest: Vec<u8>) -> Result<DigestInfo, CrypError> {
+        Ok(DigestInfo { algorithm: Oid::new(algorithm)?, digest })
+    }
+
+    /// Parses a `DigestInfo`. The NULL parameters may be absent, as some signers omit them.
+    pub fn from_der(data: &[u8]) -> Result<DigestInfo, CrypError> {
+        let value = der::parse(data)?;
+        let items = sequence(&value, 2, DIGEST_INFO)?;
+        let (algorithm, parameters) = parse_algorithm(&items[0], DIGEST_INFO)?;
+        if !matches!(parameters, None | Some(Der::Null)) {
+            return Err(unexpected(DIGEST_INFO));
+        }
+        let digest = items[1].as_octet_string().ok_or(unexpected(DIGEST_INFO))?;
+        Ok(DigestInfo { algorithm: algorithm.clone(), digest: digest.to_vec() })
+    }
+
+    pub fn to_der(&self) -> Vec<u8> {
+        let algorithm = Der::Sequence(vec![Der::Oid(self.algorithm.clone()), Der::Null]);
+        Der::Sequence(vec![algorithm, Der::OctetString(self.digest.clone())]).to_der()
+    }
+}
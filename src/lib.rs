@@ -1,18 +1,121 @@
-use std::error::Error;
 use std::fmt::Write;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::ops::Range;
 use openssl::symm::{Cipher, Mode, Crypter};
 
+use crate::score::{LogLikelihood, Scorer};
+
+pub mod challenges;
+pub mod classical;
+pub mod codec;
+pub mod crib;
+pub mod der;
+pub mod detect;
+pub mod entropy;
+mod error;
+pub mod hex;
+pub mod input;
+pub mod keys;
+pub mod ngram;
+pub mod pem;
+pub mod profile;
+pub mod randomness;
+pub mod score;
+pub mod stream;
+
+pub use codec::{
+    ascii85_decode, ascii85_encode, base32_decode, base32_encode, base58_decode, base58_encode,
+    decimal_decode, decimal_encode, percent_decode, percent_encode, z85_decode, z85_encode,
+};
+pub use error::CrypError;
+
 const CHARSET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE_CHARSET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
 const PADDING: char = '=';
 
-fn validate_hexstr(hexstr: &str) -> Result<(), Box<dyn Error>> {
-    if hexstr.len() % 2 != 0 {
-        return Err("hex not valid: odd length".into());
+/// The 64 characters a Base64 variant encodes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// `A-Z a-z 0-9 + /` (RFC 4648 section 4).
+    Standard,
+    /// `A-Z a-z 0-9 - _`, safe in URLs and file names (RFC 4648 section 5).
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    fn charset(self) -> &'static [u8; 64] {
+        match self {
+            Base64Alphabet::Standard => CHARSET,
+            Base64Alphabet::UrlSafe => URL_SAFE_CHARSET,
+        }
+    }
+}
+
+/// Selects the Base64 variant used by `base64_encode_config` and `base64_decode_config`.
+///
+/// - `padding`: encoding appends `=` up to a multiple of 4 characters. Strict decoding
+///   requires padding to be present (or absent, when `false`); lenient decoding accepts both.
+/// - `strict`: decoding rejects whitespace and non-canonical encodings, i.e. unused bits in
+///   the last character that are not zero. Lenient decoding skips ASCII whitespace anywhere.
+///
+/// Both modes reject `=` anywhere but at the end and a wrong amount of padding.
+///
+/// # Example
+/// ```rust
+/// use cryp::{base64_decode_config, base64_encode_config, Base64Config};
+///
+/// let token = base64_encode_config(&[0xfb, 0xff], &Base64Config::URL_SAFE_NO_PAD);
+/// assert_eq!(token, "-_8");
+/// assert_eq!(base64_decode_config(&token, &Base64Config::URL_SAFE_NO_PAD).unwrap(), [0xfb, 0xff]);
+///
+/// let strict = Base64Config::STANDARD.strict();
+/// assert!(base64_decode_config("SGVsbG8=", &strict).is_ok());
+/// assert!(base64_decode_config("SGVsbG9=", &strict).is_err()); // non-canonical last character
+/// assert!(base64_decode_config("SGVs\nbG8=", &strict).is_err());
+/// assert!(base64_decode_config("SGVs\nbG8=", &Base64Config::STANDARD).is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64Config {
+    pub alphabet: Base64Alphabet,
+    pub padding: bool,
+    pub strict: bool,
+}
+
+impl Base64Config {
+    /// Standard alphabet with padding, lenient decoding. Used by `base64_encode`/`base64_decode`.
+    pub const STANDARD: Base64Config = Base64Config { alphabet: Base64Alphabet::Standard, padding: true, strict: false };
+    pub const STANDARD_NO_PAD: Base64Config = Base64Config { alphabet: Base64Alphabet::Standard, padding: false, strict: false };
+    pub const URL_SAFE: Base64Config = Base64Config { alphabet: Base64Alphabet::UrlSafe, padding: true, strict: false };
+    /// The variant used by JWTs and most URL tokens.
+    pub const URL_SAFE_NO_PAD: Base64Config = Base64Config { alphabet: Base64Alphabet::UrlSafe, padding: false, strict: false };
+
+    /// Returns the same variant with strict decoding.
+    pub const fn strict(self) -> Self {
+        Base64Config { strict: true, ..self }
+    }
+
+    /// Returns the same variant with lenient decoding.
+    pub const fn lenient(self) -> Self {
+        Base64Config { strict: false, ..self }
+    }
+
+    /// Returns the same variant with padding turned on or off.
+    pub const fn with_padding(self, padding: bool) -> Self {
+        Base64Config { padding, ..self }
+    }
+}
+
+impl Default for Base64Config {
+    fn default() -> Self {
+        Base64Config::STANDARD
     }
-    Ok(())
+}
+
+pub(crate) fn hex_digit(pos: usize, ch: char) -> Result<u8, CrypError> {
+    ch.to_digit(16)
+        .map(|digit| digit as u8)
+        .ok_or(CrypError::InvalidHex { pos, char: ch })
 }
 
 fn collect_six_bits(from: (u8, u8), offset: u8) -> u8 {
@@ -25,6 +128,7 @@ fn collect_six_bits(from: (u8, u8), offset: u8) -> u8 {
 /// This function takes a string slice containing a hexadecimal representation of data
 /// and converts it into its corresponding byte values. The input string must have
 /// an even number of characters and consist only of valid hexadecimal digits (`0-9`, `a-f`, `A-F`).
+/// Use `hex::parse_hex` for hex with `0x` prefixes, separators or `\x` escapes.
 ///
 /// # Arguments
 ///
@@ -34,40 +138,41 @@ fn collect_six_bits(from: (u8, u8), offset: u8) -> u8 {
 ///
 /// A `Result` containing:
 /// - `Ok(Vec<u8>)` if the conversion succeeds, with the vector representing the bytes.
-/// - `Err(Box<dyn Error>)` if the input string is invalid (e.g., not a valid hexadecimal string or of incorrect length).
+/// - `Err(CrypError)` if the input string is invalid (e.g., not a valid hexadecimal string or of incorrect length).
 ///
 /// # Errors
 ///
 /// This function returns an error in the following cases:
-/// - If the input string is not a valid hexadecimal string.
-/// - If the string length is odd (since each byte requires two hexadecimal characters).
+/// - `CrypError::InvalidHex` with the position of the first character that is not a hexadecimal digit.
+/// - `CrypError::OddHexLength` if the string length is odd (since each byte requires two hexadecimal characters).
 ///
 /// # Examples
 ///
 /// ```
+/// use cryp::hexstr_to_bytes;
+///
 /// let hexstr = "48656c6c6f"; // Hexadecimal for "Hello"
 /// let bytes = hexstr_to_bytes(hexstr).unwrap();
 /// assert_eq!(bytes, vec![72, 101, 108, 108, 111]);
 /// ```
 ///
 /// ```
+/// use cryp::{hexstr_to_bytes, CrypError};
+///
 /// let invalid_hexstr = "48656g"; // Invalid hex character 'g'
 /// let result = hexstr_to_bytes(invalid_hexstr);
-/// assert!(result.is_err());
+/// assert!(matches!(result, Err(CrypError::InvalidHex { pos: 5, char: 'g' })));
 /// ```
-pub fn hexstr_to_bytes(hexstr: &str) -> Result<Vec<u8>, Box<dyn Error>> {
-    if let Err(e) = validate_hexstr(&hexstr) {
-        return Err(e);
-    }
-
-    let mut bytes: Vec<u8> = Vec::new();    
+pub fn hexstr_to_bytes(hexstr: &str) -> Result<Vec<u8>, CrypError> {
+    let digits = hexstr.char_indices()
+        .map(|(pos, ch)| hex_digit(pos, ch))
+        .collect::<Result<Vec<u8>, CrypError>>()?;
 
-    for i in (0..hexstr.len()).step_by(2) {
-        let byte = u8::from_str_radix(&hexstr[i..][..2], 16).unwrap();
-        bytes.push(byte);
+    if !digits.len().is_multiple_of(2) {
+        return Err(CrypError::OddHexLength { len: digits.len() });
     }
 
-    Ok(bytes)
+    Ok(digits.chunks(2).map(|pair| (pair[0] << 4) | pair[1]).collect())
 }
 
 /// Encodes the given data as a Base64-encoded string.
@@ -86,11 +191,19 @@ pub fn hexstr_to_bytes(hexstr: &str) -> Result<Vec<u8>, Box<dyn Error>> {
 /// # Examples
 /// 
 /// ```
+/// use cryp::base64_encode;
+///
 /// let data = vec![72, 101, 108, 108, 111]; // Represents "Hello" in ASCII
-/// let encoded = base64_encode(data);
+/// let encoded = base64_encode(&data);
 /// assert_eq!(encoded, "SGVsbG8=");
 /// ```
 pub fn base64_encode(data: &[u8]) -> String {
+    base64_encode_config(data, &Base64Config::STANDARD)
+}
+
+/// Encodes `data` as Base64 using the alphabet and padding of `config`.
+pub fn base64_encode_config(data: &[u8], config: &Base64Config) -> String {
+    let charset = config.alphabet.charset();
     let mut bits_encoded = 0usize;
     let mut encoded_string = String::new();
     let padding_needed = ((6 - (data.len() * 8) % 6) / 2) % 3;
@@ -107,48 +220,107 @@ pub fn base64_encode(data: &[u8]) -> String {
         };
         let bytes_to_encode = (lower_byte_to_encode, upper_byte_to_encode);
         let offset: u8 = (bits_encoded % 8) as u8;
-        encoded_string.push(CHARSET[collect_six_bits(bytes_to_encode, offset) as usize] as char);
+        encoded_string.push(charset[collect_six_bits(bytes_to_encode, offset) as usize] as char);
         bits_encoded += 6;
     }
-    for _ in 0..padding_needed {
-        encoded_string.push(PADDING);
+    if config.padding {
+        for _ in 0..padding_needed {
+            encoded_string.push(PADDING);
+        }
     }
     encoded_string
 }
 
-pub fn base64_decode(data: &str) -> Result<Vec<u8>, (&str, u8)> {
-    let mut collected_bits = 0;
+/// Decodes a Base64-encoded string into bytes.
+///
+/// This uses `Base64Config::STANDARD`: the standard alphabet, with whitespace (such as line
+/// breaks in wrapped input) skipped and the trailing `=` padding optional.
+///
+/// # Arguments
+///
+/// * `data` - A string slice (`&str`) containing standard Base64.
+///
+/// # Errors
+///
+/// - `CrypError::InvalidBase64` with the position of the first byte outside the Base64 alphabet.
+/// - `CrypError::InvalidBase64Padding` if `=` appears before the end or the input does not end
+///   on a byte boundary.
+///
+/// # Examples
+///
+/// ```
+/// use cryp::{base64_decode, CrypError};
+///
+/// assert_eq!(base64_decode("SGVsbG8=").unwrap(), b"Hello");
+/// assert_eq!(base64_decode("SGVs\nbG8").unwrap(), b"Hello");
+/// assert!(matches!(base64_decode("SGV*bG8="), Err(CrypError::InvalidBase64 { pos: 3, byte: b'*' })));
+/// assert!(matches!(base64_decode("SGk=SGk="), Err(CrypError::InvalidBase64Padding { pos: 3 })));
+/// ```
+pub fn base64_decode(data: &str) -> Result<Vec<u8>, CrypError> {
+    base64_decode_config(data, &Base64Config::STANDARD)
+}
+
+/// Decodes Base64 according to `config`; see `Base64Config` for what each setting accepts.
+///
+/// # Errors
+///
+/// - `CrypError::InvalidBase64` for a byte outside the alphabet (including whitespace when strict).
+/// - `CrypError::InvalidBase64Padding` for misplaced, wrong, missing (strict, padded) or
+///   unexpected (strict, unpadded) padding, or a dangling final character.
+/// - `CrypError::NonCanonicalBase64` when strict and the unused bits of the last character are set.
+pub fn base64_decode_config(data: &str, config: &Base64Config) -> Result<Vec<u8>, CrypError> {
+    let charset = config.alphabet.charset();
+    let mut outputbytes = Vec::<u8>::with_capacity(data.len() / 4 * 3 + 2);
     let mut byte_buffer = 0u16;
-    let mut databytes = data.bytes();
-    let mut outputbytes = Vec::<u8>::new();
-
-    'decodeloop: loop {
-        while collected_bits < 8 {
-            if let Some(nextbyte) = databytes.next() {
-                // Finds the first occurrence of the latest byte
-                if let Some(idx) = CHARSET.iter().position(|&x| x == nextbyte) {
-                    byte_buffer |= ((idx & 0b00111111) as u16) << (10 - collected_bits);
-                    collected_bits += 6;
-                } else if nextbyte == (PADDING as u8) {
-                    collected_bits -= 2; // Padding only comes at the end so this works
-                } else {
-                    return Err((
-                        "Failed to decode base64: Expected byte from charset, found invalid byte.",
-                        nextbyte,
-                    ));
-                }
-            } else {
-                break 'decodeloop;
-            }
+    let mut collected_bits = 0;
+    let mut symbols = 0usize;
+    let mut padding = 0usize;
+    let mut first_padding_pos = None;
+    let mut last_symbol_pos = 0;
+
+    for (pos, nextbyte) in data.bytes().enumerate() {
+        if !config.strict && nextbyte.is_ascii_whitespace() {
+            continue;
+        }
+        if nextbyte == PADDING as u8 {
+            first_padding_pos.get_or_insert(pos);
+            padding += 1;
+            continue;
+        }
+        if let Some(pos) = first_padding_pos {
+            // Padding only comes at the end
+            return Err(CrypError::InvalidBase64Padding { pos });
+        }
+
+        let idx = charset.iter()
+            .position(|&x| x == nextbyte)
+            .ok_or(CrypError::InvalidBase64 { pos, byte: nextbyte })?;
+        byte_buffer = (byte_buffer << 6) | idx as u16;
+        collected_bits += 6;
+        symbols += 1;
+        last_symbol_pos = pos;
+
+        if collected_bits >= 8 {
+            collected_bits -= 8;
+            outputbytes.push((byte_buffer >> collected_bits) as u8);
+            byte_buffer &= (1 << collected_bits) - 1;
         }
-        outputbytes.push(((0b1111111100000000 & byte_buffer) >> 8) as u8);
-        byte_buffer &= 0b0000000011111111;
-        byte_buffer <<= 8;
-        collected_bits -= 8;
     }
 
-    if collected_bits != 0 {
-        return Err(("Failed to decode base64: Invalid padding.", collected_bits));
+    // A single character in the last quantum carries less than a byte.
+    if symbols % 4 == 1 {
+        return Err(CrypError::InvalidBase64Padding { pos: first_padding_pos.unwrap_or(data.len()) });
+    }
+    let padding_needed = (4 - symbols % 4) % 4;
+    if let Some(pos) = first_padding_pos {
+        if padding != padding_needed || (config.strict && !config.padding) {
+            return Err(CrypError::InvalidBase64Padding { pos });
+        }
+    } else if config.strict && config.padding && padding_needed > 0 {
+        return Err(CrypError::InvalidBase64Padding { pos: data.len() });
+    }
+    if config.strict && byte_buffer != 0 {
+        return Err(CrypError::NonCanonicalBase64 { pos: last_symbol_pos });
     }
 
     Ok(outputbytes)
@@ -173,13 +345,17 @@ pub fn base64_decode(data: &str) -> Result<Vec<u8>, (&str, u8)> {
 /// # Examples
 ///
 /// ```
+/// use cryp::bytes_to_hexstr;
+///
 /// let bytes = &[72, 101, 108, 108, 111]; // Represents "Hello" in bytes
 /// let hex_str = bytes_to_hexstr(bytes);
 /// assert_eq!(hex_str, "48656c6c6f");
 /// ```
 ///
 /// ```
-/// let empty_bytes = &[]; // Empty input
+/// use cryp::bytes_to_hexstr;
+///
+/// let empty_bytes: &[u8] = &[]; // Empty input
 /// let hex_str = bytes_to_hexstr(empty_bytes);
 /// assert_eq!(hex_str, "");
 /// ```
@@ -205,24 +381,25 @@ pub fn bytes_to_hexstr(data: &[u8]) -> String {
 ///
 /// # Returns
 ///
-/// A `Result<Vec<u8>, Box<dyn Error>>`. On success, it returns a `Vec<u8>` containing the XORed bytes.
-/// If the input slices have different lengths, it returns an error with a message describing the issue.
+/// A `Result<Vec<u8>, CrypError>`. On success, it returns a `Vec<u8>` containing the XORed bytes.
 ///
 /// # Errors
 ///
-/// This function returns an error if the input slices (`data` and `key`) have different lengths.
+/// This function returns `CrypError::LengthMismatch` if the input slices (`data` and `key`) have different lengths.
 ///
 /// # Examples
 ///
 /// ```
+/// use cryp::xor_fixed;
+///
 /// let data = vec![0x1c, 0x01, 0x11, 0x00];
 /// let key = vec![0x1f, 0x01, 0x01, 0x00];
 /// let result = xor_fixed(&data, &key);
-/// assert_eq!(result.unwrap(), vec![0x05, 0x00, 0x10, 0x00]);
+/// assert_eq!(result.unwrap(), vec![0x03, 0x00, 0x10, 0x00]);
 /// ```
-pub fn xor_fixed(data: &[u8], key: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+pub fn xor_fixed(data: &[u8], key: &[u8]) -> Result<Vec<u8>, CrypError> {
     if data.len() != key.len() {
-        return Err("inputs (data and key) should have equal length".into());        
+        return Err(CrypError::LengthMismatch { left: data.len(), right: key.len() });
     }
 
     let mut xored: Vec<u8> = vec![];
@@ -251,6 +428,8 @@ pub fn xor_fixed(data: &[u8], key: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
 /// # Examples
 ///
 /// ```
+/// use cryp::xor_one_byte;
+///
 /// let data = b"Hello";
 /// let key = 42; // Example key
 /// let result = xor_one_byte(data, key);
@@ -282,13 +461,29 @@ pub fn xor_one_byte(data: &[u8], key: u8) -> Vec<u8> {
 /// is the XOR of the corresponding byte in the data and the key. The result will have the same
 /// length as the input data.
 ///
-pub fn xor_repeating(data: &[u8], key: &[u8]) -> Vec<u8> {
+/// # Errors
+///
+/// Returns `CrypError::EmptyKey` if `key` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use cryp::xor_repeating;
+///
+/// let xored = xor_repeating(b"abc", &[0x01, 0x02]).unwrap();
+/// assert_eq!(xored, b"``b");
+/// ```
+pub fn xor_repeating(data: &[u8], key: &[u8]) -> Result<Vec<u8>, CrypError> {
+    if key.is_empty() {
+        return Err(CrypError::EmptyKey);
+    }
+
     let mut xored: Vec<u8> = vec![];
     for (i, &byte) in data.iter().enumerate() {
         xored.push(byte ^ key[i % key.len()]);
     }
 
-    xored
+    Ok(xored)
 }
 
 /// Scores the text based on the proportion of alphanumeric and space characters.
@@ -308,6 +503,8 @@ pub fn xor_repeating(data: &[u8], key: &[u8]) -> Vec<u8> {
 /// # Examples
 ///
 /// ```
+/// use cryp::score_text;
+///
 /// let data = b"Hello, world!";
 /// let score = score_text(data);
 /// assert!(score > 50.0);
@@ -366,7 +563,7 @@ fn analyze_string(s: &str) -> (f64, f64, f64) {
 ///   is not desired in the future.
 ///
 pub fn is_valid_text(s: &[u8]) -> bool {
-    let s = String::from_utf8_lossy(&s);
+    let s = String::from_utf8_lossy(s);
     let (letter_ratio, space_ratio, symbol_ratio) = analyze_string(&s);
 
     letter_ratio >= 0.7 &&
@@ -374,6 +571,21 @@ pub fn is_valid_text(s: &[u8]) -> bool {
     symbol_ratio <= 0.1
 }
 
+/// Validates whether `s` looks like text according to `scorer` (see `Scorer::is_text`).
+///
+/// # Example
+/// ```rust
+/// use cryp::is_valid_text_with;
+/// use cryp::score::{AlnumRatio, ChiSquared};
+///
+/// let sentence = b"Well, it's 9:30 now - isn't it? Yes, it is.";
+/// assert!(is_valid_text_with(sentence, &ChiSquared));
+/// assert!(!is_valid_text_with(sentence, &AlnumRatio)); // too many symbols for `is_valid_text`
+/// ```
+pub fn is_valid_text_with(s: &[u8], scorer: &dyn Scorer) -> bool {
+    scorer.is_text(s)
+}
+
 /// Computes the frequency of ASCII alphabetic characters in a byte slice.
 ///
 /// # Arguments
@@ -395,6 +607,7 @@ pub fn is_valid_text(s: &[u8]) -> bool {
 ///
 /// ```
 /// use std::collections::HashMap;
+/// use cryp::character_frequency;
 ///
 /// let data = b"Hello, World!";
 /// let result = character_frequency(data);
@@ -435,6 +648,8 @@ pub fn character_frequency(data: &[u8]) -> HashMap<char, usize> {
 ///
 /// # Example
 /// ```rust
+/// use cryp::print_type;
+///
 /// let x = 42;
 /// print_type(&x); // Outputs: i32
 /// ```
@@ -457,8 +672,10 @@ pub fn print_type<T>(_: &T) {
 ///
 /// # Example
 /// ```rust
-/// let s1 = "hello";
-/// let s2 = "h3llo";
+/// use cryp::hamming_distance_bit;
+///
+/// let s1 = b"hello";
+/// let s2 = b"h3llo";
 /// let distance = hamming_distance_bit(s1, s2);
 /// println!("Bit-level Hamming distance: {}", distance); // Outputs: 4
 /// ```
@@ -482,6 +699,8 @@ pub fn hamming_distance_bit(s1: &[u8], s2: &[u8]) -> u32 {
 ///
 /// # Example
 /// ```rust
+/// use cryp::hamming_distance_char;
+///
 /// let s1 = "hello";
 /// let s2 = "h3llo";
 /// let distance = hamming_distance_char(s1, s2);
@@ -489,15 +708,82 @@ pub fn hamming_distance_bit(s1: &[u8], s2: &[u8]) -> u32 {
 /// ```
 pub fn hamming_distance_char(s1: &str, s2: &str) -> usize {
     s1.chars().zip(s2.chars())
-        .filter(|(c1, c2)| c1 != c2).count() as usize
+        .filter(|(c1, c2)| c1 != c2).count()
+}
+
+/// A key length is reported as a multiple of a shorter candidate when its distance is within
+/// this fraction of the shorter one's: multiples of the true key length score just as well, and
+/// with fewer blocks to compare they often score marginally better.
+const MULTIPLE_TOLERANCE: f64 = 0.05;
+
+/// Pairs of blocks `xor_guess_key_len` compares per key size: enough for a stable average,
+/// few enough that megabyte inputs take milliseconds.
+pub const DEFAULT_MAX_PAIRS: usize = 1000;
+
+/// One key length considered by `xor_guess_key_len`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyLenCandidate {
+    pub key_len: usize,
+    /// Average Hamming distance between pairs of `key_len`-byte blocks, in bits per byte.
+    pub distance: f64,
+    /// The best candidate, when this length is a multiple of it.
+    pub multiple_of: Option<usize>,
+}
+
+/// The key lengths ranked by `xor_guess_key_len`, most likely first.
+///
+/// # Example
+/// ```rust
+/// use cryp::{xor_guess_key_len, xor_repeating};
+///
+/// let plaintext = b"Now that the party is jumping, with the bass kicked in and the vegas are pumpin. \
+///                   Quick to the point, to the point, no faking. Cooking MC's like a pound of bacon. \
+///                   Burning 'em, if you ain't quick and nimble. I go crazy when I hear a cymbal";
+/// let data = xor_repeating(plaintext, b"Vanilla").unwrap();
+/// let ranking = xor_guess_key_len(&data, 2, 30).unwrap();
+///
+/// assert_eq!(ranking.best(), 7);
+/// assert!(ranking.multiples().all(|len| len % 7 == 0));
+/// assert!(ranking.confidence > 0.0);
+/// for pair in ranking.pairs().windows(2).skip(1) {
+///     assert!(pair[0].1 <= pair[1].1);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyLenRanking {
+    /// Every key length with enough data for at least one pair of blocks. The first one is the
+    /// shortest length that scores as well as the lowest distance; the rest follow by distance.
+    pub candidates: Vec<KeyLenCandidate>,
+    /// How far the best candidate stands out from the closest length that is not a multiple of
+    /// it: `1 - best / runner_up` distance, between 0 (a tie) and 1.
+    pub confidence: f64,
+}
+
+impl KeyLenRanking {
+    /// The most likely key length.
+    pub fn best(&self) -> usize {
+        self.candidates[0].key_len
+    }
+
+    /// `(key_len, distance)` pairs, most likely first.
+    pub fn pairs(&self) -> Vec<(usize, f64)> {
+        self.candidates.iter().map(|c| (c.key_len, c.distance)).collect()
+    }
+
+    /// The candidates that are multiples of the best one, in ranked order.
+    pub fn multiples(&self) -> impl Iterator<Item = usize> + '_ {
+        self.candidates.iter().filter(|c| c.multiple_of.is_some()).map(|c| c.key_len)
+    }
 }
 
 /// Guesses the most likely key size used in a repeating-key XOR cipher by calculating
 /// the average normalized Hamming distance for different key sizes.
 ///
-/// The function compares chunks of ciphertext for different key sizes and returns
-/// the key size with the smallest normalized average Hamming distance, which is
-/// assumed to be the most likely key size.
+/// The ciphertext is cut into blocks of each key size and pairs of blocks are compared: at the
+/// right key size the key cancels out and the distance is that of two plaintexts, which is
+/// lower than that of two unrelated ciphertexts. Up to `DEFAULT_MAX_PAIRS` pairs are compared
+/// per key size, so the cost grows linearly with the key sizes tried rather than with the
+/// square of the input length; use `xor_guess_key_len_with_pairs` to compare more or all.
 ///
 /// # Arguments
 /// - `data`: The ciphertext encrypted with a repeating-key XOR cipher.
@@ -505,42 +791,459 @@ pub fn hamming_distance_char(s1: &str, s2: &str) -> usize {
 /// - `max_guess`: The maximum possible key size to consider.
 ///
 /// # Returns
-/// - The most likely key size (usize) based on the average normalized Hamming distance.
+/// - Every key size from `min_guess` to `max_guess` that fits twice in `data`, ranked by
+///   distance, with multiples of the best size flagged.
+///
+/// # Errors
+/// - `CrypError::InvalidRange` if `min_guess` is zero or larger than `max_guess`.
+/// - `CrypError::NotEnoughData` if `data` is shorter than two blocks of `min_guess` bytes.
 ///
 /// # Example
 /// ```rust
-/// let data = vec![/* some ciphertext bytes */];
+/// use cryp::{xor_guess_key_len, CrypError};
+///
+/// let data: Vec<u8> = vec![/* some ciphertext bytes */];
 /// let guessed_key_size = xor_guess_key_len(&data, 2, 40);
-/// println!("Guessed key size: {}", guessed_key_size);
+/// assert!(matches!(guessed_key_size, Err(CrypError::NotEnoughData { .. })));
+///
+/// let huge = usize::MAX / 2 + 1;
+/// assert!(matches!(xor_guess_key_len(b"too short", huge, huge), Err(CrypError::NotEnoughData { .. })));
+/// ```
+pub fn xor_guess_key_len(data: &[u8], min_guess: usize, max_guess: usize) -> Result<KeyLenRanking, CrypError> {
+    xor_guess_key_len_with_pairs(data, min_guess, max_guess, Some(DEFAULT_MAX_PAIRS))
+}
+
+/// Like `xor_guess_key_len`, but compares at most `max_pairs` pairs of blocks per key size
+/// (all of them when `None`, which takes time quadratic in the length of `data`). Pairs of
+/// neighbouring blocks come first, then blocks two apart, and so on, so `Some(1)` compares
+/// only the first two blocks.
+///
+/// # Errors
+/// - As `xor_guess_key_len`, and `CrypError::InvalidArgument` if `max_pairs` is `Some(0)`.
+///
+/// # Example
+/// ```rust
+/// use cryp::{xor_guess_key_len_with_pairs, xor_repeating, CrypError};
+///
+/// let plaintext = b"It was the best of times, it was the worst of times, it was the age of wisdom, \
+///                   it was the age of foolishness, it was the epoch of belief, it was the epoch \
+///                   of incredulity, it was the season of Light, it was the season of Darkness";
+/// let data = xor_repeating(plaintext, b"ICEBERG").unwrap();
+/// let ranking = xor_guess_key_len_with_pairs(&data, 2, 20, Some(200)).unwrap();
+/// assert_eq!(ranking.best(), 7);
+/// assert_eq!(ranking.candidates[1].key_len, 14);
+/// assert_eq!(ranking.multiples().collect::<Vec<_>>(), [14]);
+///
+/// // Too few pairs on such a short text, and a multiple of the key length comes out on top.
+/// let ranking = xor_guess_key_len_with_pairs(&data, 2, 20, Some(50)).unwrap();
+/// assert_eq!(ranking.best(), 14);
+///
+/// let no_pairs = xor_guess_key_len_with_pairs(&data, 2, 20, Some(0));
+/// assert!(matches!(no_pairs, Err(CrypError::InvalidArgument { name: "max pairs", value: 0 })));
 /// ```
-pub fn xor_guess_key_len(data: &[u8], min_guess: usize, max_guess: usize) -> usize {
-    let mut scores: Vec<(f64, usize)> = Vec::new();
+pub fn xor_guess_key_len_with_pairs(
+    data: &[u8],
+    min_guess: usize,
+    max_guess: usize,
+    max_pairs: Option<usize>,
+) -> Result<KeyLenRanking, CrypError> {
+    if min_guess == 0 || min_guess > max_guess {
+        return Err(CrypError::InvalidRange { min: min_guess, max: max_guess });
+    }
+    if max_pairs == Some(0) {
+        return Err(CrypError::InvalidArgument { name: "max pairs", value: 0 });
+    }
+    if data.len() / 2 < min_guess {
+        return Err(CrypError::NotEnoughData { needed: min_guess.saturating_mul(2), got: data.len() });
+    }
 
-    for key_size in min_guess..=max_guess {
-        let mut total_distance = 0.0;
-        let mut count = 0;
+    let mut candidates: Vec<KeyLenCandidate> = (min_guess..=max_guess.min(data.len() / 2))
+        .map(|key_len| KeyLenCandidate {
+            key_len,
+            distance: block_distance(data, key_len, max_pairs.unwrap_or(usize::MAX)),
+            multiple_of: None,
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(Ordering::Equal));
 
-        for chunk in data.chunks(key_size * 2) {
-            if chunk.len() < key_size * 2 {
-                break;
-            }
-            let a = &chunk[..key_size];
-            let b = &chunk[key_size..key_size * 2];
-            total_distance += hamming_distance_bit(a, b) as f64 / key_size as f64;
-            count += 1;
+    // The shortest divisor of the lowest-distance length that scores about as well is the key
+    // length; the lowest-distance one is then just a multiple of it.
+    let lowest = candidates[0];
+    let best = candidates.iter()
+        .enumerate()
+        .filter(|(_, c)| {
+            lowest.key_len.is_multiple_of(c.key_len) && c.distance <= lowest.distance * (1.0 + MULTIPLE_TOLERANCE)
+        })
+        .min_by_key(|(_, c)| c.key_len)
+        .map_or(0, |(i, _)| i);
+    let best = candidates.remove(best);
+    candidates.insert(0, best);
+
+    for candidate in &mut candidates[1..] {
+        if candidate.key_len.is_multiple_of(best.key_len) {
+            candidate.multiple_of = Some(best.key_len);
         }
+    }
+
+    let confidence = match candidates[1..].iter().find(|c| c.multiple_of.is_none()) {
+        Some(runner_up) if runner_up.distance > 0.0 => (1.0 - best.distance / runner_up.distance).max(0.0),
+        Some(_) => 0.0,
+        None => 1.0,
+    };
+
+    Ok(KeyLenRanking { candidates, confidence })
+}
+
+/// Average Hamming distance in bits per byte over up to `max_pairs` pairs of `key_len`-byte
+/// blocks, taking pairs in order of increasing gap between the blocks.
+fn block_distance(data: &[u8], key_len: usize, max_pairs: usize) -> f64 {
+    let blocks: Vec<&[u8]> = data.chunks_exact(key_len).collect();
+    let pairs = (1..blocks.len())
+        .flat_map(|gap| (0..blocks.len() - gap).map(move |i| (i, i + gap)))
+        .take(max_pairs);
+
+    let (mut total, mut count) = (0u64, 0u64);
+    for (i, j) in pairs {
+        total += hamming_distance_bit(blocks[i], blocks[j]) as u64;
+        count += 1;
+    }
+    total as f64 / (count * key_len as u64) as f64
+}
 
-        if count > 0 {
-            scores.push((total_distance / count as f64, key_size));
+/// Length of the repeated sequences `kasiski_examination` looks for.
+const KASISKI_MIN_REPEAT: usize = 3;
+
+/// Kasiski examination: finds sequences of three or more bytes that repeat in `data` and counts,
+/// for every period from `min_period` to `max_period`, how many of the distances between
+/// consecutive repeats it divides. Repeats of plaintext that line up with the same part of the
+/// key are exactly a multiple of the key length apart.
+///
+/// Short periods divide many distances by chance (2 divides half of them), so compare
+/// `count * period` rather than raw counts when ranking periods.
+///
+/// # Returns
+/// - `(period, count)` for every period, in increasing order of period.
+///
+/// # Example
+/// ```rust
+/// use cryp::kasiski_examination;
+///
+/// // "GRH" repeats at offsets 0, 10 and 20, "RHZ" at 11 and 21.
+/// let counts = kasiski_examination(b"GRHKAXHTMLGRHZYIIIPEGRHZ", 2, 10);
+/// assert_eq!(counts.iter().find(|&&(period, _)| period == 10), Some(&(10, 3)));
+/// assert_eq!(counts.iter().find(|&&(period, _)| period == 3), Some(&(3, 0)));
+/// ```
+pub fn kasiski_examination(data: &[u8], min_period: usize, max_period: usize) -> Vec<(usize, usize)> {
+    let mut last_seen: HashMap<&[u8], usize> = HashMap::new();
+    let mut distances = Vec::new();
+    for (i, window) in data.windows(KASISKI_MIN_REPEAT).enumerate() {
+        if let Some(previous) = last_seen.insert(window, i) {
+            distances.push(i - previous);
         }
     }
 
-    scores.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
-    scores[0].1
+    (min_period.max(1)..=max_period)
+        .map(|period| (period, distances.iter().filter(|d| d.is_multiple_of(period)).count()))
+        .collect()
+}
+
+/// The index of coincidence of `data`: the probability that two bytes drawn from different
+/// positions are equal. English letters score about 0.066, uniformly random letters 1/26 and
+/// random bytes 1/256. Returns 0 for inputs shorter than two bytes.
+///
+/// # Example
+/// ```rust
+/// use cryp::index_of_coincidence;
+///
+/// assert_eq!(index_of_coincidence(b"AABB"), 1.0 / 3.0);
+/// assert_eq!(index_of_coincidence(b"ABCD"), 0.0);
+/// ```
+pub fn index_of_coincidence(data: &[u8]) -> f64 {
+    if data.len() < 2 {
+        return 0.0;
+    }
+    let mut counts = [0u64; 256];
+    for &b in data {
+        counts[b as usize] += 1;
+    }
+    let n = data.len() as u64;
+    counts.iter().map(|c| c * c.saturating_sub(1)).sum::<u64>() as f64 / (n * (n - 1)) as f64
+}
+
+/// The index of coincidence of each of the `period` columns of `data` (bytes `i`, `i + period`,
+/// ...). At the key period every column is a monoalphabetic substitution of the plaintext and
+/// keeps its index of coincidence; at other periods the columns mix key bytes and look random.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `period` is zero.
+///
+/// # Example
+/// ```rust
+/// use cryp::{column_index_of_coincidence, xor_repeating};
+///
+/// let plaintext = b"Now that the party is jumping, with the bass kicked in and the vegas are pumpin";
+/// let data = xor_repeating(plaintext, b"key").unwrap();
+/// let mean = |ics: Vec<f64>| ics.iter().sum::<f64>() / ics.len() as f64;
+/// assert!(mean(column_index_of_coincidence(&data, 3).unwrap()) > mean(column_index_of_coincidence(&data, 4).unwrap()));
+/// ```
+pub fn column_index_of_coincidence(data: &[u8], period: usize) -> Result<Vec<f64>, CrypError> {
+    if period == 0 {
+        return Err(CrypError::EmptyKey);
+    }
+    Ok((0..period)
+        .map(|i| {
+            let column: Vec<u8> = data.iter().skip(i).step_by(period).cloned().collect();
+            index_of_coincidence(&column)
+        })
+        .collect())
+}
+
+/// Counts the positions where `data` equals itself shifted by `shift` bytes, i.e. where
+/// `data[i] == data[i + shift]`. Shifting by a multiple of the key length of a repeating-key
+/// cipher lines up equal key bytes, so equal plaintext bytes stay equal.
+///
+/// # Example
+/// ```rust
+/// use cryp::xor_autocorrelation;
+///
+/// assert_eq!(xor_autocorrelation(b"abcabcabd", 3), 5);
+/// assert_eq!(xor_autocorrelation(b"abc", 3), 0);
+/// ```
+pub fn xor_autocorrelation(data: &[u8], shift: usize) -> usize {
+    if shift >= data.len() {
+        return 0;
+    }
+    data.iter().zip(&data[shift..]).filter(|(a, b)| a == b).count()
+}
+
+/// How much weight each estimator's vote carries in `estimate_key_len`: Hamming distance,
+/// Kasiski examination, index of coincidence and autocorrelation. Kasiski and autocorrelation
+/// are noisy on short inputs and mostly break ties.
+const KEY_LEN_VOTE_WEIGHTS: [f64; 4] = [2.0, 0.5, 2.0, 0.5];
+
+/// `estimate_key_len` prefers a divisor of the winning key length whose vote is at most this
+/// much lower.
+const KEY_LEN_VOTE_TOLERANCE: f64 = 1.0;
+
+/// Estimates the key length by letting the Hamming distance, Kasiski examination, index of
+/// coincidence and autocorrelation estimators vote.
+///
+/// Each estimator scores every key length; the scores are standardized (how many standard
+/// deviations above the mean a length is) and averaged with `KEY_LEN_VOTE_WEIGHTS`. Every
+/// estimator also favours multiples of the key length, so the shortest divisor of the winner
+/// whose vote is nearly as high takes its place. Unlike `xor_guess_key_len` this copes with
+/// Vigenère ciphertexts over letters, where Hamming distances carry little signal.
+///
+/// # Arguments
+/// - `data`: The ciphertext of a repeating-key cipher (XOR, Vigenère, ...).
+/// - `min_guess`: The minimum possible key size to consider.
+/// - `max_guess`: The maximum possible key size to consider.
+///
+/// # Returns
+/// - `(key_len, vote)` pairs, most likely first.
+///
+/// # Errors
+/// - As `xor_guess_key_len`.
+///
+/// # Example
+/// ```rust
+/// use cryp::{estimate_key_len, xor_repeating};
+///
+/// let plaintext = b"Now that the party is jumping, with the bass kicked in and the vegas are pumpin. \
+///                   Quick to the point, to the point, no faking. Cooking MC's like a pound of bacon";
+/// let data = xor_repeating(plaintext, b"Vanilla").unwrap();
+/// assert_eq!(estimate_key_len(&data, 2, 20).unwrap()[0].0, 7);
+///
+/// // Vigenère over letters, key "LEMON".
+/// let plaintext = "Call me Ishmael. Some years ago, never mind how long precisely, having little or no \
+///                  money in my purse, and nothing particular to interest me on shore, I thought I would \
+///                  sail about a little and see the watery part of the world. It is a way I have of \
+///                  driving off the spleen and regulating the circulation. Whenever I find myself growing \
+///                  grim about the mouth; whenever it is a damp, drizzly November in my soul; whenever I \
+///                  find myself involuntarily pausing before coffin warehouses, then, I account it high \
+///                  time to get to sea as soon as I can.";
+/// let key = b"LEMON";
+/// let ciphertext: Vec<u8> = plaintext.bytes()
+///     .filter(u8::is_ascii_alphabetic)
+///     .enumerate()
+///     .map(|(i, b)| b'A' + (b.to_ascii_uppercase() - b'A' + key[i % 5] - b'A') % 26)
+///     .collect();
+/// assert_eq!(estimate_key_len(&ciphertext, 2, 20).unwrap()[0].0, 5);
+/// ```
+pub fn estimate_key_len(data: &[u8], min_guess: usize, max_guess: usize) -> Result<Vec<(usize, f64)>, CrypError> {
+    let hamming: Vec<(usize, f64)> = xor_guess_key_len(data, min_guess, max_guess)?
+        .candidates.iter()
+        .map(|c| (c.key_len, -c.distance))
+        .collect();
+    let max_guess = max_guess.min(data.len() / 2);
+
+    // Period 1 divides every distance between repeats.
+    let repeats = kasiski_examination(data, 1, 1)[0].1.max(1) as f64;
+    let kasiski = kasiski_examination(data, min_guess, max_guess)
+        .into_iter()
+        .map(|(period, count)| (period, count as f64 * period as f64 / repeats))
+        .collect();
+    let coincidence = (min_guess..=max_guess)
+        .map(|period| {
+            let ics = column_index_of_coincidence(data, period)?;
+            Ok((period, ics.iter().sum::<f64>() / period as f64))
+        })
+        .collect::<Result<_, CrypError>>()?;
+    let autocorrelation = (min_guess..=max_guess)
+        .map(|shift| (shift, xor_autocorrelation(data, shift) as f64 / (data.len() - shift) as f64))
+        .collect();
+
+    let mut votes: HashMap<usize, f64> = HashMap::new();
+    let total_weight: f64 = KEY_LEN_VOTE_WEIGHTS.iter().sum();
+    for (scores, weight) in [hamming, kasiski, coincidence, autocorrelation].into_iter().zip(KEY_LEN_VOTE_WEIGHTS) {
+        for (key_len, score) in standardize(scores) {
+            *votes.entry(key_len).or_default() += score * weight / total_weight;
+        }
+    }
+
+    let mut ranked: Vec<(usize, f64)> = votes.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal).then(a.0.cmp(&b.0)));
+
+    let (top, top_vote) = ranked[0];
+    if let Some(divisor) = ranked.iter()
+        .filter(|&&(key_len, vote)| top.is_multiple_of(key_len) && vote >= top_vote - KEY_LEN_VOTE_TOLERANCE)
+        .min_by_key(|&&(key_len, _)| key_len)
+        .and_then(|&(key_len, _)| ranked.iter().position(|&(other, _)| other == key_len))
+    {
+        let divisor = ranked.remove(divisor);
+        ranked.insert(0, divisor);
+    }
+    Ok(ranked)
+}
+
+/// Replaces every score with its distance from the mean in standard deviations.
+fn standardize(scores: Vec<(usize, f64)>) -> Vec<(usize, f64)> {
+    let n = scores.len() as f64;
+    let mean = scores.iter().map(|&(_, score)| score).sum::<f64>() / n;
+    let deviation = (scores.iter().map(|&(_, score)| (score - mean).powi(2)).sum::<f64>() / n).sqrt();
+    scores.into_iter()
+        .map(|(key_len, score)| (key_len, if deviation > 0.0 { (score - mean) / deviation } else { 0.0 }))
+        .collect()
+}
+
+/// Finds the single-byte XOR key whose decryption of `data` scores best.
+///
+/// All 256 keys are tried; on ties the smallest key wins.
+///
+/// # Arguments
+/// - `data`: The ciphertext, XORed with one repeated byte.
+/// - `scorer`: How to rank the candidate plaintexts (see the `score` module).
+///
+/// # Returns
+/// - The best key and its score.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::ChiSquared;
+/// use cryp::{xor_guess_single_byte, xor_one_byte};
+///
+/// let ciphertext = xor_one_byte(b"Cooking MC's like a pound of bacon", 0x58);
+/// let (key, _) = xor_guess_single_byte(&ciphertext, &ChiSquared);
+/// assert_eq!(key, 0x58);
+/// ```
+pub fn xor_guess_single_byte(data: &[u8], scorer: &dyn Scorer) -> (u8, f64) {
+    best_key(data, 0..=255, scorer)
+}
+
+/// `detect_single_byte_xor` spreads the work over threads once the input has this many bytes.
+const PARALLEL_THRESHOLD: usize = 64 * 1024;
+
+/// A line of `detect_single_byte_xor` input with its best single-byte XOR key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    /// Index of the line in the input.
+    pub line_idx: usize,
+    pub key: u8,
+    pub score: f64,
+    pub plaintext: Vec<u8>,
+}
+
+/// Finds the lines of `lines` that are most likely single-byte XOR encrypted text.
+///
+/// Every line is decrypted with all 256 keys and its best key kept (see
+/// `xor_guess_single_byte`); the lines are then ranked by the score of their best decryption.
+/// Inputs larger than 64 KiB are split across all available CPUs.
+///
+/// # Arguments
+/// - `lines`: The candidate ciphertexts, e.g. the lines of a dump.
+/// - `scorer`: How to rank the candidate plaintexts (see the `score` module).
+/// - `top_k`: Keep only the `top_k` best lines (all of them when `None`).
+///
+/// # Returns
+/// - One detection per line, best first; ties keep the input order.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::LogLikelihood;
+/// use cryp::{detect_single_byte_xor, xor_one_byte};
+///
+/// let lines = vec![
+///     vec![0x13, 0x9f, 0x00, 0xe2, 0x47, 0x5a, 0xc8, 0x01],
+///     xor_one_byte(b"Now that the party is jumping", 0xff),
+///     vec![0x61, 0x62, 0x63, 0x00, 0x7f, 0x80, 0x81, 0x82],
+/// ];
+/// let detections = detect_single_byte_xor(&lines, &LogLikelihood, Some(2));
+/// assert_eq!(detections.len(), 2);
+/// assert_eq!((detections[0].line_idx, detections[0].key), (1, 0xff));
+/// assert_eq!(detections[0].plaintext, b"Now that the party is jumping");
+/// ```
+pub fn detect_single_byte_xor<L: AsRef<[u8]> + Sync>(
+    lines: &[L],
+    scorer: &dyn Scorer,
+    top_k: Option<usize>,
+) -> Vec<Detection> {
+    let detect = |line_idx: usize, line: &L| {
+        let line = line.as_ref();
+        let (key, score) = xor_guess_single_byte(line, scorer);
+        Detection { line_idx, key, score, plaintext: xor_one_byte(line, key) }
+    };
+
+    let total: usize = lines.iter().map(|line| line.as_ref().len()).sum();
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut detections: Vec<Detection> = if total < PARALLEL_THRESHOLD || threads == 1 {
+        lines.iter().enumerate().map(|(i, line)| detect(i, line)).collect()
+    } else {
+        let chunk_size = lines.len().div_ceil(threads);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = lines.chunks(chunk_size)
+                .enumerate()
+                .map(|(chunk, lines)| {
+                    scope.spawn(move || {
+                        lines.iter()
+                            .enumerate()
+                            .map(|(i, line)| detect(chunk * chunk_size + i, line))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles.into_iter()
+                .flat_map(|handle| handle.join().expect("detection thread panicked"))
+                .collect()
+        })
+    };
+
+    detections.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    if let Some(k) = top_k {
+        detections.truncate(k);
+    }
+    detections
+}
+
+/// Returns the candidate key that scores best on `data`, keeping the first one on ties.
+fn best_key(data: &[u8], candidates: impl IntoIterator<Item = u8>, scorer: &dyn Scorer) -> (u8, f64) {
+    candidates.into_iter()
+        .map(|key| (key, scorer.score(&xor_one_byte(data, key))))
+        .fold((0, f64::NEG_INFINITY), |best, candidate| if candidate.1 > best.1 { candidate } else { best })
 }
 
 /// Guesses the key used for a repeating-key XOR cipher, given a guessed key size.
-/// It tries each possible (printable) byte (from 32 to 126) for each key position and scores the results based on how likely they are to be plaintext.
+/// It tries each possible (printable) byte (from 32 to 126) for each key position and scores the results
+/// with the English unigram scorer (`score::LogLikelihood`).
 ///
 /// # Arguments
 /// - `data`: The ciphertext encrypted with a repeating-key XOR cipher.
@@ -549,81 +1252,706 @@ pub fn xor_guess_key_len(data: &[u8], min_guess: usize, max_guess: usize) -> usi
 /// # Returns
 /// - A vector containing the guessed key (as bytes).
 ///
+/// # Errors
+/// - `CrypError::EmptyKey` if `key_size` is zero.
+///
 /// # Example
 /// ```rust
-/// let data = vec![/* some ciphertext bytes */];
-/// let key_size = 5;
-/// let guessed_key = xor_guess_key(&data, key_size);
-/// println!("Guessed key: {:?}", guessed_key);
-/// ```
-pub fn xor_guess_key(data: &[u8], key_size: usize) -> Vec<u8> {
-    let mut key = Vec::new();
-
-    for i in 0..key_size {
-        let mut scores: BinaryHeap<(usize, u8)> = BinaryHeap::new();
-        let chunk: Vec<u8> = data.iter()
-                                .skip(i)    //control starting idx
-                                .step_by(key_size)  //collect at every key_size(th) position
-                                .cloned()
-                                .collect();
-
-        for ch in 32..=126 {
-            let decrypted = xor_one_byte(&chunk, ch);
-            let score = score_text(&decrypted);
-            scores.push((score as usize, ch));
+/// use cryp::{xor_guess_key, xor_repeating};
+///
+/// let plaintext = b"It was the best of times, it was the worst of times, it was the age of wisdom, \
+///                   it was the age of foolishness, it was the epoch of belief";
+/// let data = xor_repeating(plaintext, b"ICE").unwrap();
+/// let guessed_key = xor_guess_key(&data, 3).unwrap();
+/// assert_eq!(guessed_key, b"ICE");
+/// ```
+pub fn xor_guess_key(data: &[u8], key_size: usize) -> Result<Vec<u8>, CrypError> {
+    xor_guess_key_with(data, key_size, &LogLikelihood)
+}
+
+/// Like `xor_guess_key`, but ranks the candidate key bytes with `scorer`.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::{AlnumRatio, ChiSquared};
+/// use cryp::{xor_guess_key_with, xor_repeating};
+///
+/// let plaintext = b"Now that the party is jumping, with the bass kicked in and the vegas are pumpin";
+/// let data = xor_repeating(plaintext, b"key").unwrap();
+/// assert_eq!(xor_guess_key_with(&data, 3, &ChiSquared).unwrap(), b"key");
+/// assert!(xor_guess_key_with(&data, 3, &AlnumRatio).is_ok());
+/// ```
+pub fn xor_guess_key_with(data: &[u8], key_size: usize, scorer: &dyn Scorer) -> Result<Vec<u8>, CrypError> {
+    xor_guess_key_in(data, key_size, &KeyAlphabet::Printable, scorer)
+}
+
+/// The bytes a repeating XOR key is assumed to be made of.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum KeyAlphabet {
+    /// Any byte, for binary keys.
+    AllBytes,
+    /// Printable ASCII, space to `~`. The default of `xor_guess_key`.
+    #[default]
+    Printable,
+    /// `0-9a-fA-F`, for keys that are hex strings.
+    HexDigits,
+    /// `a-z`, for keys that are lowercase words.
+    Lowercase,
+    /// An explicit list of bytes.
+    Custom(Vec<u8>),
+}
+
+impl KeyAlphabet {
+    /// Looks up a predefined alphabet by name: `bytes`, `printable`, `hex` or `lower`.
+    pub fn by_name(name: &str) -> Option<KeyAlphabet> {
+        match name.to_ascii_lowercase().as_str() {
+            "bytes" => Some(KeyAlphabet::AllBytes),
+            "printable" => Some(KeyAlphabet::Printable),
+            "hex" => Some(KeyAlphabet::HexDigits),
+            "lower" => Some(KeyAlphabet::Lowercase),
+            _ => None,
+        }
+    }
+
+    /// The candidate bytes, in increasing order for the predefined alphabets.
+    pub fn bytes(&self) -> Vec<u8> {
+        match self {
+            KeyAlphabet::AllBytes => (0..=255).collect(),
+            KeyAlphabet::Printable => (32..=126).collect(),
+            KeyAlphabet::HexDigits => (b'0'..=b'9').chain(b'A'..=b'F').chain(b'a'..=b'f').collect(),
+            KeyAlphabet::Lowercase => (b'a'..=b'z').collect(),
+            KeyAlphabet::Custom(bytes) => bytes.clone(),
+        }
+    }
+}
+
+/// Ranks the candidate bytes of every position of a repeating XOR key.
+///
+/// Each column of `data` (bytes `i`, `i + key_size`, ...) is decrypted with every byte of
+/// `alphabet` and scored on its own.
+///
+/// # Returns
+/// - One list per key position of `(key byte, score)` pairs, best first. Ties keep the order
+///   of `alphabet`.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `key_size` is zero or `alphabet` is empty.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::LogLikelihood;
+/// use cryp::{xor_key_candidates, xor_repeating, KeyAlphabet};
+///
+/// let plaintext = b"Now that the party is jumping, with the bass kicked in and the vegas are pumpin";
+/// let data = xor_repeating(plaintext, &[0x00, 0x9c, 0xff]).unwrap();
+/// let columns = xor_key_candidates(&data, 3, &KeyAlphabet::AllBytes, &LogLikelihood).unwrap();
+/// assert_eq!(columns.len(), 3);
+/// assert_eq!(columns[1].len(), 256);
+/// assert_eq!(columns.iter().map(|column| column[0].0).collect::<Vec<u8>>(), [0x00, 0x9c, 0xff]);
+/// ```
+pub fn xor_key_candidates(
+    data: &[u8],
+    key_size: usize,
+    alphabet: &KeyAlphabet,
+    scorer: &dyn Scorer,
+) -> Result<Vec<Vec<(u8, f64)>>, CrypError> {
+    let candidates = alphabet.bytes();
+    if key_size == 0 || candidates.is_empty() {
+        return Err(CrypError::EmptyKey);
+    }
+
+    Ok((0..key_size)
+        .map(|i| {
+            let column: Vec<u8> = data.iter().skip(i).step_by(key_size).cloned().collect();
+            let mut ranked: Vec<(u8, f64)> = candidates.iter()
+                .map(|&key| (key, scorer.score(&xor_one_byte(&column, key))))
+                .collect();
+            ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+            ranked
+        })
+        .collect())
+}
+
+/// Like `xor_guess_key_with`, but draws the key bytes from `alphabet`. Every key position is
+/// chosen independently; see `xor_guess_key_beam` to score whole keys.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `key_size` is zero or `alphabet` is empty.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::LogLikelihood;
+/// use cryp::{xor_guess_key_in, xor_repeating, KeyAlphabet};
+///
+/// let plaintext = b"Now that the party is jumping, with the bass kicked in and the vegas are pumpin";
+/// let key = [0x13, 0x37, 0xc0, 0xde];
+/// let data = xor_repeating(plaintext, &key).unwrap();
+/// assert_eq!(xor_guess_key_in(&data, 4, &KeyAlphabet::AllBytes, &LogLikelihood).unwrap(), key);
+/// assert_ne!(xor_guess_key_in(&data, 4, &KeyAlphabet::Printable, &LogLikelihood).unwrap(), key);
+/// ```
+pub fn xor_guess_key_in(
+    data: &[u8],
+    key_size: usize,
+    alphabet: &KeyAlphabet,
+    scorer: &dyn Scorer,
+) -> Result<Vec<u8>, CrypError> {
+    Ok(xor_key_candidates(data, key_size, alphabet, scorer)?
+        .iter()
+        .map(|column| column[0].0)
+        .collect())
+}
+
+/// Guesses a repeating XOR key with a beam search over whole keys.
+///
+/// Key positions are filled from left to right. At every step each of the `beam_width` best
+/// partial keys is extended with the `beam_width` best candidates of the next position (see
+/// `xor_key_candidates`), and the extensions are rescored on the plaintext they decrypt: the
+/// first bytes of every `key_size` block, so a scorer that looks at neighbouring bytes (such as
+/// an `ngram::NgramModel` of order 2 or more) can reject combinations that column-wise scoring
+/// can't. Complete keys are scored on the full plaintext.
+///
+/// # Returns
+/// - Up to `beam_width` keys with their scores, best first.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `key_size` is zero or `alphabet` is empty.
+/// - `CrypError::InvalidArgument` if `beam_width` is zero.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::LogLikelihood;
+/// use cryp::{xor_guess_key_beam, xor_repeating, CrypError, KeyAlphabet};
+///
+/// let plaintext = b"Now that the party is jumping, with the bass kicked in and the vegas are pumpin";
+/// let data = xor_repeating(plaintext, b"key").unwrap();
+/// let keys = xor_guess_key_beam(&data, 3, &KeyAlphabet::Lowercase, &LogLikelihood, 4).unwrap();
+/// assert_eq!(keys.len(), 4);
+/// assert_eq!(keys[0].0, b"key");
+/// assert!(keys.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+///
+/// let empty_beam = xor_guess_key_beam(&data, 3, &KeyAlphabet::Lowercase, &LogLikelihood, 0);
+/// assert!(matches!(empty_beam, Err(CrypError::InvalidArgument { name: "beam width", value: 0 })));
+/// ```
+pub fn xor_guess_key_beam(
+    data: &[u8],
+    key_size: usize,
+    alphabet: &KeyAlphabet,
+    scorer: &dyn Scorer,
+    beam_width: usize,
+) -> Result<Vec<(Vec<u8>, f64)>, CrypError> {
+    if beam_width == 0 {
+        return Err(CrypError::InvalidArgument { name: "beam width", value: beam_width });
+    }
+    let columns = xor_key_candidates(data, key_size, alphabet, scorer)?;
+
+    let mut beam: Vec<(Vec<u8>, f64)> = vec![(Vec::new(), 0.0)];
+    for column in &columns {
+        let mut extended: Vec<(Vec<u8>, f64)> = beam.iter()
+            .flat_map(|(prefix, _)| {
+                column.iter().take(beam_width).map(move |&(byte, _)| {
+                    let mut key = prefix.clone();
+                    key.push(byte);
+                    let score = score_key_prefix(data, &key, key_size, scorer);
+                    (key, score)
+                })
+            })
+            .collect();
+        extended.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        extended.truncate(beam_width);
+        beam = extended;
+    }
+    Ok(beam)
+}
+
+/// Scores the plaintext `prefix` decrypts: the first `prefix.len()` bytes of every `key_size`
+/// block, averaged over blocks weighted by length, or the whole plaintext once the key is
+/// complete.
+fn score_key_prefix(data: &[u8], prefix: &[u8], key_size: usize, scorer: &dyn Scorer) -> f64 {
+    if prefix.len() == key_size {
+        let plaintext: Vec<u8> = data.iter().zip(prefix.iter().cycle()).map(|(a, b)| a ^ b).collect();
+        return scorer.score(&plaintext);
+    }
+
+    let (mut total, mut count) = (0.0, 0);
+    for block in data.chunks(key_size) {
+        let plaintext: Vec<u8> = block.iter().zip(prefix).map(|(a, b)| a ^ b).collect();
+        if !plaintext.is_empty() {
+            total += scorer.score(&plaintext) * plaintext.len() as f64;
+            count += plaintext.len();
+        }
+    }
+    if count == 0 { f64::MIN } else { total / count as f64 }
+}
+
+/// Tries every word of `words` as a repeating XOR key and returns the one whose plaintext
+/// scores best, for keys known to be dictionary words. Empty words are skipped.
+///
+/// # Returns
+/// - The best word and its score, or `None` if there are no non-empty words.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::LogLikelihood;
+/// use cryp::{xor_guess_key_word, xor_repeating};
+///
+/// let plaintext = b"Now that the party is jumping, with the bass kicked in and the vegas are pumpin";
+/// let data = xor_repeating(plaintext, b"vanilla").unwrap();
+/// let words = ["ice", "vanilla", "chocolate", "strawberry"];
+/// assert_eq!(xor_guess_key_word(&data, words, &LogLikelihood).unwrap().0, "vanilla");
+/// ```
+pub fn xor_guess_key_word<'a>(
+    data: &[u8],
+    words: impl IntoIterator<Item = &'a str>,
+    scorer: &dyn Scorer,
+) -> Option<(&'a str, f64)> {
+    words.into_iter()
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let plaintext: Vec<u8> = data.iter().zip(word.as_bytes().iter().cycle()).map(|(a, b)| a ^ b).collect();
+            (word, scorer.score(&plaintext))
+        })
+        .fold(None, |best: Option<(&str, f64)>, candidate| match best {
+            Some(best) if best.1 >= candidate.1 => Some(best),
+            _ => Some(candidate),
+        })
+}
+
+/// Key-sized blocks on either side of a crib that `xor_guess_key_with_cribs` scores to rank the
+/// offsets it could be at.
+const CRIB_CONTEXT_BLOCKS: usize = 4;
+
+/// Best-ranked offsets of a crib whose full plaintext `xor_guess_key_with_cribs` scores.
+const CRIB_FINALISTS: usize = 8;
+
+/// A fragment of known plaintext, such as a file header or a protocol keyword.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crib {
+    pub plaintext: Vec<u8>,
+    /// Where the fragment starts in the plaintext, if known.
+    pub offset: Option<usize>,
+}
+
+impl Crib {
+    /// Known plaintext starting at byte `offset`.
+    pub fn at(offset: usize, plaintext: impl Into<Vec<u8>>) -> Crib {
+        Crib { plaintext: plaintext.into(), offset: Some(offset) }
+    }
+
+    /// Known plaintext somewhere in the message.
+    pub fn anywhere(plaintext: impl Into<Vec<u8>>) -> Crib {
+        Crib { plaintext: plaintext.into(), offset: None }
+    }
+}
+
+/// A repeating XOR key recovered by `xor_guess_key_with_cribs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CribKey {
+    pub key: Vec<u8>,
+    /// Which key bytes were derived from a crib; the others come from the statistical solver.
+    pub pinned: Vec<bool>,
+    /// Where each crib was placed, in the order they were given.
+    pub offsets: Vec<usize>,
+    /// The score of the full plaintext.
+    pub score: f64,
+}
+
+/// Recovers a repeating XOR key from known plaintext, guessing the rest statistically.
+///
+/// Every crib pins the key bytes under it: `key[(offset + i) % key_size] = data[offset + i] ^
+/// plaintext[i]`. Cribs with a known offset are applied first. Each crib with an unknown offset
+/// is then tried at every offset where it agrees with the key bytes pinned so far, with itself
+/// (a crib longer than the key covers some key positions twice) and with `alphabet`. The
+/// placements are ranked by how well the plaintext around the crib scores, and of the best few
+/// the one whose full plaintext scores best is kept. Key bytes no crib covers are the best
+/// candidates of `xor_key_candidates`.
+///
+/// # Arguments
+/// - `data`: The ciphertext encrypted with a repeating-key XOR cipher.
+/// - `key_size`: The key size (e.g. from `xor_guess_key_len`).
+/// - `cribs`: The known plaintext fragments.
+/// - `alphabet`: The bytes the key is made of.
+/// - `scorer`: Ranks the candidate plaintexts.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `key_size` is zero or `alphabet` is empty.
+/// - `CrypError::InvalidCrib` if a crib is empty, extends past the end of `data`, contradicts
+///   the key bytes pinned by earlier cribs, or fits nowhere.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::LogLikelihood;
+/// use cryp::{xor_guess_key_with_cribs, xor_repeating, Crib, CrypError, KeyAlphabet};
+///
+/// let plaintext = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"user\": \"alice\"}";
+/// let key = [0x8e, 0x21, 0x5a, 0xf0, 0x03, 0x77, 0xc4, 0x19, 0x62, 0xd5];
+/// let data = xor_repeating(plaintext, &key).unwrap();
+///
+/// let cribs = [Crib::at(0, "HTTP/1.1 "), Crib::anywhere("Content-Type: ")];
+/// let found = xor_guess_key_with_cribs(&data, 10, &cribs, &KeyAlphabet::AllBytes, &LogLikelihood).unwrap();
+/// assert_eq!(found.key, key);
+/// assert_eq!(found.offsets, [0, 17]);
+/// assert!(found.pinned.iter().all(|&pinned| pinned));
+///
+/// let past_the_end = [Crib::at(usize::MAX, "ab")];
+/// let found = xor_guess_key_with_cribs(&data, 10, &past_the_end, &KeyAlphabet::AllBytes, &LogLikelihood);
+/// assert!(matches!(found, Err(CrypError::InvalidCrib { index: 0, .. })));
+/// ```
+pub fn xor_guess_key_with_cribs(
+    data: &[u8],
+    key_size: usize,
+    cribs: &[Crib],
+    alphabet: &KeyAlphabet,
+    scorer: &dyn Scorer,
+) -> Result<CribKey, CrypError> {
+    let columns = xor_key_candidates(data, key_size, alphabet, scorer)?;
+    let allowed = alphabet.bytes();
+
+    let mut pinned: Vec<Option<u8>> = vec![None; key_size];
+    let mut offsets = vec![0; cribs.len()];
+    for (index, crib) in cribs.iter().enumerate() {
+        if crib.plaintext.is_empty() {
+            return Err(CrypError::InvalidCrib { index, reason: "empty crib" });
+        }
+        if let Some(offset) = crib.offset {
+            if offset.checked_add(crib.plaintext.len()).is_none_or(|end| end > data.len()) {
+                return Err(CrypError::InvalidCrib { index, reason: "extends past the end of the ciphertext" });
+            }
+            pinned = pin_crib(data, &pinned, offset, &crib.plaintext, None)
+                .ok_or(CrypError::InvalidCrib { index, reason: "contradicts an earlier crib" })?;
+            offsets[index] = offset;
         }
-        // returns the highest element in the heap
-        key.push(scores.pop().unwrap().1);
     }
 
-    key
+    let fill = |pinned: &[Option<u8>]| -> Vec<u8> {
+        pinned.iter().zip(&columns).map(|(byte, column)| byte.unwrap_or(column[0].0)).collect()
+    };
+    // Scores the plaintext of `range` decrypted with `key`.
+    let score_range = |key: &[u8], range: Range<usize>| {
+        let plaintext: Vec<u8> = range.map(|i| data[i] ^ key[i % key.len()]).collect();
+        scorer.score(&plaintext)
+    };
+    let score = |key: &[u8]| score_range(key, 0..data.len());
+
+    for (index, crib) in cribs.iter().enumerate().filter(|(_, crib)| crib.offset.is_none()) {
+        // Scoring the whole plaintext at every offset would take time quadratic in its length,
+        // so rank the offsets by the plaintext around the crib and only rescore the best few.
+        let context = CRIB_CONTEXT_BLOCKS * key_size;
+        let last_offset = data.len().checked_sub(crib.plaintext.len());
+        let mut placements: Vec<(usize, f64, Vec<Option<u8>>)> = last_offset.into_iter()
+            .flat_map(|last| 0..=last)
+            .filter_map(|offset| Some((offset, pin_crib(data, &pinned, offset, &crib.plaintext, Some(&allowed))?)))
+            .map(|(offset, candidate)| {
+                let window = offset.saturating_sub(context)..(offset + crib.plaintext.len() + context).min(data.len());
+                (offset, score_range(&fill(&candidate), window), candidate)
+            })
+            .collect();
+        placements.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        placements.truncate(CRIB_FINALISTS);
+
+        let (offset, best) = placements.into_iter()
+            .map(|(offset, _, candidate)| (offset, score(&fill(&candidate)), candidate))
+            .fold(None, |best: Option<(usize, f64, Vec<Option<u8>>)>, candidate| match best {
+                Some(best) if best.1 >= candidate.1 => Some(best),
+                _ => Some(candidate),
+            })
+            .map(|(offset, _, candidate)| (offset, candidate))
+            .ok_or(CrypError::InvalidCrib { index, reason: "fits nowhere in the ciphertext" })?;
+        pinned = best;
+        offsets[index] = offset;
+    }
+
+    let key = fill(&pinned);
+    Ok(CribKey {
+        score: score(&key),
+        pinned: pinned.iter().map(Option::is_some).collect(),
+        key,
+        offsets,
+    })
+}
+
+/// Pins the key bytes `plaintext` implies at `offset`, or returns `None` if they contradict
+/// `pinned`, each other, or (when given) the `allowed` key bytes.
+fn pin_crib(
+    data: &[u8],
+    pinned: &[Option<u8>],
+    offset: usize,
+    plaintext: &[u8],
+    allowed: Option<&[u8]>,
+) -> Option<Vec<Option<u8>>> {
+    let mut pinned = pinned.to_vec();
+    for (i, &p) in plaintext.iter().enumerate() {
+        let position = (offset + i) % pinned.len();
+        let byte = data[offset + i] ^ p;
+        match pinned[position] {
+            Some(existing) if existing != byte => return None,
+            Some(_) => {}
+            None if allowed.is_some_and(|allowed| !allowed.contains(&byte)) => return None,
+            None => pinned[position] = Some(byte),
+        }
+    }
+    Some(pinned)
+}
+
+/// XOR with an autokey: the keystream is `primer` followed by the plaintext itself, so unlike
+/// `xor_repeating` it never repeats.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `primer` is empty.
+///
+/// # Example
+/// ```rust
+/// use cryp::{xor_autokey_decrypt, xor_autokey_encrypt};
+///
+/// let ciphertext = xor_autokey_encrypt(b"attack at dawn", b"key").unwrap();
+/// assert_eq!(&ciphertext[..4], &[b'a' ^ b'k', b't' ^ b'e', b't' ^ b'y', b'a' ^ b'a']);
+/// assert_eq!(xor_autokey_decrypt(&ciphertext, b"key").unwrap(), b"attack at dawn");
+/// ```
+pub fn xor_autokey_encrypt(data: &[u8], primer: &[u8]) -> Result<Vec<u8>, CrypError> {
+    if primer.is_empty() {
+        return Err(CrypError::EmptyKey);
+    }
+    Ok(data.iter()
+        .enumerate()
+        .map(|(i, &p)| p ^ if i < primer.len() { primer[i] } else { data[i - primer.len()] })
+        .collect())
 }
 
-pub fn pkcs7_padding(block: &[u8], len: usize) -> Option<Vec<u8>> {
-    if len < block.len() {
-        return None;
+/// Decrypts `xor_autokey_encrypt`, recovering the keystream as it goes.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `primer` is empty.
+pub fn xor_autokey_decrypt(data: &[u8], primer: &[u8]) -> Result<Vec<u8>, CrypError> {
+    if primer.is_empty() {
+        return Err(CrypError::EmptyKey);
+    }
+    let mut plain: Vec<u8> = Vec::with_capacity(data.len());
+    for (i, &c) in data.iter().enumerate() {
+        let key = if i < primer.len() { primer[i] } else { plain[i - primer.len()] };
+        plain.push(c ^ key);
+    }
+    Ok(plain)
+}
+
+/// Breaks XOR with an autokey (see `xor_autokey_encrypt`) by trying every primer length from
+/// `min_primer` to `max_primer`. Bytes a primer length apart form chains that depend on one
+/// primer byte only, so each primer byte is the byte of `alphabet` whose chain `scorer` likes
+/// best, and the length whose whole plaintext scores best wins. Lengths that leave fewer than
+/// 10 bytes per chain are skipped, since such short chains fit any ciphertext.
+///
+/// # Errors
+/// - `CrypError::InvalidRange` unless `1 <= min_primer <= max_primer`.
+/// - `CrypError::NotEnoughData` if `data` is shorter than `10 * min_primer` bytes.
+/// - `CrypError::EmptyKey` if `alphabet` is empty.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::LogLikelihood;
+/// use cryp::{xor_autokey_encrypt, xor_break_autokey, CrypError, KeyAlphabet};
+///
+/// let plaintext = b"It was the best of times, it was the worst of times, it was the age of wisdom, \
+///                   it was the age of foolishness, it was the epoch of belief";
+/// let data = xor_autokey_encrypt(plaintext, b"Dickens").unwrap();
+/// let solution = xor_break_autokey(&data, 1, 10, &KeyAlphabet::Printable, &LogLikelihood).unwrap();
+/// assert_eq!(solution.key, b"Dickens");
+/// assert_eq!(solution.plaintext, plaintext);
+///
+/// let huge = usize::MAX / 2;
+/// let too_long = xor_break_autokey(&data, huge, huge, &KeyAlphabet::Printable, &LogLikelihood);
+/// assert!(matches!(too_long, Err(CrypError::NotEnoughData { .. })));
+/// ```
+pub fn xor_break_autokey(
+    data: &[u8],
+    min_primer: usize,
+    max_primer: usize,
+    alphabet: &KeyAlphabet,
+    scorer: &dyn Scorer,
+) -> Result<classical::Solution<Vec<u8>, Vec<u8>>, CrypError> {
+    if min_primer == 0 || min_primer > max_primer {
+        return Err(CrypError::InvalidRange { min: min_primer, max: max_primer });
+    }
+    let needed = min_primer.saturating_mul(classical::AUTOKEY_MIN_CHAIN);
+    if data.len() < needed {
+        return Err(CrypError::NotEnoughData { needed, got: data.len() });
+    }
+    let max_primer = max_primer.min(data.len() / classical::AUTOKEY_MIN_CHAIN);
+    let candidates = alphabet.bytes();
+    if candidates.is_empty() {
+        return Err(CrypError::EmptyKey);
+    }
+
+    let best = (min_primer..=max_primer)
+        .map(|len| {
+            let (key, plaintext) = classical::solve_autokey(data, len, &candidates, |c, k| c ^ k, |p| p, scorer);
+            let score = scorer.score(&plaintext);
+            classical::Solution { key, plaintext, score }
+        })
+        .reduce(|best, candidate| if candidate.score > best.score { candidate } else { best })
+        .expect("at least one primer length");
+    Ok(best)
+}
+
+/// Splits XOR of two texts (a plaintext and a running key, or two messages that shared a
+/// keystream) into both texts, assuming every byte of both comes from `alphabet`: a beam of
+/// `beam_width` partial solutions is extended byte by byte, scoring both streams with `model`
+/// (an `NgramAlphabet::Bytes` model, ideally of order 3 or 4).
+///
+/// The two texts play the same role, so `plaintext` may hold either of them and `key` the
+/// other. Expect some wrong bytes, especially where both texts are improbable. `Printable`
+/// leaves out newlines; pass a `KeyAlphabet::Custom` for multi-line texts.
+///
+/// # Errors
+/// - `CrypError::EmptyKey` if `alphabet` is empty.
+/// - `CrypError::InvalidArgument` if `beam_width` is 0.
+/// - `CrypError::WrongAlphabet` unless `model` is an `NgramAlphabet::Bytes` model.
+///
+/// # Example
+/// ```rust
+/// use cryp::input::lyrics_model;
+/// use cryp::ngram::NgramAlphabet;
+/// use cryp::{xor_break_running_key, xor_fixed, CrypError, KeyAlphabet};
+///
+/// let model = lyrics_model(3, NgramAlphabet::Bytes).unwrap();
+///
+/// let (a, b) = (b"Play that funky music, white boy", b"Lay down and boogie and play the");
+/// let data = xor_fixed(a, b).unwrap();
+/// let solution = xor_break_running_key(&data, &model, &KeyAlphabet::Printable, 200).unwrap();
+/// // The capitals are lost: XOR can't tell which text has them when both do.
+/// assert_eq!(solution.plaintext, b"play that funky music, white boy");
+/// assert_eq!(solution.key, b"lay down and boogie and play the");
+///
+/// let nothing = KeyAlphabet::Custom(Vec::new());
+/// assert!(matches!(xor_break_running_key(&data, &model, &nothing, 200), Err(CrypError::EmptyKey)));
+/// assert!(matches!(
+///     xor_break_running_key(&data, &model, &KeyAlphabet::Printable, 0),
+///     Err(CrypError::InvalidArgument { name: "beam width", value: 0 })
+/// ));
+///
+/// let letters = lyrics_model(3, NgramAlphabet::Letters).unwrap();
+/// let wrong = xor_break_running_key(&data, &letters, &KeyAlphabet::Printable, 200);
+/// assert!(matches!(wrong, Err(CrypError::WrongAlphabet { .. })));
+/// ```
+pub fn xor_break_running_key(
+    data: &[u8],
+    model: &ngram::NgramModel,
+    alphabet: &KeyAlphabet,
+    beam_width: usize,
+) -> Result<classical::Solution<Vec<u8>, Vec<u8>>, CrypError> {
+    if model.alphabet() != ngram::NgramAlphabet::Bytes {
+        let (expected, got) = (ngram::NgramAlphabet::Bytes.name(), model.alphabet().name());
+        return Err(CrypError::WrongAlphabet { expected, got });
+    }
+    let candidates = alphabet.bytes();
+    if candidates.is_empty() {
+        return Err(CrypError::EmptyKey);
+    }
+    let (plaintext, key, score) =
+        classical::solve_running_key(data, &candidates, |c, p| c ^ p, |b| b, model, beam_width)?;
+    Ok(classical::Solution { key, plaintext, score })
+}
+
+/// Pads `block` to `len` bytes following PKCS#7: every padding byte holds the number of
+/// bytes added.
+///
+/// # Errors
+/// - `CrypError::BadBlockLength` if `block` is longer than `len` or more than 255 bytes of
+///   padding would be needed.
+///
+/// # Example
+/// ```rust
+/// use cryp::pkcs7_padding;
+///
+/// let padded = pkcs7_padding(b"YELLOW SUBMARINE", 20).unwrap();
+/// assert_eq!(padded, b"YELLOW SUBMARINE\x04\x04\x04\x04");
+/// ```
+pub fn pkcs7_padding(block: &[u8], len: usize) -> Result<Vec<u8>, CrypError> {
+    if len < block.len() || len - block.len() > u8::MAX as usize {
+        return Err(CrypError::BadBlockLength { block_size: len, len: block.len() });
     }
     let mut padded_vec: Vec<u8> = block.to_vec();
-    
-    for _ in 0..len - block.len() {
-        padded_vec.push(0x00);
+    padded_vec.resize(len, (len - block.len()) as u8);
+    Ok(padded_vec)
+}
+
+/// Removes and validates PKCS#7 padding from data that is a whole number of `block_size` blocks.
+///
+/// # Errors
+/// - `CrypError::BadBlockLength` if `data` is empty or not a multiple of `block_size`.
+/// - `CrypError::BadPadding` if the trailing bytes are not valid PKCS#7 padding.
+///
+/// # Example
+/// ```rust
+/// use cryp::{pkcs7_unpad, CrypError};
+///
+/// assert_eq!(pkcs7_unpad(b"ICE ICE BABY\x04\x04\x04\x04", 16).unwrap(), b"ICE ICE BABY");
+/// assert!(matches!(pkcs7_unpad(b"ICE ICE BABY\x01\x02\x03\x04", 16), Err(CrypError::BadPadding)));
+/// ```
+pub fn pkcs7_unpad(data: &[u8], block_size: usize) -> Result<Vec<u8>, CrypError> {
+    if data.is_empty() || block_size == 0 || !data.len().is_multiple_of(block_size) {
+        return Err(CrypError::BadBlockLength { block_size, len: data.len() });
+    }
+
+    let padding_len = data[data.len() - 1] as usize;
+    if padding_len == 0 || padding_len > block_size {
+        return Err(CrypError::BadPadding);
     }
-    Some(padded_vec)
+    let (unpadded, padding) = data.split_at(data.len() - padding_len);
+    if padding.iter().any(|&byte| byte as usize != padding_len) {
+        return Err(CrypError::BadPadding);
+    }
+
+    Ok(unpadded.to_vec())
 }
 
-fn aes(block: &[u8], key: &[u8], cipher: Cipher, mode: Mode) -> Vec<u8> {
-    assert_eq!(block.len() % 16, 0);
-    let mut encrypter = Crypter::new(cipher, mode, key, None).unwrap();
+const AES_BLOCK_SIZE: usize = 16;
+
+fn aes(block: &[u8], key: &[u8], cipher: Cipher, mode: Mode) -> Result<Vec<u8>, CrypError> {
+    if key.len() != cipher.key_len() {
+        return Err(CrypError::BadKeyLength { expected: cipher.key_len(), got: key.len() });
+    }
+    if !block.len().is_multiple_of(AES_BLOCK_SIZE) {
+        return Err(CrypError::BadBlockLength { block_size: AES_BLOCK_SIZE, len: block.len() });
+    }
+    let mut encrypter = Crypter::new(cipher, mode, key, None)?;
     encrypter.pad(false);
-    let mut out = vec![0;block.len()+16];
-    encrypter.update(&block, &mut out).unwrap();
+    let mut out = vec![0; block.len() + AES_BLOCK_SIZE];
+    encrypter.update(block, &mut out)?;
     out.truncate(block.len());
-    out
-  }
-  
-pub fn aes_ecb_decrypt(block: &[u8], key: &[u8]) -> Vec<u8> {
-aes(block, key, Cipher::aes_128_ecb(), Mode::Decrypt)
-}
-  
-pub fn aes_ecb_encrypt(block: &[u8], key: &[u8]) -> Vec<u8> {
+    Ok(out)
+}
+
+/// Decrypts `block` (any whole number of 16 byte blocks) with AES-128 in ECB mode, without padding.
+pub fn aes_ecb_decrypt(block: &[u8], key: &[u8]) -> Result<Vec<u8>, CrypError> {
+    aes(block, key, Cipher::aes_128_ecb(), Mode::Decrypt)
+}
+
+/// Encrypts `block` (any whole number of 16 byte blocks) with AES-128 in ECB mode, without padding.
+pub fn aes_ecb_encrypt(block: &[u8], key: &[u8]) -> Result<Vec<u8>, CrypError> {
     aes(block, key, Cipher::aes_128_ecb(), Mode::Encrypt)
 }
 
-pub fn encrypt_cbc(key: &[u8], iv: &[u8], plaintext: &[u8]) -> Option<Vec<u8>> {
-    const BLOCK_SIZE: usize = 16;
-    if key.len() != BLOCK_SIZE || iv.len() != BLOCK_SIZE {
-        return None;
+fn check_iv(iv: &[u8]) -> Result<(), CrypError> {
+    if iv.len() != AES_BLOCK_SIZE {
+        return Err(CrypError::InvalidArgument { name: "iv length", value: iv.len() });
     }
+    Ok(())
+}
+
+/// Encrypts `plaintext` with AES-128 in CBC mode, adding PKCS#7 padding.
+///
+/// # Errors
+/// - `CrypError::BadKeyLength` if `key` is not 16 bytes long.
+/// - `CrypError::InvalidArgument` if `iv` is not 16 bytes long.
+pub fn encrypt_cbc(key: &[u8], iv: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, CrypError> {
+    check_iv(iv)?;
 
-    let padding_len = BLOCK_SIZE - (plaintext.len() % BLOCK_SIZE);
-    let mut padded_plaintext = plaintext.to_vec();
-    padded_plaintext.extend(vec![padding_len as u8; padding_len]);
+    let padded_len = (plaintext.len() / AES_BLOCK_SIZE + 1) * AES_BLOCK_SIZE;
+    let padded_plaintext = pkcs7_padding(plaintext, padded_len)?;
 
     let mut ciphertext = Vec::new();
     let mut previous_block = iv.to_vec();
 
-    for chunk in padded_plaintext.chunks(BLOCK_SIZE) {
-        let mut xored_block = vec![0u8; BLOCK_SIZE];
+    for chunk in padded_plaintext.chunks(AES_BLOCK_SIZE) {
+        let mut xored_block = vec![0u8; AES_BLOCK_SIZE];
         xored_block.iter_mut()
             .zip(chunk.iter())
             .zip(previous_block.iter())
@@ -631,10 +1959,80 @@ pub fn encrypt_cbc(key: &[u8], iv: &[u8], plaintext: &[u8]) -> Option<Vec<u8>> {
                 *xored_byte = chunk_byte ^ prev_byte;
             });
 
-        let encrypted_block = aes_ecb_encrypt(&xored_block, &key);
+        let encrypted_block = aes_ecb_encrypt(&xored_block, key)?;
         ciphertext.extend(&encrypted_block);
         previous_block = encrypted_block;
     }
 
-    Some(ciphertext)
+    Ok(ciphertext)
+}
+
+/// Decrypts AES-128 CBC `ciphertext` and strips its PKCS#7 padding.
+///
+/// # Errors
+/// - `CrypError::BadKeyLength` if `key` is not 16 bytes long.
+/// - `CrypError::InvalidArgument` if `iv` is not 16 bytes long.
+/// - `CrypError::BadBlockLength` if `ciphertext` is empty or not a multiple of 16 bytes.
+/// - `CrypError::BadPadding` if the decrypted padding is invalid.
+///
+/// # Example
+/// ```rust
+/// use cryp::{decrypt_cbc, encrypt_cbc, CrypError};
+///
+/// let key = b"YELLOW SUBMARINE";
+/// let iv = [0u8; 16];
+/// let ciphertext = encrypt_cbc(key, &iv, b"attack at dawn").unwrap();
+/// assert_eq!(decrypt_cbc(key, &iv, &ciphertext).unwrap(), b"attack at dawn");
+///
+/// let truncated = decrypt_cbc(key, &iv, &[ciphertext.as_slice(), b"extra"].concat());
+/// assert!(matches!(truncated, Err(CrypError::BadBlockLength { block_size: 16, len: 21 })));
+///
+/// let short_iv = decrypt_cbc(key, &iv[..8], &ciphertext);
+/// assert!(matches!(short_iv, Err(CrypError::InvalidArgument { name: "iv length", value: 8 })));
+/// ```
+pub fn decrypt_cbc(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, CrypError> {
+    check_iv(iv)?;
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(AES_BLOCK_SIZE) {
+        return Err(CrypError::BadBlockLength { block_size: AES_BLOCK_SIZE, len: ciphertext.len() });
+    }
+
+    let mut plaintext = Vec::new();
+    let mut previous_block = iv;
+
+    for chunk in ciphertext.chunks(AES_BLOCK_SIZE) {
+        let decrypted_block = aes_ecb_decrypt(chunk, key)?;
+        plaintext.extend(decrypted_block.iter()
+            .zip(previous_block.iter())
+            .map(|(&dec_byte, &prev_byte)| dec_byte ^ prev_byte));
+        previous_block = chunk;
+    }
+
+    pkcs7_unpad(&plaintext, AES_BLOCK_SIZE)
+}
+
+/// Counts how many blocks of `block_size` bytes occur more than once in `data`.
+///
+/// ECB encrypts identical plaintext blocks to identical ciphertext blocks, so any
+/// repetition is a strong hint that the data was produced by a block cipher in ECB mode.
+/// A trailing partial block is ignored.
+///
+/// # Arguments
+/// - `data`: The ciphertext to inspect.
+/// - `block_size`: The cipher block size in bytes (16 for AES).
+///
+/// # Returns
+/// - The number of blocks that are a repetition of an earlier block.
+///
+/// # Example
+/// ```rust
+/// use cryp::count_repeated_blocks;
+///
+/// let data = b"YELLOW SUBMARINEYELLOW SUBMARINEsomething else";
+/// assert_eq!(count_repeated_blocks(data, 16), 1);
+/// ```
+pub fn count_repeated_blocks(data: &[u8], block_size: usize) -> usize {
+    let mut seen = HashSet::new();
+    data.chunks_exact(block_size)
+        .filter(|chunk| !seen.insert(*chunk))
+        .count()
 }
@@ -1,12 +1,875 @@
-use cryp::{hexstr_to_bytes, bytes_to_hexstr};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
 
-fn main() {
-    let s = "We started dancing and love put us into a groove";
-    println!("String literal: \"{s}\"\n");
+use colored::*;
+use cryp::{challenges, classical, codec, crib, detect, entropy, hex, randomness, score};
+use cryp::classical::AnnealOptions;
+use cryp::crib::CribSession;
+use cryp::ngram::{NgramAlphabet, NgramModel};
+use cryp::score::{LogLikelihood, Scorer};
+use cryp::input::{parse_base64_lines, parse_hex_lines};
+use cryp::stream::{Base64Decoder, Base64Encoder, HexDecoder, HexEncoder};
+use cryp::{
+    Base64Config, aes_ecb_decrypt, aes_ecb_encrypt, bytes_to_hexstr, character_frequency,
+    count_repeated_blocks, decrypt_cbc, detect_single_byte_xor, encrypt_cbc, hamming_distance_bit,
+    hamming_distance_char, hexstr_to_bytes, xor_break_autokey, xor_break_running_key, xor_guess_key_beam,
+    xor_guess_key_in, xor_guess_key_len_with_pairs, xor_guess_key_with_cribs, xor_guess_key_word, xor_repeating,
+    Crib, DEFAULT_MAX_PAIRS, KeyAlphabet,
+};
+
+const USAGE: &str = "\
+usage: cryp <command> [options] [FILE]
+
+Reads FILE (or stdin when FILE is missing or `-`) and writes to stdout.
+
+commands:
+  encode <ENCODING> [--wrap N] [--no-pad] [FILE]
+                                        encode raw bytes, optionally wrapping lines at N columns
+  decode <ENCODING> [--strict] [--no-pad] [FILE]
+                                        decode text to raw bytes (whitespace is ignored unless --strict)
+      ENCODING is hex, base64, base64url, base32, base58, ascii85, z85, decimal or percent
+  detect [--peel] [--depth N] [FILE]    rank likely encodings; --peel decodes nested layers,
+                                        listing them on stderr and writing the result to stdout
+  hexdump [--xxd] [--reverse] [FILE]   hexdump -C (or xxd) style dump; --reverse parses either back
+  xor --key KEY [--hex-key] [FILE]      XOR with a repeating key
+  break-xor [--min N] [--max N] [--pairs N|all] [--key-alphabet ALPHABET] [--beam N]
+            [--crib TEXT [--crib-offset N]] [--words FILE] [--autokey | --running-key]
+            [--scorer NAME | --model MODEL] [FILE]
+                                        recover a repeating XOR key and print the plaintext;
+                                        --pairs sets the block pairs compared per key size
+                                        (default 1000, `all` compares every pair),
+                                        --beam searches whole keys keeping the N best,
+                                        --crib pins the key bytes under known plaintext,
+                                        --words tries each word of FILE as the key,
+                                        --autokey finds a primer of --min to --max bytes,
+                                        --running-key splits two XORed texts with a MODEL
+      ALPHABET is bytes, printable (default), hex or lower
+      NAME is loglik (default), chi2, alnum or a profile: english, german, french, spanish,
+      russian, code, json or binary; MODEL is a file written by `cryp ngram`
+  crib-drag [--base64] [--auto] FILE    interactive crib dragging over ciphertexts that share a
+                                        keystream, one hex (or base64) line each; type `help`;
+                                        --auto starts from the break-keystream result
+  break-keystream [--base64] [--min-samples N] [--scorer NAME | --model MODEL] [FILE]
+                                        recover a keystream reused across hex (or base64) lines
+                                        and print the plaintexts, `_` where too few lines reach
+  ngram --output MODEL [--order N] [--letters] [--smoothing K] FILE...
+                                        train an N-gram model (default 4, bytes) on text files
+  aes <ecb|cbc> <encrypt|decrypt> --key KEY [--hex-key] [--iv HEX] [FILE]
+                                        AES-128 (ECB without padding, CBC with PKCS#7)
+  classical <CIPHER> <encrypt|decrypt> --key KEY [FILE]
+  classical <CIPHER> break [--min N] [--max N] [--beam N] [--known TEXT [--size N]]
+            [--scorer NAME | --model MODEL] [--restarts N] [--iterations N]
+            [--temperature T] [--seed N] [FILE]
+                                        classical ciphers over letters; break recovers the key
+                                        (--min/--max bound the period, primer, columns or
+                                        rails; substitution, playfair and runningkey need a
+                                        --letters MODEL from `cryp ngram`, transpositions and
+                                        hill an n-gram MODEL; --beam widens the runningkey search;
+                                        --known recovers an N x N hill key from known plaintext)
+      CIPHER is caesar (KEY is a shift), vigenere (a word), autokey (the primer), runningkey
+      (a text at least as long), affine (A,B), substitution (the 26 ciphertext letters for
+      A..Z), columnar (a word), railfence (the number of rails), playfair (a word) or hill
+      (the matrix row by row, e.g. 3,3,2,5)
+  detect-xor [--top N] [--base64] [--scorer NAME | --model MODEL] [FILE]
+                                        rank hex (or base64) lines by how well a single-byte XOR
+                                        key decrypts them to text (default top 10)
+  detect-ecb [--block N] [FILE]         report hex lines containing repeated blocks
+  entropy [--window N [--step N] [--threshold BITS]] [FILE]
+                                        byte statistics and a guess at what the data is; --window
+                                        also prints the entropy of every N bytes (moving --step,
+                                        default N/4) and the regions above --threshold (default 7.5)
+  randomness [--alpha A] [FILE]         run statistical randomness tests (after NIST SP 800-22)
+                                        on the bits of FILE and report their p-values; fails if
+                                        any p-value is below A (default 0.01)
+  freq [FILE]                           letter frequencies
+  hamming [--char] A B                  Hamming distance between two strings
+  run <ID...|--all>                     run Cryptopals challenges (e.g. s1c6) and check the answers";
+
+/// Command-line arguments split into positionals and `--name [value]` options.
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    /// `switches` lists the options that don't take a value.
+    fn parse(raw: &[String], switches: &[&str]) -> Result<Args, Box<dyn Error>> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut iter = raw.iter();
+
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if switches.contains(&name) => {
+                    options.insert(name.to_string(), String::new());
+                }
+                Some(name) => {
+                    let value = iter.next().ok_or(format!("option --{} needs a value", name))?;
+                    options.insert(name.to_string(), value.clone());
+                }
+                None => positional.push(arg.clone()),
+            }
+        }
+
+        Ok(Args { positional, options })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn required(&self, name: &str) -> Result<&str, Box<dyn Error>> {
+        self.option(name).ok_or_else(|| format!("missing required option --{}", name).into())
+    }
+
+    fn number(&self, name: &str, default: usize) -> Result<usize, Box<dyn Error>> {
+        match self.option(name) {
+            Some(value) => Ok(value.parse().map_err(|_| format!("--{} expects a number", name))?),
+            None => Ok(default),
+        }
+    }
+
+    fn positional(&self, idx: usize, what: &str) -> Result<&str, Box<dyn Error>> {
+        self.positional.get(idx).map(String::as_str).ok_or_else(|| format!("missing {}", what).into())
+    }
+
+    /// Reads the input file at positional `idx`, falling back to stdin.
+    fn input(&self, idx: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+        match self.positional.get(idx).map(String::as_str) {
+            None | Some("-") => {
+                let mut buf = Vec::new();
+                io::stdin().read_to_end(&mut buf)?;
+                Ok(buf)
+            }
+            Some(path) => fs::read(path).map_err(|e| format!("{}: {}", path, e).into()),
+        }
+    }
+
+    /// Opens the input file at positional `idx` for streaming, falling back to stdin.
+    fn reader(&self, idx: usize) -> Result<Box<dyn Read>, Box<dyn Error>> {
+        match self.positional.get(idx).map(String::as_str) {
+            None | Some("-") => Ok(Box::new(io::stdin().lock())),
+            Some(path) => {
+                let file = fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+                Ok(Box::new(io::BufReader::new(file)))
+            }
+        }
+    }
+
+    fn input_text(&self, idx: usize) -> Result<String, Box<dyn Error>> {
+        Ok(String::from_utf8(self.input(idx)?)?)
+    }
+
+    /// The plaintext scorer named by `--scorer` or loaded from `--model`, `loglik` by default.
+    fn scorer(&self) -> Result<Box<dyn Scorer>, Box<dyn Error>> {
+        if let Some(path) = self.option("model") {
+            return Ok(Box::new(NgramModel::load(path)?));
+        }
+        let name = self.option("scorer").unwrap_or("loglik");
+        score::by_name(name).ok_or_else(|| format!("unknown scorer: {}", name).into())
+    }
+
+    /// Parses `--key`, either as literal bytes or as hex with `--hex-key`.
+    fn key(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let key = self.required("key")?;
+        if self.flag("hex-key") {
+            Ok(hexstr_to_bytes(key)?)
+        } else {
+            Ok(key.as_bytes().to_vec())
+        }
+    }
+}
+
+fn write_bytes(data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(data)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// The base64 variant selected by the encoding name and the `--strict`/`--no-pad` switches.
+fn base64_config(args: &Args, encoding: &str) -> Option<Base64Config> {
+    let config = match encoding {
+        "base64" => Base64Config::STANDARD,
+        "base64url" => Base64Config::URL_SAFE,
+        _ => return None,
+    };
+    let config = config.with_padding(!args.flag("no-pad"));
+    Some(if args.flag("strict") { config.strict() } else { config })
+}
+
+fn cmd_encode(args: &Args) -> Result<(), Box<dyn Error>> {
+    let encoding = args.positional(0, "encoding")?;
+    let wrap = args.number("wrap", 0)?;
+    let mut input = args.reader(1)?;
+    let stdout = io::stdout().lock();
+
+    let mut stdout = match (encoding, base64_config(args, encoding)) {
+        ("hex", _) => {
+            let mut encoder = HexEncoder::new(stdout).wrap(wrap);
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?
+        }
+        (_, Some(config)) => {
+            let mut encoder = Base64Encoder::with_config(stdout, config).wrap(wrap);
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?
+        }
+        (other, None) => {
+            let codec = codec::by_name(other).ok_or_else(|| format!("unknown encoding: {}", other))?;
+            let mut data = Vec::new();
+            input.read_to_end(&mut data)?;
+            let encoded = codec.encode(&data)?;
+
+            let mut stdout = stdout;
+            if wrap == 0 {
+                stdout.write_all(encoded.as_bytes())?;
+            }
+            for line in encoded.as_bytes().chunks(wrap.max(1)).filter(|_| wrap > 0) {
+                stdout.write_all(line)?;
+                writeln!(stdout)?;
+            }
+            stdout
+        }
+    };
+    if wrap == 0 {
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+fn cmd_decode(args: &Args) -> Result<(), Box<dyn Error>> {
+    let encoding = args.positional(0, "encoding")?;
+    let mut input = args.reader(1)?;
+    let mut decoder: Box<dyn Read> = match (encoding, base64_config(args, encoding)) {
+        ("hex", _) => Box::new(HexDecoder::new(input)),
+        (_, Some(config)) => Box::new(Base64Decoder::with_config(input, config)),
+        (other, None) => {
+            let codec = codec::by_name(other).ok_or_else(|| format!("unknown encoding: {}", other))?;
+            let mut text = String::new();
+            input.read_to_string(&mut text)?;
+            return write_bytes(&codec.decode(&text)?);
+        }
+    };
+    let mut stdout = io::stdout().lock();
+    io::copy(&mut decoder, &mut stdout)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn cmd_xor(args: &Args) -> Result<(), Box<dyn Error>> {
+    let key = args.key()?;
+    let data = args.input(0)?;
+    write_bytes(&xor_repeating(&data, &key)?)
+}
+
+fn cmd_break_xor(args: &Args) -> Result<(), Box<dyn Error>> {
+    let data = args.input(0)?;
+    let min = args.number("min", 2)?;
+    let max = args.number("max", 40)?;
+
+    let scorer = args.scorer()?;
+
+    let alphabet = match args.option("key-alphabet") {
+        Some(name) => KeyAlphabet::by_name(name).ok_or_else(|| format!("unknown key alphabet: {}", name))?,
+        None => KeyAlphabet::Printable,
+    };
+
+    if args.flag("autokey") {
+        let solution = xor_break_autokey(&data, args.number("min", 1)?, max, &alphabet, scorer.as_ref())?;
+        eprintln!("primer: {:?} ({})", String::from_utf8_lossy(&solution.key), bytes_to_hexstr(&solution.key));
+        return write_bytes(&solution.plaintext);
+    }
+    if args.flag("running-key") {
+        let model = NgramModel::load(args.required("model")?)?;
+        let solution = xor_break_running_key(&data, &model, &alphabet, args.number("beam", 1000)?)?;
+        eprintln!("other text: {:?}", String::from_utf8_lossy(&solution.key));
+        return write_bytes(&solution.plaintext);
+    }
+
+    if let Some(path) = args.option("words") {
+        let words = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let (word, _) = xor_guess_key_word(&data, words.split_whitespace(), scorer.as_ref())
+            .ok_or_else(|| format!("{}: no words", path))?;
+        eprintln!("key: {:?}", word);
+        return write_bytes(&xor_repeating(&data, word.as_bytes())?);
+    }
+
+    let max_pairs = match args.option("pairs") {
+        Some("all") => None,
+        _ => Some(args.number("pairs", DEFAULT_MAX_PAIRS)?),
+    };
+    let ranking = xor_guess_key_len_with_pairs(&data, min, max, max_pairs)?;
+    let next: Vec<String> = ranking.candidates[1..].iter().take(3).map(|c| c.key_len.to_string()).collect();
+    eprintln!("key size: {} (confidence {:.2}, next: {})", ranking.best(), ranking.confidence, next.join(", "));
+
+    let key = match (args.option("crib"), args.option("beam")) {
+        (Some(text), _) => {
+            let cribs = match args.option("crib-offset") {
+                Some(_) => [Crib::at(args.number("crib-offset", 0)?, text)],
+                None => [Crib::anywhere(text)],
+            };
+            // A crib rules out key sizes it doesn't fit, so fall back to the next candidates.
+            let found = ranking.candidates.iter()
+                .find_map(|c| {
+                    xor_guess_key_with_cribs(&data, c.key_len, &cribs, &alphabet, scorer.as_ref()).ok()
+                })
+                .ok_or("the crib fits no candidate key size")?;
+            let pinned = found.pinned.iter().filter(|&&pinned| pinned).count();
+            eprintln!("crib at offset {}, pins {} of {} key bytes", found.offsets[0], pinned, found.key.len());
+            found.key
+        }
+        (None, Some(_)) => {
+            let beam_width = args.number("beam", 0)?;
+            xor_guess_key_beam(&data, ranking.best(), &alphabet, scorer.as_ref(), beam_width)?.swap_remove(0).0
+        }
+        (None, None) => xor_guess_key_in(&data, ranking.best(), &alphabet, scorer.as_ref())?,
+    };
+    eprintln!("key: {:?} ({})", String::from_utf8_lossy(&key), bytes_to_hexstr(&key));
+    write_bytes(&xor_repeating(&data, &key)?)
+}
+
+fn cmd_ngram(args: &Args) -> Result<(), Box<dyn Error>> {
+    let output = args.required("output")?;
+    let alphabet = if args.flag("letters") { NgramAlphabet::Letters } else { NgramAlphabet::Bytes };
+    if args.positional.is_empty() {
+        return Err("expected at least one training file".into());
+    }
+
+    let mut model = NgramModel::from_files(args.number("order", 4)?, alphabet, &args.positional)?;
+    if let Some(k) = args.option("smoothing") {
+        model = model.with_smoothing(k.parse().map_err(|_| format!("invalid --smoothing: {}", k))?)?;
+    }
+    model.save(output)?;
+    eprintln!("wrote {} ({}-grams over {})", output, model.order(), alphabet.name());
+    Ok(())
+}
+
+fn cmd_aes(args: &Args) -> Result<(), Box<dyn Error>> {
+    let key = args.key()?;
+    let data = args.input(2)?;
+    let iv = match args.option("iv") {
+        Some(iv) => hexstr_to_bytes(iv)?,
+        None => vec![0u8; 16],
+    };
+
+    let output = match (args.positional(0, "mode")?, args.positional(1, "direction")?) {
+        ("ecb", "encrypt") => aes_ecb_encrypt(&data, &key)?,
+        ("ecb", "decrypt") => aes_ecb_decrypt(&data, &key)?,
+        ("cbc", "encrypt") => encrypt_cbc(&key, &iv, &data)?,
+        ("cbc", "decrypt") => decrypt_cbc(&key, &iv, &data)?,
+        (mode, direction) => return Err(format!("unknown aes operation: {} {}", mode, direction).into()),
+    };
+    write_bytes(&output)
+}
+
+/// Parses a list of numbers written `A,B,...`, such as an affine or Hill key.
+fn number_list(key: &str, what: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    key.split(',')
+        .map(|n| n.trim().parse().map_err(|_| format!("{} key must be numbers separated by commas: {}", what, key).into()))
+        .collect()
+}
+
+/// Parses an affine key written `A,B`.
+fn affine_key(key: &str) -> Result<(u8, u8), Box<dyn Error>> {
+    match number_list(key, "affine")?[..] {
+        [a, b] => Ok((a, b)),
+        _ => Err(format!("affine key must be A,B: {}", key).into()),
+    }
+}
 
-    let hex = bytes_to_hexstr(s.as_bytes());
-    println!("bytes to hex encoded str: {}", hex);
+/// `defaults` with `--restarts`, `--iterations`, `--temperature` and `--seed` applied.
+fn anneal_options(args: &Args, defaults: AnnealOptions) -> Result<AnnealOptions, Box<dyn Error>> {
+    let temperature = match args.option("temperature") {
+        Some(t) => t.parse().map_err(|_| format!("invalid --temperature: {}", t))?,
+        None => defaults.temperature,
+    };
+    Ok(AnnealOptions {
+        restarts: args.number("restarts", defaults.restarts)?,
+        iterations: args.number("iterations", defaults.iterations)?,
+        temperature,
+        seed: args.number("seed", defaults.seed as usize)? as u64,
+    })
+}
 
-    let bytes = hexstr_to_bytes(&hex).unwrap();
-    println!("vector of bytes: {:?}", bytes);
-}
\ No newline at end of file
+fn cmd_classical(args: &Args) -> Result<(), Box<dyn Error>> {
+    let text = args.input_text(2)?;
+    let cipher = args.positional(0, "cipher")?;
+    let direction = args.positional(1, "direction")?;
+
+    if direction == "break" {
+        let scorer = args.scorer()?;
+        let (key, plaintext) = match cipher {
+            "caesar" => {
+                let solution = classical::break_caesar(&text, scorer.as_ref());
+                (solution.key.to_string(), solution.plaintext)
+            }
+            "affine" => {
+                let solution = classical::break_affine(&text, scorer.as_ref());
+                (format!("{},{}", solution.key.0, solution.key.1), solution.plaintext)
+            }
+            "vigenere" => {
+                let (min, max) = (args.number("min", 2)?, args.number("max", 20)?);
+                let solution = classical::break_vigenere(&text, min, max, scorer.as_ref())?;
+                (solution.key, solution.plaintext)
+            }
+            "substitution" => {
+                let model = NgramModel::load(args.required("model")?)?;
+                let solution = classical::break_substitution(&text, &model, &anneal_options(args, AnnealOptions::DEFAULT)?)?;
+                (solution.key, solution.plaintext)
+            }
+            "columnar" => {
+                let (min, max) = (args.number("min", 2)?, args.number("max", 12)?);
+                let options = anneal_options(args, AnnealOptions::DEFAULT)?;
+                let solution = classical::break_columnar(&text, min, max, scorer.as_ref(), &options)?;
+                (solution.key, solution.plaintext)
+            }
+            "railfence" => {
+                let solution = classical::break_rail_fence(&text, args.number("max", 20)?, scorer.as_ref());
+                (solution.key.to_string(), solution.plaintext)
+            }
+            "playfair" => {
+                let model = NgramModel::load(args.required("model")?)?;
+                let solution = classical::break_playfair(&text, &model, &anneal_options(args, AnnealOptions::PLAYFAIR)?)?;
+                (solution.key, solution.plaintext)
+            }
+            "autokey" => {
+                let (min, max) = (args.number("min", 1)?, args.number("max", 20)?);
+                let solution = classical::break_autokey(&text, min, max, scorer.as_ref())?;
+                (solution.key, solution.plaintext)
+            }
+            "runningkey" => {
+                let model = NgramModel::load(args.required("model")?)?;
+                let solution = classical::break_running_key(&text, &model, args.number("beam", 1000)?)?;
+                (solution.key, solution.plaintext)
+            }
+            "hill" => {
+                let key = match args.option("known") {
+                    Some(known) => classical::hill_key_from_known_plaintext(known, &text, args.number("size", 2)?)?,
+                    None => classical::break_hill_2x2(&text, scorer.as_ref())?.key,
+                };
+                let key_text: Vec<String> = key.iter().map(u8::to_string).collect();
+                (key_text.join(","), classical::hill_decrypt(&text, &key)?)
+            }
+            other => return Err(format!("unknown classical cipher: {}", other).into()),
+        };
+        eprintln!("key: {}", key);
+        return write_bytes(plaintext.as_bytes());
+    }
+
+    let key = args.required("key")?;
+    let shift = || key.parse::<u8>().map_err(|_| "caesar key must be a shift");
+    let rails = || key.parse::<usize>().map_err(|_| "railfence key must be a number of rails");
+    let output = match (cipher, direction) {
+        ("caesar", "encrypt") => classical::caesar_encrypt(&text, shift()?),
+        ("caesar", "decrypt") => classical::caesar_decrypt(&text, shift()?),
+        ("vigenere", "encrypt") => classical::vigenere_encrypt(&text, key)?,
+        ("vigenere", "decrypt") => classical::vigenere_decrypt(&text, key)?,
+        ("affine", "encrypt") => {
+            let (a, b) = affine_key(key)?;
+            classical::affine_encrypt(&text, a, b)?
+        }
+        ("affine", "decrypt") => {
+            let (a, b) = affine_key(key)?;
+            classical::affine_decrypt(&text, a, b)?
+        }
+        ("substitution", "encrypt") => classical::substitution_encrypt(&text, key)?,
+        ("substitution", "decrypt") => classical::substitution_decrypt(&text, key)?,
+        ("columnar", "encrypt") => classical::columnar_encrypt(&text, key)?,
+        ("columnar", "decrypt") => classical::columnar_decrypt(&text, key)?,
+        ("railfence", "encrypt") => classical::rail_fence_encrypt(&text, rails()?)?,
+        ("railfence", "decrypt") => classical::rail_fence_decrypt(&text, rails()?)?,
+        ("playfair", "encrypt") => classical::playfair_encrypt(&text, key)?,
+        ("playfair", "decrypt") => classical::playfair_decrypt(&text, key)?,
+        ("autokey", "encrypt") => classical::autokey_encrypt(&text, key)?,
+        ("autokey", "decrypt") => classical::autokey_decrypt(&text, key)?,
+        ("runningkey", "encrypt") => classical::running_key_encrypt(&text, key)?,
+        ("runningkey", "decrypt") => classical::running_key_decrypt(&text, key)?,
+        ("hill", "encrypt") => classical::hill_encrypt(&text, &number_list(key, "hill")?)?,
+        ("hill", "decrypt") => classical::hill_decrypt(&text, &number_list(key, "hill")?)?,
+        (cipher, direction) => return Err(format!("unknown classical operation: {} {}", cipher, direction).into()),
+    };
+    write_bytes(output.as_bytes())
+}
+
+fn cmd_hexdump(args: &Args) -> Result<(), Box<dyn Error>> {
+    if args.flag("reverse") {
+        return write_bytes(&hex::parse_hexdump(&args.input_text(0)?)?);
+    }
+
+    let data = args.input(0)?;
+    let dump = if args.flag("xxd") { hex::xxd(&data) } else { hex::hexdump(&data) };
+    write_bytes(dump.as_bytes())
+}
+
+fn cmd_detect(args: &Args) -> Result<(), Box<dyn Error>> {
+    let text = args.input_text(0)?;
+
+    if args.flag("peel") {
+        let layers = detect::peel(&text, args.number("depth", 16)?);
+        for (depth, layer) in layers.iter().enumerate() {
+            eprintln!("{}. {} ({:.2})", depth + 1, layer.encoding.name(), layer.confidence);
+        }
+        return match layers.last() {
+            Some(layer) => write_bytes(&layer.data),
+            None => Err("input does not look encoded".into()),
+        };
+    }
+
+    for candidate in detect::detect_candidates(&text) {
+        let preview: String = String::from_utf8_lossy(&candidate.decoded)
+            .chars()
+            .take(48)
+            .map(|c| if c.is_control() { '.' } else { c })
+            .collect();
+        println!("{:<10} {:.2}  {}", candidate.encoding.name(), candidate.confidence, preview);
+    }
+    Ok(())
+}
+
+fn cmd_detect_xor(args: &Args) -> Result<(), Box<dyn Error>> {
+    let text = args.input_text(0)?;
+    let lines = if args.flag("base64") { parse_base64_lines(&text)? } else { parse_hex_lines(&text)? };
+    let top = args.number("top", 10)?;
+    let scorer = args.scorer()?;
+
+    for detection in detect_single_byte_xor(&lines, scorer.as_ref(), Some(top)) {
+        let plaintext = String::from_utf8_lossy(&detection.plaintext).replace(|c: char| c.is_control(), ".");
+        println!("line {:4}  key {:#04x}  score {:7.3}  {}", detection.line_idx + 1, detection.key, detection.score, plaintext);
+    }
+    Ok(())
+}
+
+fn cmd_detect_ecb(args: &Args) -> Result<(), Box<dyn Error>> {
+    let block_size = args.number("block", 16)?;
+    if block_size == 0 {
+        return Err("--block must be positive".into());
+    }
+    let lines = parse_hex_lines(&args.input_text(0)?)?;
+
+    for (line_no, data) in lines.iter().enumerate() {
+        let repeats = count_repeated_blocks(data, block_size);
+        if repeats == 0 {
+            continue;
+        }
+
+        println!("line {}: {} repeated block(s)", line_no + 1, repeats);
+        let blocks: Vec<&[u8]> = data.chunks(block_size).collect();
+        for block in &blocks {
+            let hex = bytes_to_hexstr(block);
+            if blocks.iter().filter(|other| *other == block).count() > 1 {
+                print!("{}", hex.red());
+            } else {
+                print!("{}", hex);
+            }
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn cmd_entropy(args: &Args) -> Result<(), Box<dyn Error>> {
+    let data = args.input(0)?;
+    let stats = entropy::ByteStats::new(&data);
+
+    println!("length          {}", stats.len);
+    println!("entropy         {:.4} bits/byte (at most {:.4})", stats.entropy, stats.max_entropy());
+    match stats.chi_squared_sigmas() {
+        Some(sigmas) => println!("chi-squared     {:.2} ({:+.1} sigma from uniform)", stats.chi_squared, sigmas),
+        None => println!("chi-squared     {:.2} (too little data to judge)", stats.chi_squared),
+    }
+    println!("serial corr.    {:.4}", stats.serial_correlation);
+    println!("mean            {:.4}", stats.mean);
+    println!("compression     {:.3}", stats.compression_ratio);
+    println!("printable       {:.1}%", 100.0 * stats.printable);
+    println!("looks like      {}", stats.kind().name());
+
+    if args.option("window").is_some() {
+        let window = args.number("window", 0)?;
+        let step = args.number("step", (window / 4).max(1))?;
+        let threshold: f64 = match args.option("threshold") {
+            Some(value) => value.parse().map_err(|_| "--threshold expects a number")?,
+            None => 7.5,
+        };
+
+        let profile = entropy::entropy_profile(&data, window, step)?;
+        println!();
+        for w in profile {
+            let bar = "#".repeat((w.entropy * 8.0).round() as usize);
+            let line = format!("{:#010x} {:6.3} {}", w.offset, w.entropy, bar);
+            if w.entropy >= threshold {
+                println!("{}", line.red());
+            } else {
+                println!("{}", line);
+            }
+        }
+        println!();
+        for region in entropy::high_entropy_regions(&data, window, step, threshold)? {
+            println!("high entropy {:#010x}..{:#010x} ({} bytes)", region.start, region.end, region.len());
+        }
+    }
+    Ok(())
+}
+
+fn cmd_randomness(args: &Args) -> Result<(), Box<dyn Error>> {
+    let data = args.input(0)?;
+    let alpha: f64 = match args.option("alpha") {
+        Some(value) => value.parse().map_err(|_| "--alpha expects a number")?,
+        None => randomness::DEFAULT_SIGNIFICANCE,
+    };
+
+    let (mut ran, mut failed) = (0, 0);
+    for result in randomness::run_battery(&data) {
+        let p_values = match &result.p_values {
+            Ok(p_values) => p_values.iter().map(|p| format!("{:.6}", p)).collect::<Vec<_>>().join(" "),
+            Err(e) => {
+                println!("{:<20} {}", result.name, format!("skipped: {}", e).yellow());
+                continue;
+            }
+        };
+        ran += 1;
+        if result.passed(alpha) == Some(true) {
+            println!("{:<20} {}  {}", result.name, "pass".green(), p_values);
+        } else {
+            failed += 1;
+            println!("{:<20} {}  {}", result.name, "FAIL".red(), p_values);
+        }
+    }
+
+    if ran == 0 {
+        return Err("every test was skipped, no p-values to judge the data by".into());
+    }
+    if failed > 0 {
+        return Err(format!("{} test(s) with a p-value below {}", failed, alpha).into());
+    }
+    Ok(())
+}
+
+fn cmd_freq(args: &Args) -> Result<(), Box<dyn Error>> {
+    let data = args.input(0)?;
+    let mut frequencies: Vec<(char, usize)> = character_frequency(&data).into_iter().collect();
+    frequencies.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let total: usize = frequencies.iter().map(|&(_, count)| count).sum();
+    for (ch, count) in frequencies {
+        println!("{} {:>8} {:>6.2}%", ch, count, 100.0 * count as f64 / total as f64);
+    }
+    Ok(())
+}
+
+fn cmd_break_keystream(args: &Args) -> Result<(), Box<dyn Error>> {
+    let text = args.input_text(0)?;
+    let ciphertexts = if args.flag("base64") { parse_base64_lines(&text)? } else { parse_hex_lines(&text)? };
+    let min_samples = args.number("min-samples", crib::DEFAULT_MIN_SAMPLES)?;
+    let scorer = args.scorer()?;
+
+    let broken = crib::break_reused_keystream(&ciphertexts, scorer.as_ref(), min_samples);
+    let keystream: String = broken.keystream().iter()
+        .map(|byte| byte.map_or("??".to_string(), |b| format!("{:02x}", b)))
+        .collect();
+    let unsure = broken.columns.iter().filter(|c| !c.sparse && c.confidence < 0.5).count();
+    let sparse = broken.columns.iter().filter(|c| c.sparse).count();
+    eprintln!("keystream: {}", keystream);
+    eprintln!("{} columns, {} with confidence below 0.5, {} too sparse", broken.columns.len(), unsure, sparse);
+    for plaintext in &broken.plaintexts {
+        println!("{}", crib::printable(plaintext));
+    }
+    Ok(())
+}
+
+const CRIB_HELP: &str = "\
+drag N CRIB           guess CRIB in message N at every offset, decrypting the others
+pair A B CRIB         drag CRIB over the XOR of messages A and B
+accept N OFFSET TEXT  message N reads TEXT at OFFSET; fixes the keystream there
+reject OFFSET LEN     forget LEN keystream bytes from OFFSET
+show                  print the messages as far as they are known
+key                   print the keystream recovered so far (?? where unknown)
+quit";
+
+fn cmd_crib_drag(args: &Args) -> Result<(), Box<dyn Error>> {
+    let path = args.positional(0, "ciphertext file")?;
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let ciphertexts = if args.flag("base64") { parse_base64_lines(&text)? } else { parse_hex_lines(&text)? };
+    let mut session = CribSession::new(ciphertexts);
+    if args.flag("auto") {
+        let broken = crib::break_reused_keystream(session.ciphertexts(), &LogLikelihood, crib::DEFAULT_MIN_SAMPLES);
+        session.set_keystream(&broken.keystream());
+        print!("{}", session.render());
+    }
+    println!("{} ciphertexts, messages numbered from 0. Commands:\n{}", session.ciphertexts().len(), CRIB_HELP);
+
+    let stdin = io::stdin();
+    let mut line = String::new();
+    loop {
+        print!("crib> ");
+        io::stdout().flush()?;
+        line.clear();
+        if stdin.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        // Only the line break is trimmed: spaces are part of the crib.
+        let line = line.trim_end_matches(['\n', '\r']);
+        match crib_command(&mut session, line) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(e) => eprintln!("{}", e.to_string().red()),
+        }
+    }
+}
+
+/// Runs one interactive crib-dragging command, returning `false` on `quit`.
+fn crib_command(session: &mut CribSession, line: &str) -> Result<bool, Box<dyn Error>> {
+    let number = |word: Option<&str>, what: &str| -> Result<usize, Box<dyn Error>> {
+        let word = word.ok_or_else(|| format!("missing {}", what))?;
+        Ok(word.parse().map_err(|_| format!("{} should be a number: {}", what, word))?)
+    };
+    let command = line.split(' ').next().unwrap_or("");
+
+    match command {
+        "" => {}
+        "drag" => {
+            let mut words = line.splitn(3, ' ').skip(1);
+            let message = number(words.next(), "message")?;
+            let crib = words.next().ok_or("missing crib")?;
+            for m in session.drag(message, crib.as_bytes())? {
+                let fragments: Vec<String> = m.fragments.iter()
+                    .enumerate()
+                    .filter(|&(i, _)| i != message)
+                    .map(|(_, fragment)| String::from_utf8_lossy(fragment).replace(|c: char| c.is_control(), "."))
+                    .collect();
+                let fragments = fragments.join(" | ");
+                let fragments = if m.is_text { fragments.green().bold().to_string() } else { fragments };
+                println!("{:6} {:5.1}%  {}", m.offset, m.score, fragments);
+            }
+        }
+        "pair" => {
+            let mut words = line.splitn(4, ' ').skip(1);
+            let a = number(words.next(), "first message")?;
+            let b = number(words.next(), "second message")?;
+            let crib = words.next().ok_or("missing crib")?;
+            let ciphertexts = session.ciphertexts();
+            let count = ciphertexts.len();
+            let (a, b) = match (ciphertexts.get(a), ciphertexts.get(b)) {
+                (Some(a), Some(b)) => (a, b),
+                _ => return Err(format!("there are {} messages", count).into()),
+            };
+            print!("{}", crib::highlight(&crib::drag(&crib::xor_pair(a, b), crib.as_bytes())));
+        }
+        "accept" => {
+            let mut words = line.splitn(4, ' ').skip(1);
+            let message = number(words.next(), "message")?;
+            let offset = number(words.next(), "offset")?;
+            let text = words.next().ok_or("missing text")?;
+            session.accept(message, offset, text.as_bytes())?;
+            print!("{}", session.render());
+        }
+        "reject" => {
+            let mut words = line.split(' ').skip(1);
+            let offset = number(words.next(), "offset")?;
+            let len = number(words.next(), "length")?;
+            session.reject(offset, len);
+            print!("{}", session.render());
+        }
+        "show" => print!("{}", session.render()),
+        "key" => {
+            let key: String = session.keystream().iter()
+                .map(|byte| byte.map_or("??".to_string(), |b| format!("{:02x}", b)))
+                .collect();
+            println!("{}", key);
+        }
+        "help" => println!("{}", CRIB_HELP),
+        "quit" | "exit" => return Ok(false),
+        _ => return Err(format!("unknown command: {} (try `help`)", command).into()),
+    }
+    Ok(true)
+}
+
+fn cmd_hamming(args: &Args) -> Result<(), Box<dyn Error>> {
+    let a = args.positional(0, "first string")?;
+    let b = args.positional(1, "second string")?;
+    if args.flag("char") {
+        println!("{}", hamming_distance_char(a, b));
+    } else {
+        println!("{}", hamming_distance_bit(a.as_bytes(), b.as_bytes()));
+    }
+    Ok(())
+}
+
+fn cmd_run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let selected: Vec<_> = if args.flag("all") {
+        challenges::all()
+    } else if args.positional.is_empty() {
+        return Err("expected challenge ids or --all".into());
+    } else {
+        args.positional.iter()
+            .map(|id| challenges::find(id).ok_or_else(|| format!("unknown challenge: {}", id)))
+            .collect::<Result<_, _>>()?
+    };
+
+    let mut failed = 0;
+    for challenge in &selected {
+        let outcome = challenge.run();
+        let status = if outcome.passed() { "PASS".green() } else { "FAIL".red() };
+        println!("{} {:<6} {:<32} {:>9.2?}", status, outcome.id, outcome.title, outcome.elapsed);
+
+        if !outcome.passed() {
+            failed += 1;
+            match &outcome.answer {
+                Ok(answer) => println!("       expected {:?}\n       got      {:?}", outcome.expected, answer),
+                Err(e) => println!("       error: {}", e),
+            }
+        }
+    }
+
+    println!("{} passed, {} failed", selected.len() - failed, failed);
+    if failed > 0 {
+        return Err(format!("{} challenge(s) failed", failed).into());
+    }
+    Ok(())
+}
+
+fn run(raw: &[String]) -> Result<(), Box<dyn Error>> {
+    let (command, rest) = raw.split_first().ok_or(USAGE)?;
+    let args = Args::parse(rest, &["hex-key", "char", "all", "strict", "no-pad", "peel", "xxd", "reverse", "letters", "base64", "auto", "autokey", "running-key"])?;
+
+    match command.as_str() {
+        "encode" => cmd_encode(&args),
+        "decode" => cmd_decode(&args),
+        "hexdump" => cmd_hexdump(&args),
+        "xor" => cmd_xor(&args),
+        "break-xor" => cmd_break_xor(&args),
+        "ngram" => cmd_ngram(&args),
+        "aes" => cmd_aes(&args),
+        "classical" => cmd_classical(&args),
+        "detect" => cmd_detect(&args),
+        "detect-ecb" => cmd_detect_ecb(&args),
+        "entropy" => cmd_entropy(&args),
+        "randomness" => cmd_randomness(&args),
+        "freq" => cmd_freq(&args),
+        "hamming" => cmd_hamming(&args),
+        "detect-xor" => cmd_detect_xor(&args),
+        "crib-drag" => cmd_crib_drag(&args),
+        "break-keystream" => cmd_break_keystream(&args),
+        "run" => cmd_run(&args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("unknown command: {}\n\n{}", other, USAGE).into()),
+    }
+}
+
+fn main() {
+    let raw: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&raw) {
+        eprintln!("cryp: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,397 @@
+//! Character n-gram language models for recognizing plaintext.
+//!
+//! An [`NgramModel`] counts every 1- to `order`-gram of its training text and scores new text
+//! by the add-k smoothed log-probability of its n-grams. Quadgram fitness is the standard
+//! objective for hill-climbing classical cipher solvers, and a byte-level model separates
+//! near-miss XOR keys that unigram scorers rank equally.
+//!
+//! Models work on one of two alphabets:
+//! - [`NgramAlphabet::Letters`]: `A`-`Z` only. Text is uppercased and everything else is
+//!   dropped before counting, the usual setup for classical ciphers.
+//! - [`NgramAlphabet::Bytes`]: all 256 byte values, for XOR and other binary ciphers.
+//!
+//! # Example
+//! ```rust
+//! use cryp::ngram::{NgramAlphabet, NgramModel};
+//! use cryp::score::Scorer;
+//!
+//! let mut model = NgramModel::new(4, NgramAlphabet::Letters).unwrap();
+//! model.train(b"It was the best of times, it was the worst of times, it was the age of wisdom, \
+//!               it was the age of foolishness, it was the epoch of belief, it was the epoch of \
+//!               incredulity, it was the season of Light, it was the season of Darkness");
+//!
+//! assert!(model.fitness(b"THEAGEOFTIMES") > model.fitness(b"QXZVKJQWPXZVK"));
+//! assert!(model.is_text(b"it was the season of wisdom"));
+//! assert!(!model.is_text(b"qxzv kjqw pxzv kqjx"));
+//! assert!(!model.is_text(&[0x01, 0xc8, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]));
+//!
+//! let restored = NgramModel::deserialize(&model.serialize()).unwrap();
+//! assert_eq!(restored.fitness(b"THEAGE"), model.fitness(b"THEAGE"));
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::score::Scorer;
+use crate::{bytes_to_hexstr, hexstr_to_bytes, CrypError};
+
+/// The largest supported order.
+pub const MAX_ORDER: usize = 4;
+
+/// Smoothing constant used unless `NgramModel::with_smoothing` says otherwise.
+pub const DEFAULT_SMOOTHING: f64 = 0.01;
+
+const MAGIC: &str = "cryp-ngram 1";
+
+/// The symbols an `NgramModel` counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NgramAlphabet {
+    /// Case-insensitive `A`-`Z`; other characters are dropped.
+    Letters,
+    /// Every byte value.
+    Bytes,
+}
+
+impl NgramAlphabet {
+    pub fn name(self) -> &'static str {
+        match self {
+            NgramAlphabet::Letters => "letters",
+            NgramAlphabet::Bytes => "bytes",
+        }
+    }
+
+    fn size(self) -> f64 {
+        match self {
+            NgramAlphabet::Letters => 26.0,
+            NgramAlphabet::Bytes => 256.0,
+        }
+    }
+
+    /// The symbols of `text` in this alphabet.
+    fn symbols(self, text: &[u8]) -> Vec<u8> {
+        match self {
+            NgramAlphabet::Letters => text.iter()
+                .filter(|b| b.is_ascii_alphabetic())
+                .map(u8::to_ascii_uppercase)
+                .collect(),
+            NgramAlphabet::Bytes => text.to_vec(),
+        }
+    }
+}
+
+/// Packs an n-gram of at most four symbols into a map key.
+fn pack(gram: &[u8]) -> u32 {
+    gram.iter().fold(0, |key, &b| (key << 8) | b as u32)
+}
+
+fn unpack(key: u32, len: usize) -> Vec<u8> {
+    (0..len).rev().map(|i| (key >> (8 * i)) as u8).collect()
+}
+
+/// A character n-gram model with add-k smoothing.
+#[derive(Debug, Clone)]
+pub struct NgramModel {
+    order: usize,
+    alphabet: NgramAlphabet,
+    smoothing: f64,
+    /// `counts[i]` holds the (i + 1)-gram counts.
+    counts: Vec<HashMap<u32, u64>>,
+    totals: Vec<u64>,
+}
+
+impl NgramModel {
+    /// An empty model counting 1- to `order`-grams.
+    ///
+    /// # Errors
+    /// - `CrypError::InvalidArgument` unless `order` is between 1 and `MAX_ORDER`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::ngram::{NgramAlphabet, NgramModel};
+    /// use cryp::CrypError;
+    ///
+    /// let order = NgramModel::new(5, NgramAlphabet::Bytes);
+    /// assert!(matches!(order, Err(CrypError::InvalidArgument { name: "order", value: 5 })));
+    /// ```
+    pub fn new(order: usize, alphabet: NgramAlphabet) -> Result<NgramModel, CrypError> {
+        if !(1..=MAX_ORDER).contains(&order) {
+            return Err(CrypError::InvalidArgument { name: "order", value: order });
+        }
+        Ok(NgramModel {
+            order,
+            alphabet,
+            smoothing: DEFAULT_SMOOTHING,
+            counts: vec![HashMap::new(); order],
+            totals: vec![0; order],
+        })
+    }
+
+    /// Trains a new model on every file in `paths`.
+    pub fn from_files<P: AsRef<Path>>(order: usize, alphabet: NgramAlphabet, paths: &[P]) -> Result<NgramModel, CrypError> {
+        let mut model = NgramModel::new(order, alphabet)?;
+        for path in paths {
+            model.train_file(path)?;
+        }
+        Ok(model)
+    }
+
+    /// Uses `k` as the add-k smoothing constant (the pseudo-count of every possible n-gram).
+    ///
+    /// # Errors
+    /// - `CrypError::InvalidFloatArgument` unless `k` is positive and finite; without a
+    ///   pseudo-count, unseen n-grams would have a log-probability of -inf.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::ngram::{NgramAlphabet, NgramModel};
+    ///
+    /// let model = NgramModel::new(2, NgramAlphabet::Letters).unwrap();
+    /// assert!(model.clone().with_smoothing(0.5).is_ok());
+    /// assert!(model.clone().with_smoothing(0.0).is_err());
+    /// assert!(model.clone().with_smoothing(f64::NAN).is_err());
+    /// assert!(model.with_smoothing(f64::INFINITY).is_err());
+    /// ```
+    pub fn with_smoothing(mut self, k: f64) -> Result<Self, CrypError> {
+        if !(k > 0.0 && k.is_finite()) {
+            return Err(CrypError::InvalidFloatArgument { name: "smoothing", value: k });
+        }
+        self.smoothing = k;
+        Ok(self)
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn alphabet(&self) -> NgramAlphabet {
+        self.alphabet
+    }
+
+    /// Adds the n-grams of `text` to the counts.
+    pub fn train(&mut self, text: &[u8]) {
+        let symbols = self.alphabet.symbols(text);
+        for n in 1..=self.order {
+            for gram in symbols.windows(n) {
+                *self.counts[n - 1].entry(pack(gram)).or_insert(0) += 1;
+                self.totals[n - 1] += 1;
+            }
+        }
+    }
+
+    /// Adds the n-grams of the file at `path` to the counts.
+    pub fn train_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), CrypError> {
+        let path = path.as_ref();
+        let text = fs::read(path).map_err(|source| CrypError::Io { path: path.to_path_buf(), source })?;
+        self.train(&text);
+        Ok(())
+    }
+
+    /// How often `gram` was seen in training. Grams are given in the model's alphabet
+    /// (uppercase for `Letters`).
+    pub fn count(&self, gram: &[u8]) -> u64 {
+        match gram.len() {
+            1..=MAX_ORDER if gram.len() <= self.order => {
+                self.counts[gram.len() - 1].get(&pack(gram)).copied().unwrap_or(0)
+            }
+            _ => 0,
+        }
+    }
+
+    /// The smoothed natural log-probability of `gram`. Grams that are empty or longer than the
+    /// model's order have no probability and give `f64::NEG_INFINITY`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::ngram::{NgramAlphabet, NgramModel};
+    ///
+    /// let mut model = NgramModel::new(2, NgramAlphabet::Letters).unwrap();
+    /// model.train(b"the cat sat on the mat");
+    /// assert!(model.log_probability(b"TH") > model.log_probability(b"QX"));
+    /// assert_eq!(model.log_probability(b""), f64::NEG_INFINITY);
+    /// assert_eq!(model.log_probability(b"THE"), f64::NEG_INFINITY);
+    /// ```
+    pub fn log_probability(&self, gram: &[u8]) -> f64 {
+        let n = gram.len();
+        if !(1..=self.order).contains(&n) {
+            return f64::NEG_INFINITY;
+        }
+        self.smoothed(self.count(gram), n)
+    }
+
+    /// The smoothed natural log-probability of `symbol` following `context`, which holds at
+    /// most `order - 1` symbols (in the model's alphabet). An empty context gives the unigram
+    /// probability. Unlike a difference of `log_probability`s, unseen contexts fall back to a
+    /// uniform guess instead of looking certain. A context of `order` or more symbols gives
+    /// `f64::NEG_INFINITY`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::ngram::{NgramAlphabet, NgramModel};
+    ///
+    /// let mut model = NgramModel::new(2, NgramAlphabet::Letters).unwrap();
+    /// model.train(b"the cat sat on the mat");
+    /// assert!(model.conditional_log_probability(b"T", b'H') > model.conditional_log_probability(b"T", b'Q'));
+    /// assert_eq!(model.conditional_log_probability(b"TH", b'E'), f64::NEG_INFINITY);
+    /// ```
+    pub fn conditional_log_probability(&self, context: &[u8], symbol: u8) -> f64 {
+        if context.len() >= self.order {
+            return f64::NEG_INFINITY;
+        }
+        if context.is_empty() {
+            return self.log_probability(&[symbol]);
+        }
+        let mut gram = context.to_vec();
+        gram.push(symbol);
+        let seen = self.count(&gram) as f64 + self.smoothing;
+        (seen / (self.count(context) as f64 + self.smoothing * self.alphabet.size())).ln()
+    }
+
+    /// The log-probability of an `n`-gram seen `count` times.
+    fn smoothed(&self, count: u64, n: usize) -> f64 {
+        let possible = self.alphabet.size().powi(n as i32);
+        let total = self.totals[n - 1] as f64 + self.smoothing * possible;
+        ((count as f64 + self.smoothing) / total).ln()
+    }
+
+    /// The summed log-probability of every n-gram of `text` after mapping it to the model's
+    /// alphabet: the fitness function of classical cipher solvers. Texts shorter than the
+    /// order are scored with shorter grams; empty text scores 0.
+    pub fn fitness(&self, text: &[u8]) -> f64 {
+        self.symbol_fitness(&self.alphabet.symbols(text)).0
+    }
+
+    /// Fitness and n-gram count of text already in the model's alphabet.
+    fn symbol_fitness(&self, symbols: &[u8]) -> (f64, usize) {
+        let n = self.order.min(symbols.len());
+        if n == 0 {
+            return (0.0, 0);
+        }
+        let grams = symbols.windows(n);
+        let count = grams.len();
+        (grams.map(|gram| self.log_probability(gram)).sum(), count)
+    }
+
+    /// The average log-probability of an n-gram drawn from the training text, i.e. the score
+    /// that typical training-like text gets.
+    pub fn expected_score(&self) -> f64 {
+        let n = self.order;
+        let total = self.totals[n - 1] as f64;
+        if total == 0.0 {
+            return f64::MIN;
+        }
+        self.counts[n - 1].iter()
+            .map(|(&key, &count)| count as f64 / total * self.log_probability(&unpack(key, n)))
+            .sum()
+    }
+
+    /// Writes the model as text: a header followed by one `HEX-GRAM COUNT` line per n-gram.
+    pub fn serialize(&self) -> String {
+        let mut out = format!(
+            "{}\norder {}\nalphabet {}\nsmoothing {}\n",
+            MAGIC, self.order, self.alphabet.name(), self.smoothing
+        );
+        for (i, counts) in self.counts.iter().enumerate() {
+            let mut grams: Vec<(&u32, &u64)> = counts.iter().collect();
+            grams.sort();
+            for (&key, count) in grams {
+                out.push_str(&format!("{} {}\n", bytes_to_hexstr(&unpack(key, i + 1)), count));
+            }
+        }
+        out
+    }
+
+    /// Reads a model written by `serialize`.
+    ///
+    /// # Errors
+    /// - `CrypError::InvalidModel` with the 1-based line of the first malformed line.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::ngram::NgramModel;
+    /// use cryp::CrypError;
+    ///
+    /// let model = NgramModel::deserialize("cryp-ngram 1\norder 2\nalphabet letters\nsmoothing 0.5\n41 3\n").unwrap();
+    /// assert_eq!(model.count(b"A"), 3);
+    ///
+    /// let unsmoothed = NgramModel::deserialize("cryp-ngram 1\norder 2\nalphabet letters\nsmoothing -1\n");
+    /// assert!(matches!(unsmoothed, Err(CrypError::InvalidModel { line: 4, .. })));
+    /// ```
+    pub fn deserialize(text: &str) -> Result<NgramModel, CrypError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line.trim()));
+        let mut header = |key: &str| -> Result<String, CrypError> {
+            let (line, text) = lines.next().ok_or(CrypError::InvalidModel { line: 0, reason: "truncated header" })?;
+            text.strip_prefix(key)
+                .map(|value| value.trim().to_string())
+                .ok_or(CrypError::InvalidModel { line, reason: "unexpected header line" })
+        };
+        let invalid = |line, reason| CrypError::InvalidModel { line, reason };
+
+        header(MAGIC)?;
+        let order = header("order")?.parse().map_err(|_| invalid(2, "invalid order"))?;
+        let alphabet = match header("alphabet")?.as_str() {
+            "letters" => NgramAlphabet::Letters,
+            "bytes" => NgramAlphabet::Bytes,
+            _ => return Err(invalid(3, "unknown alphabet")),
+        };
+        let smoothing = header("smoothing")?.parse().map_err(|_| invalid(4, "invalid smoothing"))?;
+        let mut model = NgramModel::new(order, alphabet).map_err(|_| invalid(2, "unsupported order"))?
+            .with_smoothing(smoothing)
+            .map_err(|_| invalid(4, "smoothing must be positive and finite"))?;
+
+        for (line, text) in lines.filter(|(_, text)| !text.is_empty()) {
+            let (gram, count) = text.split_once(' ').ok_or(invalid(line, "expected a gram and a count"))?;
+            let gram = hexstr_to_bytes(gram).map_err(|e| CrypError::Line { line, source: Box::new(e) })?;
+            let count: u64 = count.trim().parse().map_err(|_| invalid(line, "invalid count"))?;
+            if gram.is_empty() || gram.len() > order {
+                return Err(invalid(line, "n-gram longer than the order"));
+            }
+            *model.counts[gram.len() - 1].entry(pack(&gram)).or_insert(0) += count;
+            model.totals[gram.len() - 1] += count;
+        }
+        Ok(model)
+    }
+
+    /// Saves the model to `path` (see `serialize`).
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CrypError> {
+        let path = path.as_ref();
+        fs::write(path, self.serialize()).map_err(|source| CrypError::Io { path: path.to_path_buf(), source })
+    }
+
+    /// Loads a model saved by `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<NgramModel, CrypError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| CrypError::Io { path: path.to_path_buf(), source })?;
+        NgramModel::deserialize(&text)
+    }
+}
+
+/// Scores text by its average n-gram log-probability. With `Letters`, whitespace is skipped
+/// for free but every other non-letter byte costs as much as an unseen n-gram, so binary
+/// garbage can't score well by containing few letters.
+///
+/// Column-wise breakers such as `xor_guess_key_with` only see every k-th byte, so higher
+/// orders add nothing there; they pay off when whole candidate plaintexts are compared.
+impl Scorer for NgramModel {
+    fn name(&self) -> &'static str { "ngram" }
+
+    fn score(&self, data: &[u8]) -> f64 {
+        let symbols = self.alphabet.symbols(data);
+        let penalized = match self.alphabet {
+            NgramAlphabet::Letters => data.iter().filter(|b| !b.is_ascii_alphabetic() && !b.is_ascii_whitespace()).count(),
+            NgramAlphabet::Bytes => 0,
+        };
+
+        let (fitness, grams) = self.symbol_fitness(&symbols);
+        if grams + penalized == 0 {
+            return f64::MIN;
+        }
+        let unseen = self.smoothed(0, self.order);
+        (fitness + penalized as f64 * unseen) / (grams + penalized) as f64
+    }
+
+    /// Accepts `data` when it scores within half again of `expected_score`.
+    fn is_text(&self, data: &[u8]) -> bool {
+        self.score(data) >= 1.5 * self.expected_score()
+    }
+}
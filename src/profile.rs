@@ -0,0 +1,364 @@
+//! Plaintext profiles for languages other than English, source code, JSON and binary files.
+//!
+//! Every text profile is a unigram model over *bytes*: the character frequencies of the
+//! language are expanded into their UTF-8 encodings, so a profile scores whole texts and the
+//! strided columns of a repeating-key XOR alike, including Cyrillic where every letter is two
+//! bytes. [`Profile::Binary`] instead recognizes file formats by their magic numbers.
+//!
+//! # Example
+//! ```rust
+//! use cryp::profile::Profile;
+//! use cryp::score::Scorer;
+//!
+//! let german = "Über allen Gipfeln ist Ruh, in allen Wipfeln spürest du kaum einen Hauch".as_bytes();
+//! let russian = "Мой дядя самых честных правил, когда не в шутку занемог".as_bytes();
+//!
+//! assert_eq!(Profile::best_match(german), Profile::German);
+//! assert_eq!(Profile::best_match(russian), Profile::Russian);
+//! assert!(Profile::Russian.is_text(russian));
+//! assert!(!Profile::English.is_text(russian));
+//! assert_eq!(Profile::best_match(b"{\"id\": 42, \"tags\": [\"a\", \"b\"]}"), Profile::Json);
+//! ```
+
+use std::sync::OnceLock;
+
+use crate::score::{Scorer, ENGLISH_LETTER_FREQUENCIES, LETTER_SHARE, SPACE_SHARE};
+
+const GERMAN: &[(char, f64)] = &[
+    ('a', 6.516), ('b', 1.886), ('c', 2.732), ('d', 5.076), ('e', 16.396), ('f', 1.656), ('g', 3.009),
+    ('h', 4.577), ('i', 6.550), ('j', 0.268), ('k', 1.417), ('l', 3.437), ('m', 2.534), ('n', 9.776),
+    ('o', 2.594), ('p', 0.670), ('q', 0.018), ('r', 7.003), ('s', 7.270), ('t', 6.154), ('u', 4.166),
+    ('v', 0.846), ('w', 1.921), ('x', 0.034), ('y', 0.039), ('z', 1.134), ('ä', 0.578), ('ö', 0.443),
+    ('ü', 0.995), ('ß', 0.307),
+];
+
+const FRENCH: &[(char, f64)] = &[
+    ('a', 7.636), ('b', 0.901), ('c', 3.260), ('d', 3.669), ('e', 14.715), ('f', 1.066), ('g', 0.866),
+    ('h', 0.737), ('i', 7.529), ('j', 0.613), ('k', 0.074), ('l', 5.456), ('m', 2.968), ('n', 7.095),
+    ('o', 5.796), ('p', 2.521), ('q', 1.362), ('r', 6.693), ('s', 7.948), ('t', 7.244), ('u', 6.311),
+    ('v', 1.838), ('w', 0.049), ('x', 0.427), ('y', 0.128), ('z', 0.326), ('à', 0.486), ('â', 0.051),
+    ('ç', 0.085), ('è', 0.271), ('é', 1.504), ('ê', 0.218), ('ë', 0.008), ('î', 0.045), ('ï', 0.005),
+    ('ô', 0.023), ('ù', 0.058), ('û', 0.060), ('œ', 0.018),
+];
+
+const SPANISH: &[(char, f64)] = &[
+    ('a', 11.525), ('b', 2.215), ('c', 4.019), ('d', 5.010), ('e', 12.181), ('f', 0.692), ('g', 1.768),
+    ('h', 0.703), ('i', 6.247), ('j', 0.493), ('k', 0.011), ('l', 4.967), ('m', 3.157), ('n', 6.712),
+    ('o', 8.683), ('p', 2.510), ('q', 0.877), ('r', 6.871), ('s', 7.977), ('t', 4.632), ('u', 2.927),
+    ('v', 1.138), ('w', 0.017), ('x', 0.215), ('y', 1.008), ('z', 0.467), ('á', 0.502), ('é', 0.433),
+    ('í', 0.725), ('ñ', 0.311), ('ó', 0.827), ('ú', 0.168), ('ü', 0.012),
+];
+
+const RUSSIAN: &[(char, f64)] = &[
+    ('о', 10.97), ('е', 8.45), ('а', 8.01), ('и', 7.35), ('н', 6.70), ('т', 6.26), ('с', 5.47),
+    ('р', 4.73), ('в', 4.54), ('л', 4.40), ('к', 3.49), ('м', 3.21), ('д', 2.98), ('п', 2.81),
+    ('у', 2.62), ('я', 2.01), ('ы', 1.90), ('ь', 1.74), ('г', 1.70), ('з', 1.65), ('б', 1.59),
+    ('ч', 1.44), ('й', 1.21), ('х', 0.97), ('ж', 0.94), ('ш', 0.73), ('ю', 0.64), ('ц', 0.48),
+    ('щ', 0.36), ('э', 0.32), ('ф', 0.26), ('ъ', 0.04), ('ё', 0.04),
+];
+
+const PUNCTUATION: &str = ".,'\"!?;:-()\n";
+
+/// Relative weights of every byte value, turned into log-probabilities by `finish`.
+struct ByteWeights([f64; 256]);
+
+impl ByteWeights {
+    fn new() -> ByteWeights {
+        ByteWeights([1e-6; 256])
+    }
+
+    /// Adds `weight` to every byte of the UTF-8 encoding of `c`.
+    fn add(&mut self, c: char, weight: f64) {
+        let mut buf = [0; 4];
+        for b in c.encode_utf8(&mut buf).bytes() {
+            self.0[b as usize] += weight;
+        }
+    }
+
+    /// Spreads `weight` evenly over `chars`.
+    fn add_all(&mut self, chars: &str, weight: f64) {
+        let n = chars.chars().count() as f64;
+        for c in chars.chars() {
+            self.add(c, weight / n);
+        }
+    }
+
+    /// Adds letters with relative `frequencies`, `share` of the text in total, of which
+    /// `upper` is uppercase.
+    fn add_letters(&mut self, frequencies: &[(char, f64)], share: f64, upper: f64) {
+        let sum: f64 = frequencies.iter().map(|&(_, f)| f).sum();
+        for &(c, f) in frequencies {
+            let weight = f / sum * share;
+            let mut uppercase = c.to_uppercase();
+            match (uppercase.next(), uppercase.next()) {
+                (Some(u), None) if u != c => {
+                    self.add(c, weight * (1.0 - upper));
+                    self.add(u, weight * upper);
+                }
+                _ => self.add(c, weight),
+            }
+        }
+    }
+
+    /// Gives every printable ASCII character at least `weight`.
+    fn floor_printable(&mut self, weight: f64) {
+        for w in &mut self.0[0x21..0x7f] {
+            *w = w.max(weight);
+        }
+    }
+
+    fn finish(self) -> [f64; 256] {
+        let total: f64 = self.0.iter().sum();
+        self.0.map(|w| (w / total).ln())
+    }
+}
+
+fn english_letters() -> Vec<(char, f64)> {
+    ('a'..='z').zip(ENGLISH_LETTER_FREQUENCIES).collect()
+}
+
+fn prose(letters: &[(char, f64)]) -> [f64; 256] {
+    let mut weights = ByteWeights::new();
+    weights.add_letters(letters, LETTER_SHARE, 0.05);
+    weights.add(' ', SPACE_SHARE);
+    weights.add_all("0123456789", 0.005);
+    weights.add_all(PUNCTUATION, 0.0035 * PUNCTUATION.len() as f64);
+    weights.floor_printable(0.0001);
+    weights.finish()
+}
+
+fn source_code() -> [f64; 256] {
+    let mut weights = ByteWeights::new();
+    weights.add_letters(&english_letters(), 0.55, 0.15);
+    weights.add(' ', 0.20);
+    weights.add('\n', 0.03);
+    weights.add('\t', 0.005);
+    weights.add_all("0123456789", 0.02);
+    weights.add_all("(){}[];,.=_:\"'<>+-*/&|!#", 0.15);
+    weights.floor_printable(0.0005);
+    weights.finish()
+}
+
+fn json() -> [f64; 256] {
+    let mut weights = ByteWeights::new();
+    weights.add_letters(&english_letters(), 0.45, 0.10);
+    weights.add_all("0123456789", 0.08);
+    weights.add('"', 0.12);
+    weights.add_all("{}[]:,", 0.13);
+    weights.add(' ', 0.15);
+    weights.add('\n', 0.03);
+    weights.add_all(".-_", 0.01);
+    weights.floor_printable(0.0005);
+    weights.finish()
+}
+
+/// A file format recognized by its leading magic number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    Png,
+    Zip,
+    Pdf,
+    Elf,
+}
+
+impl FileType {
+    pub const ALL: [FileType; 4] = [FileType::Png, FileType::Zip, FileType::Pdf, FileType::Elf];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FileType::Png => "PNG",
+            FileType::Zip => "ZIP",
+            FileType::Pdf => "PDF",
+            FileType::Elf => "ELF",
+        }
+    }
+
+    /// The bytes every file of this type starts with.
+    pub fn magic(self) -> &'static [u8] {
+        match self {
+            FileType::Png => b"\x89PNG\r\n\x1a\n",
+            FileType::Zip => b"PK\x03\x04",
+            FileType::Pdf => b"%PDF-",
+            FileType::Elf => b"\x7fELF",
+        }
+    }
+
+    /// The type whose magic number `data` starts with.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::profile::FileType;
+    ///
+    /// assert_eq!(FileType::identify(b"%PDF-1.7\n..."), Some(FileType::Pdf));
+    /// assert_eq!(FileType::identify(b"plain text"), None);
+    /// ```
+    pub fn identify(data: &[u8]) -> Option<FileType> {
+        FileType::ALL.into_iter().find(|file_type| data.starts_with(file_type.magic()))
+    }
+}
+
+/// A kind of plaintext to recognize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+    English,
+    German,
+    French,
+    Spanish,
+    /// Russian in UTF-8.
+    Russian,
+    /// Program source in a C-like language.
+    SourceCode,
+    Json,
+    /// A file in one of the `FileType` formats.
+    Binary,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 8] = [
+        Profile::English,
+        Profile::German,
+        Profile::French,
+        Profile::Spanish,
+        Profile::Russian,
+        Profile::SourceCode,
+        Profile::Json,
+        Profile::Binary,
+    ];
+
+    /// Looks up a profile by its `Scorer::name`, e.g. `"german"`.
+    pub fn by_name(name: &str) -> Option<Profile> {
+        Profile::ALL.into_iter().find(|profile| profile.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Natural log-probability of every byte value, for the text profiles.
+    pub fn byte_log_probabilities(self) -> Option<&'static [f64; 256]> {
+        static TABLES: OnceLock<Vec<[f64; 256]>> = OnceLock::new();
+        let tables = TABLES.get_or_init(|| {
+            vec![
+                prose(&english_letters()),
+                prose(GERMAN),
+                prose(FRENCH),
+                prose(SPANISH),
+                prose(RUSSIAN),
+                source_code(),
+                json(),
+            ]
+        });
+        tables.get(Profile::ALL.iter().position(|&profile| profile == self)?)
+    }
+
+    /// The average score of text that follows the profile exactly (the negated entropy of its
+    /// byte distribution). `is_text` accepts text scoring within half again of this.
+    pub fn expected_score(self) -> f64 {
+        match self.byte_log_probabilities() {
+            Some(table) => table.iter().map(|&log_p| log_p.exp() * log_p).sum(),
+            None => 0.0,
+        }
+    }
+
+    /// The text profile that scores `data` best (`Binary` when `data` starts with a known magic
+    /// number).
+    ///
+    /// # Example
+    /// ```rust
+    /// use cryp::profile::Profile;
+    ///
+    /// let spanish = "En un lugar de la Mancha, de cuyo nombre no quiero acordarme";
+    /// let french = "Longtemps, je me suis couché de bonne heure. Parfois, à peine ma bougie éteinte";
+    /// let code = "fn main() {\n    let x = vec![1, 2, 3];\n    println!(\"{:?}\", x);\n}\n";
+    ///
+    /// assert_eq!(Profile::best_match(spanish.as_bytes()), Profile::Spanish);
+    /// assert_eq!(Profile::best_match(french.as_bytes()), Profile::French);
+    /// assert_eq!(Profile::best_match(code.as_bytes()), Profile::SourceCode);
+    /// assert_eq!(Profile::best_match(b"\x7fELF\x02\x01\x01\x00"), Profile::Binary);
+    /// ```
+    pub fn best_match(data: &[u8]) -> Profile {
+        if FileType::identify(data).is_some() {
+            return Profile::Binary;
+        }
+        Profile::ALL.into_iter()
+            .filter(|&profile| profile != Profile::Binary)
+            .map(|profile| (profile, profile.score(data)))
+            .fold((Profile::English, f64::NEG_INFINITY), |best, candidate| {
+                if candidate.1 > best.1 { candidate } else { best }
+            })
+            .0
+    }
+}
+
+/// Whether `data` is UTF-8, allowing a character cut off at the end.
+fn is_utf8(data: &[u8]) -> bool {
+    match std::str::from_utf8(data) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// Whether `data` has balanced JSON brackets outside of strings and starts like a JSON value.
+fn looks_like_json(data: &[u8]) -> bool {
+    let trimmed = data.trim_ascii_start();
+    if !matches!(trimmed.first(), Some(b'{' | b'[')) {
+        return false;
+    }
+
+    let mut depth: i64 = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &b in trimmed {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' if in_string => escaped = true,
+            b'"' => in_string = !in_string,
+            b'{' | b'[' if !in_string => depth += 1,
+            b'}' | b']' if !in_string => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return false;
+        }
+    }
+    true
+}
+
+/// Text profiles score the average log-probability per byte, like `LogLikelihood`. `Binary`
+/// scores the fraction of the best matching magic number present at the start of the data.
+impl Scorer for Profile {
+    fn name(&self) -> &'static str {
+        match self {
+            Profile::English => "english",
+            Profile::German => "german",
+            Profile::French => "french",
+            Profile::Spanish => "spanish",
+            Profile::Russian => "russian",
+            Profile::SourceCode => "code",
+            Profile::Json => "json",
+            Profile::Binary => "binary",
+        }
+    }
+
+    fn score(&self, data: &[u8]) -> f64 {
+        if data.is_empty() {
+            return f64::MIN;
+        }
+        match self.byte_log_probabilities() {
+            Some(table) => data.iter().map(|&b| table[b as usize]).sum::<f64>() / data.len() as f64,
+            None => FileType::ALL.iter()
+                .map(|file_type| {
+                    let magic = file_type.magic();
+                    let matched = magic.iter().zip(data).take_while(|(a, b)| a == b).count();
+                    matched as f64 / magic.len() as f64
+                })
+                .fold(0.0, f64::max),
+        }
+    }
+
+    /// Text profiles require UTF-8 and a score within half again of `expected_score`; `Json`
+    /// also requires balanced brackets. `Binary` requires a known magic number.
+    fn is_text(&self, data: &[u8]) -> bool {
+        match self {
+            Profile::Binary => FileType::identify(data).is_some(),
+            Profile::Json if !looks_like_json(data) => false,
+            _ => !data.is_empty() && is_utf8(data) && self.score(data) >= 1.5 * self.expected_score(),
+        }
+    }
+}
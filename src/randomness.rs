@@ -0,0 +1,596 @@
+//! Statistical tests for keystreams and random number generator output, after NIST SP 800-22.
+//!
+//! Each test looks at the data as a sequence of bits and returns a p-value: the probability
+//! that truly random bits would look at least as non-random by that measure. Random data gives
+//! p-values spread evenly over `0..1`, so a single p-value below the significance level (1% by
+//! default) is expected once in a hundred tests; a generator fails when it does so
+//! consistently. The tests only reveal statistical bias: passing them says nothing about
+//! whether the output can be predicted by someone who knows the generator.
+//!
+//! The tests take bits, as given by [`bits`], so that they can be checked against the worked
+//! examples of SP 800-22; [`run_battery`] runs all of them on bytes with suitable parameters.
+//!
+//! # Example
+//! ```rust
+//! use cryp::randomness::{bits, frequency, runs};
+//!
+//! // Balanced, but the bits change value far too rarely.
+//! let stream = bits(&[0x0f; 32]);
+//! assert_eq!(frequency(&stream).unwrap(), 1.0);
+//! assert!(runs(&stream).unwrap() < 0.01);
+//! ```
+
+use std::f64::consts::{LN_2, PI};
+
+use crate::CrypError;
+
+/// Significance level below which a p-value counts as a failure.
+pub const DEFAULT_SIGNIFICANCE: f64 = 0.01;
+
+/// Shortest input the battery runs on; below this most p-values mean little.
+pub const MIN_BITS: usize = 100;
+
+/// Largest pattern length of the serial and approximate entropy tests, which count every
+/// pattern of that many bits.
+const MAX_PATTERN_LEN: usize = 24;
+
+/// Pattern lengths the battery uses for the serial and approximate entropy tests, given
+/// enough data.
+const SERIAL_PATTERN_LEN: usize = 16;
+const APPROXIMATE_ENTROPY_PATTERN_LEN: usize = 10;
+
+/// Block length the battery uses for the block frequency test, given enough data.
+const BLOCK_FREQUENCY_LEN: usize = 128;
+
+/// Unpacks bytes into bits, most significant bit first.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::bits;
+///
+/// assert_eq!(bits(&[0xa0]), [true, false, true, false, false, false, false, false]);
+/// ```
+pub fn bits(data: &[u8]) -> Vec<bool> {
+    data.iter().flat_map(|&byte| (0..8).rev().map(move |i| byte >> i & 1 == 1)).collect()
+}
+
+fn require(bits: &[bool], needed: usize) -> Result<(), CrypError> {
+    if bits.len() < needed {
+        return Err(CrypError::NotEnoughData { needed, got: bits.len() });
+    }
+    Ok(())
+}
+
+/// Frequency (monobit) test: are there about as many ones as zeros?
+///
+/// # Errors
+/// - `CrypError::NotEnoughData` if `bits` is empty.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::frequency;
+///
+/// // The first 100 bits of pi, the worked example of SP 800-22.
+/// let pi: Vec<bool> = "1100100100001111110110101010001000100001011010001100001000110100\
+///                      110001001100011001100010100010111000".bytes().map(|b| b == b'1').collect();
+/// assert!((frequency(&pi).unwrap() - 0.109599).abs() < 1e-6);
+/// ```
+pub fn frequency(bits: &[bool]) -> Result<f64, CrypError> {
+    require(bits, 1)?;
+    let sum: i64 = bits.iter().map(|&bit| if bit { 1 } else { -1 }).sum();
+    let s_obs = sum.unsigned_abs() as f64 / (bits.len() as f64).sqrt();
+    Ok(erfc(s_obs / 2f64.sqrt()))
+}
+
+/// Frequency test within blocks: is each block of `block_len` bits about half ones? Leftover
+/// bits at the end are ignored. SP 800-22 recommends at least 20 bits per block, more than 1%
+/// of the input, and fewer than 100 blocks.
+///
+/// # Errors
+/// - `CrypError::InvalidArgument` if `block_len` is 0.
+/// - `CrypError::NotEnoughData` if `bits` doesn't fill a single block.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::block_frequency;
+///
+/// let pi: Vec<bool> = "1100100100001111110110101010001000100001011010001100001000110100\
+///                      110001001100011001100010100010111000".bytes().map(|b| b == b'1').collect();
+/// assert!((block_frequency(&pi, 10).unwrap() - 0.706438).abs() < 1e-6);
+/// ```
+pub fn block_frequency(bits: &[bool], block_len: usize) -> Result<f64, CrypError> {
+    if block_len == 0 {
+        return Err(CrypError::InvalidArgument { name: "block length", value: block_len });
+    }
+    require(bits, block_len)?;
+    let blocks = bits.len() / block_len;
+    let chi_squared = 4.0 * block_len as f64 * bits.chunks_exact(block_len)
+        .map(|block| {
+            let ones = block.iter().filter(|&&bit| bit).count();
+            (ones as f64 / block_len as f64 - 0.5).powi(2)
+        })
+        .sum::<f64>();
+    Ok(igamc(blocks as f64 / 2.0, chi_squared / 2.0))
+}
+
+/// Runs test: do the bits change value as often as they should? Too few changes mean runs of
+/// equal bits that are too long, too many mean the bits oscillate. The p-value is 0 if the
+/// bits are so unbalanced that they fail the frequency test.
+///
+/// # Errors
+/// - `CrypError::NotEnoughData` if there are fewer than 2 bits.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::runs;
+///
+/// let pi: Vec<bool> = "1100100100001111110110101010001000100001011010001100001000110100\
+///                      110001001100011001100010100010111000".bytes().map(|b| b == b'1').collect();
+/// assert!((runs(&pi).unwrap() - 0.500798).abs() < 1e-6);
+/// ```
+pub fn runs(bits: &[bool]) -> Result<f64, CrypError> {
+    require(bits, 2)?;
+    let n = bits.len() as f64;
+    let pi = bits.iter().filter(|&&bit| bit).count() as f64 / n;
+    if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+        return Ok(0.0);
+    }
+
+    let changes = 1 + bits.windows(2).filter(|pair| pair[0] != pair[1]).count();
+    let expected = 2.0 * n * pi * (1.0 - pi);
+    Ok(erfc((changes as f64 - expected).abs() / (2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi))))
+}
+
+/// Test for the longest run of ones within blocks: 8-bit blocks for fewer than 6272 bits,
+/// 128-bit blocks for fewer than 750000 bits and 10000-bit blocks beyond that.
+///
+/// # Errors
+/// - `CrypError::NotEnoughData` if there are fewer than 128 bits.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::longest_run;
+///
+/// let bits: Vec<bool> = "1100110000010101011011000100110011100000000000100100110101010001\
+///                        0001001111010110100000001101011111001100111001101101100010110010"
+///     .bytes().map(|b| b == b'1').collect();
+/// assert!((longest_run(&bits).unwrap() - 0.180609).abs() < 1e-6);
+/// ```
+pub fn longest_run(bits: &[bool]) -> Result<f64, CrypError> {
+    require(bits, 128)?;
+    // Block length, the longest run of the first class (shorter runs count there too), and
+    // the probability of each class of longest run; the last class takes any longer run.
+    let (block_len, shortest, probabilities): (usize, usize, &[f64]) = match bits.len() {
+        ..6272 => (8, 1, &[0.21484375, 0.3671875, 0.23046875, 0.1875]),
+        6272..750_000 => (128, 4, &[0.1174035788, 0.242955959, 0.249363483, 0.17517706, 0.102701071, 0.112398847]),
+        _ => (10_000, 10, &[0.0882, 0.2092, 0.2483, 0.1933, 0.1208, 0.0675, 0.0727]),
+    };
+
+    let mut observed = vec![0usize; probabilities.len()];
+    for block in bits.chunks_exact(block_len) {
+        let (mut longest, mut run) = (0, 0);
+        for &bit in block {
+            run = if bit { run + 1 } else { 0 };
+            longest = longest.max(run);
+        }
+        observed[longest.clamp(shortest, shortest + probabilities.len() - 1) - shortest] += 1;
+    }
+
+    let blocks = (bits.len() / block_len) as f64;
+    let chi_squared: f64 = observed.iter().zip(probabilities)
+        .map(|(&count, &p)| (count as f64 - blocks * p).powi(2) / (blocks * p))
+        .sum();
+    Ok(igamc((probabilities.len() - 1) as f64 / 2.0, chi_squared / 2.0))
+}
+
+/// How often each `len`-bit pattern occurs at every offset of `bits`, wrapping around the
+/// end, indexed by the pattern read as a big-endian number.
+fn pattern_counts(bits: &[bool], len: usize) -> Vec<u64> {
+    let mut counts = vec![0u64; 1 << len];
+    if len == 0 {
+        counts[0] = bits.len() as u64;
+        return counts;
+    }
+    let mask = (1usize << len) - 1;
+    let mut pattern = bits.iter().cycle().take(len - 1).fold(0, |acc, &bit| acc << 1 | bit as usize);
+    for &bit in bits.iter().cycle().skip(len - 1).take(bits.len()) {
+        pattern = (pattern << 1 | bit as usize) & mask;
+        counts[pattern] += 1;
+    }
+    counts
+}
+
+fn check_pattern_len(len: usize, max: usize) -> Result<(), CrypError> {
+    if !(1..=max).contains(&len) {
+        return Err(CrypError::InvalidArgument { name: "pattern length", value: len });
+    }
+    Ok(())
+}
+
+/// Serial test: do all `len`-bit patterns occur about equally often? Returns two p-values,
+/// comparing the patterns of `len` bits with those of `len - 1` bits, and those of `len - 1`
+/// bits with those of `len - 2` bits. SP 800-22 recommends `len < log2(bits.len()) - 2`.
+///
+/// # Errors
+/// - `CrypError::InvalidArgument` if `len` is 0 or above 24.
+/// - `CrypError::NotEnoughData` if there are fewer than `len` bits.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::serial;
+///
+/// let bits: Vec<bool> = "0011011101".bytes().map(|b| b == b'1').collect();
+/// let [first, second] = serial(&bits, 3).unwrap();
+/// assert!((first - 0.808792).abs() < 1e-6);
+/// assert!((second - 0.670320).abs() < 1e-6);
+/// ```
+pub fn serial(bits: &[bool], len: usize) -> Result<[f64; 2], CrypError> {
+    check_pattern_len(len, MAX_PATTERN_LEN)?;
+    require(bits, len)?;
+    let n = bits.len() as f64;
+    let psi_squared = |len: usize| {
+        let sum: f64 = pattern_counts(bits, len).iter().map(|&count| (count as f64).powi(2)).sum();
+        (1u64 << len) as f64 / n * sum - n
+    };
+
+    let psi = [psi_squared(len), psi_squared(len - 1), len.checked_sub(2).map_or(0.0, psi_squared)];
+    let delta = psi[0] - psi[1];
+    let delta_squared = psi[0] - 2.0 * psi[1] + psi[2];
+    Ok([
+        igamc(2f64.powi(len as i32 - 2), delta / 2.0),
+        igamc(2f64.powi(len as i32 - 3), delta_squared / 2.0),
+    ])
+}
+
+/// Approximate entropy test: are the patterns of `len + 1` bits as unpredictable from those of
+/// `len` bits as they should be? SP 800-22 recommends `len < log2(bits.len()) - 5`.
+///
+/// # Errors
+/// - `CrypError::InvalidArgument` if `len` is 0 or above 23 (it counts patterns of `len + 1`
+///   bits).
+/// - `CrypError::NotEnoughData` if there are `len` bits or fewer.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::approximate_entropy;
+/// use cryp::CrypError;
+///
+/// let pi: Vec<bool> = "1100100100001111110110101010001000100001011010001100001000110100\
+///                      110001001100011001100010100010111000".bytes().map(|b| b == b'1').collect();
+/// assert!((approximate_entropy(&pi, 2).unwrap() - 0.235301).abs() < 1e-6);
+/// assert!(matches!(approximate_entropy(&pi, 24), Err(CrypError::InvalidArgument { value: 24, .. })));
+/// ```
+pub fn approximate_entropy(bits: &[bool], len: usize) -> Result<f64, CrypError> {
+    check_pattern_len(len, MAX_PATTERN_LEN - 1)?;
+    require(bits, len + 1)?;
+    let n = bits.len() as f64;
+    let phi = |len: usize| -> f64 {
+        pattern_counts(bits, len).iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / n;
+                p * p.ln()
+            })
+            .sum()
+    };
+
+    let apen = phi(len) - phi(len + 1);
+    let chi_squared = 2.0 * n * (LN_2 - apen);
+    Ok(igamc(2f64.powi(len as i32 - 1), chi_squared / 2.0))
+}
+
+/// Cumulative sums test: does the random walk of +1 for every one and -1 for every zero stray
+/// too far from 0? Returns the p-values of the walk forwards and backwards.
+///
+/// # Errors
+/// - `CrypError::NotEnoughData` if `bits` is empty.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::cumulative_sums;
+///
+/// let pi: Vec<bool> = "1100100100001111110110101010001000100001011010001100001000110100\
+///                      110001001100011001100010100010111000".bytes().map(|b| b == b'1').collect();
+/// let [forward, backward] = cumulative_sums(&pi).unwrap();
+/// assert!((forward - 0.219194).abs() < 1e-6);
+/// assert!((backward - 0.114866).abs() < 1e-6);
+/// ```
+pub fn cumulative_sums(bits: &[bool]) -> Result<[f64; 2], CrypError> {
+    require(bits, 1)?;
+    let max_excursion = |bits: &mut dyn Iterator<Item = &bool>| {
+        let mut sum = 0i64;
+        let mut max = 0;
+        for &bit in bits {
+            sum += if bit { 1 } else { -1 };
+            max = max.max(sum.abs());
+        }
+        max
+    };
+    let forward = max_excursion(&mut bits.iter());
+    let backward = max_excursion(&mut bits.iter().rev());
+    Ok([cusum_p_value(bits.len() as i64, forward), cusum_p_value(bits.len() as i64, backward)])
+}
+
+/// The p-value of a random walk of `n` steps reaching `z` away from 0 at most, with the
+/// summation bounds of the SP 800-22 reference code.
+fn cusum_p_value(n: i64, z: i64) -> f64 {
+    let root_n = (n as f64).sqrt();
+    let z = z.max(1);
+    let term = |k: i64, a: i64, b: i64| normal_cdf((4 * k + a) as f64 * z as f64 / root_n) - normal_cdf((4 * k + b) as f64 * z as f64 / root_n);
+
+    let first: f64 = ((-n / z + 1) / 4..=(n / z - 1) / 4).map(|k| term(k, 1, -1)).sum();
+    let second: f64 = ((-n / z - 3) / 4..=(n / z - 1) / 4).map(|k| term(k, 3, 1)).sum();
+    (1.0 - first + second).clamp(0.0, 1.0)
+}
+
+/// Spectral (discrete Fourier transform) test: are there periodic features, i.e. too many
+/// frequencies with a large amplitude?
+///
+/// # Errors
+/// - `CrypError::NotEnoughData` if there are fewer than 2 bits.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::spectral;
+///
+/// let pi: Vec<bool> = "1100100100001111110110101010001000100001011010001100001000110100\
+///                      110001001100011001100010100010111000".bytes().map(|b| b == b'1').collect();
+/// assert!((spectral(&pi).unwrap() - 0.646355).abs() < 1e-6);
+///
+/// let periodic: Vec<bool> = (0..1024).map(|i| i % 3 == 0).collect();
+/// assert!(spectral(&periodic).unwrap() < 0.01);
+/// ```
+pub fn spectral(bits: &[bool]) -> Result<f64, CrypError> {
+    require(bits, 2)?;
+    let n = bits.len() as f64;
+    let signal: Vec<Complex> = bits.iter().map(|&bit| (if bit { 1.0 } else { -1.0 }, 0.0)).collect();
+    let spectrum = dft(&signal);
+
+    let threshold = ((1.0 / 0.05f64).ln() * n).sqrt();
+    let below = spectrum[..bits.len() / 2].iter().filter(|&&(re, im)| re.hypot(im) < threshold).count();
+    let expected = 0.95 * n / 2.0;
+    let d = (below as f64 - expected) / (n * 0.95 * 0.05 / 4.0).sqrt();
+    Ok(erfc(d.abs() / 2f64.sqrt()))
+}
+
+/// The p-values of one test of the battery, or why it couldn't run.
+#[derive(Debug)]
+pub struct TestResult {
+    pub name: &'static str,
+    pub p_values: Result<Vec<f64>, CrypError>,
+}
+
+impl TestResult {
+    /// Whether every p-value is at least `significance`; `None` if the test didn't run.
+    pub fn passed(&self, significance: f64) -> Option<bool> {
+        self.p_values.as_ref().ok().map(|p_values| p_values.iter().all(|&p| p >= significance))
+    }
+}
+
+/// Runs every test on the bits of `data`, picking block and pattern lengths suited to its
+/// length as SP 800-22 recommends. Tests that need more data than there is report
+/// `CrypError::NotEnoughData` (in bits), and so does every test below `MIN_BITS` bits. The
+/// spectral test only looks at the longest prefix whose length is a power of two, at least
+/// half the data, since transforms of other lengths are several times slower.
+///
+/// SP 800-22 suggests at least a million bits (125 KB) for meaningful results.
+///
+/// # Example
+/// ```rust
+/// use cryp::randomness::{run_battery, DEFAULT_SIGNIFICANCE};
+///
+/// // The lowest bits of a linear congruential generator alternate.
+/// let mut state = 1u32;
+/// let weak: Vec<u8> = (0..4096).map(|_| {
+///     state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+///     state as u8
+/// }).collect();
+/// let results = run_battery(&weak);
+/// assert_eq!(results.len(), 8);
+/// assert!(results.iter().any(|result| result.passed(DEFAULT_SIGNIFICANCE) == Some(false)));
+///
+/// // The top byte of each state does much better.
+/// let mut state = 1u32;
+/// let better: Vec<u8> = (0..4096).map(|_| {
+///     state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+///     (state >> 24) as u8
+/// }).collect();
+/// assert!(run_battery(&better).iter().all(|result| result.passed(DEFAULT_SIGNIFICANCE) == Some(true)));
+/// ```
+pub fn run_battery(data: &[u8]) -> Vec<TestResult> {
+    let bits = bits(data);
+    let n = bits.len();
+    let log2_n = n.max(1).ilog2() as usize;
+    let block_len = BLOCK_FREQUENCY_LEN.max(n / 100 + 1);
+    let serial_len = SERIAL_PATTERN_LEN.min(log2_n.saturating_sub(3)).max(2);
+    let apen_len = APPROXIMATE_ENTROPY_PATTERN_LEN.min(log2_n.saturating_sub(6)).max(1);
+
+    vec![
+        run_test("frequency", &bits, |bits| Ok(vec![frequency(bits)?])),
+        run_test("block frequency", &bits, |bits| Ok(vec![block_frequency(bits, block_len)?])),
+        run_test("runs", &bits, |bits| Ok(vec![runs(bits)?])),
+        run_test("longest run", &bits, |bits| Ok(vec![longest_run(bits)?])),
+        run_test("serial", &bits, |bits| Ok(serial(bits, serial_len)?.to_vec())),
+        run_test("approximate entropy", &bits, |bits| Ok(vec![approximate_entropy(bits, apen_len)?])),
+        run_test("cumulative sums", &bits, |bits| Ok(cumulative_sums(bits)?.to_vec())),
+        run_test("spectral", &bits, |bits| Ok(vec![spectral(&bits[..1 << log2_n])?])),
+    ]
+}
+
+fn run_test(name: &'static str, bits: &[bool], test: impl FnOnce(&[bool]) -> Result<Vec<f64>, CrypError>) -> TestResult {
+    TestResult { name, p_values: require(bits, MIN_BITS).and_then(|_| test(bits)) }
+}
+
+/// The complementary error function, `1 - erf(x)`.
+fn erfc(x: f64) -> f64 {
+    if x < 0.0 {
+        2.0 - igamc(0.5, x * x)
+    } else {
+        igamc(0.5, x * x)
+    }
+}
+
+/// The cumulative distribution function of the standard normal distribution.
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / 2f64.sqrt())
+}
+
+/// The natural logarithm of the gamma function for `x > 0` (Lanczos approximation, g = 7).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection formula.
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..].iter().enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, &c)| sum + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// The regularized upper incomplete gamma function `Q(a, x)`, from a power series for small
+/// `x` and a continued fraction otherwise.
+fn igamc(a: f64, x: f64) -> f64 {
+    const EPSILON: f64 = 1e-15;
+    const MAX_ITERATIONS: usize = 10_000;
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefix = a * x.ln() - x - ln_gamma(a);
+
+    if x < a + 1.0 {
+        let (mut term, mut sum, mut ap) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..MAX_ITERATIONS {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        return (1.0 - sum * log_prefix.exp()).clamp(0.0, 1.0);
+    }
+
+    // Modified Lentz's method.
+    let tiny = f64::MIN_POSITIVE / EPSILON;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..MAX_ITERATIONS {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    (log_prefix.exp() * h).clamp(0.0, 1.0)
+}
+
+type Complex = (f64, f64);
+
+fn mul(a: Complex, b: Complex) -> Complex {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+/// In-place radix-2 fast Fourier transform; `data.len()` must be a power of two. `inverse`
+/// flips the sign of the exponent without scaling.
+fn fft(data: &mut [Complex], inverse: bool) {
+    let n = data.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    // The roots of unity of the last stage; earlier stages use every (n / len)-th one.
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let roots: Vec<Complex> = (0..n / 2)
+        .map(|k| {
+            let angle = sign * 2.0 * PI * k as f64 / n as f64;
+            (angle.cos(), angle.sin())
+        })
+        .collect();
+    let mut len = 2;
+    while len <= n {
+        let stride = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (u, v) = (data[start + k], mul(data[start + k + len / 2], roots[k * stride]));
+                data[start + k] = (u.0 + v.0, u.1 + v.1);
+                data[start + k + len / 2] = (u.0 - v.0, u.1 - v.1);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// The discrete Fourier transform of `signal` of any length, using Bluestein's algorithm when
+/// the length isn't a power of two.
+fn dft(signal: &[Complex]) -> Vec<Complex> {
+    let n = signal.len();
+    if n.is_power_of_two() {
+        let mut data = signal.to_vec();
+        fft(&mut data, false);
+        return data;
+    }
+
+    // exp(-i pi k^2 / n), reducing k^2 modulo 2n to keep the angle precise.
+    let chirp: Vec<Complex> = (0..n as u64)
+        .map(|k| {
+            let angle = PI * ((k * k) % (2 * n as u64)) as f64 / n as f64;
+            (angle.cos(), -angle.sin())
+        })
+        .collect();
+    let size = (2 * n - 1).next_power_of_two();
+    let mut a = vec![(0.0, 0.0); size];
+    for (k, (&x, &w)) in signal.iter().zip(&chirp).enumerate() {
+        a[k] = mul(x, w);
+    }
+    let mut b = vec![(0.0, 0.0); size];
+    for (k, &(re, im)) in chirp.iter().enumerate() {
+        b[k] = (re, -im);
+        if k > 0 {
+            b[size - k] = (re, -im);
+        }
+    }
+
+    fft(&mut a, false);
+    fft(&mut b, false);
+    for (x, &y) in a.iter_mut().zip(&b) {
+        *x = mul(*x, y);
+    }
+    fft(&mut a, true);
+    chirp.iter().zip(&a)
+        .map(|(&w, &(re, im))| mul((re / size as f64, im / size as f64), w))
+        .collect()
+}
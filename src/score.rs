@@ -0,0 +1,184 @@
+//! Plaintext scoring functions behind a common [`Scorer`] trait.
+//!
+//! Breaking XOR ciphers comes down to ranking candidate decryptions by how much they look like
+//! plaintext. `score_text` only measures the share of letters, digits and spaces, so many
+//! candidates tie; the scorers here compare the whole byte distribution against English:
+//!
+//! - [`LogLikelihood`]: average log-probability of each byte under an English unigram model.
+//!   The default for `xor_guess_key`: it recovers the most key bytes on short columns.
+//! - [`ChiSquared`]: Pearson's chi-squared statistic over letter, space, punctuation and
+//!   control character counts.
+//! - [`AlnumRatio`]: the original `score_text` percentage.
+//!
+//! Other languages, source code, JSON and file formats are covered by [`crate::profile`].
+//!
+//! # Example
+//! ```rust
+//! use cryp::score::{self, Scorer};
+//!
+//! let english = b"Now that the party is jumping";
+//! let garbled = b"nOW\x00THAT\x00THE\x00PARTY\x00IS\x00JUMPING";
+//! for scorer in score::all() {
+//!     assert!(scorer.score(english) > scorer.score(garbled), "{}", scorer.name());
+//! }
+//! ```
+
+use crate::profile::Profile;
+use crate::{is_valid_text, score_text};
+
+/// Relative frequencies of `a` to `z` in English text, in percent.
+pub const ENGLISH_LETTER_FREQUENCIES: [f64; 26] = [
+    8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406,
+    6.749, 7.507, 1.929, 0.095, 5.987, 6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+];
+
+/// Share of letters, spaces and other printable characters in English prose.
+pub(crate) const LETTER_SHARE: f64 = 0.78;
+pub(crate) const SPACE_SHARE: f64 = 0.17;
+pub(crate) const OTHER_SHARE: f64 = 0.05;
+const CONTROL_SHARE: f64 = 1e-4;
+
+/// Ranks byte strings by how much they look like plaintext. Scorers are `Sync` so that
+/// searches such as `detect_single_byte_xor` can share one across threads.
+pub trait Scorer: Sync {
+    /// Name used on the command line, e.g. `"chi2"`.
+    fn name(&self) -> &'static str;
+
+    /// Higher is more plaintext-like. Scores are normalized by length, so inputs of different
+    /// sizes can be compared, but scores of different scorers can not.
+    fn score(&self, data: &[u8]) -> f64;
+
+    /// Whether `data` is plausibly plaintext.
+    fn is_text(&self, data: &[u8]) -> bool;
+}
+
+/// Pearson's chi-squared test against English. The categories are the common letters (case
+/// insensitive), the rare letters `jkqvxz` pooled together, space, other printable characters
+/// and control or non-ASCII bytes. The score is the negated statistic divided by the input
+/// length.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::{ChiSquared, Scorer};
+///
+/// assert!(ChiSquared.is_text(b"Cooking MC's like a pound of bacon"));
+/// assert!(!ChiSquared.is_text(b"cOOKING\x00mc\x07S\x00LIKE\x00A\x00POUND"));
+/// ```
+pub struct ChiSquared;
+
+impl ChiSquared {
+    /// The chi-squared statistic of `data` against English.
+    pub fn statistic(data: &[u8]) -> f64 {
+        let mut observed = [0usize; 29];
+        for &b in data {
+            let category = match b {
+                b'a'..=b'z' => (b - b'a') as usize,
+                b'A'..=b'Z' => (b - b'A') as usize,
+                b' ' => 26,
+                0x21..=0x7e | b'\n' | b'\r' | b'\t' => 27,
+                _ => 28,
+            };
+            observed[category] += 1;
+        }
+
+        let len = data.len() as f64;
+        let term = |count: usize, p: f64| (count as f64 - p * len).powi(2) / (p * len);
+
+        // Letters rarer than 1% are pooled: a single `z` in a short text would otherwise
+        // outweigh every other category.
+        let (mut rare_count, mut rare_p) = (0, 0.0);
+        let mut statistic = 0.0;
+        for (&count, percent) in observed.iter().zip(ENGLISH_LETTER_FREQUENCIES) {
+            let p = percent / 100.0 * LETTER_SHARE;
+            if percent < 1.0 {
+                rare_count += count;
+                rare_p += p;
+            } else {
+                statistic += term(count, p);
+            }
+        }
+
+        statistic
+            + term(rare_count, rare_p)
+            + term(observed[26], SPACE_SHARE)
+            + term(observed[27], OTHER_SHARE)
+            + term(observed[28], CONTROL_SHARE)
+    }
+}
+
+impl Scorer for ChiSquared {
+    fn name(&self) -> &'static str { "chi2" }
+
+    fn score(&self, data: &[u8]) -> f64 {
+        if data.is_empty() {
+            return f64::MIN;
+        }
+        -ChiSquared::statistic(data) / data.len() as f64
+    }
+
+    /// Accepts `data` when the statistic stays below a bound that grows with the length, loose
+    /// enough for short English phrases and far below printable garbage.
+    fn is_text(&self, data: &[u8]) -> bool {
+        !data.is_empty() && ChiSquared::statistic(data) < 60.0 + 3.0 * data.len() as f64
+    }
+}
+
+/// Average log-likelihood per byte under an English unigram model, in nats. Unlike
+/// `ChiSquared` it tells upper and lower case apart and scores every byte value.
+///
+/// # Example
+/// ```rust
+/// use cryp::score::{LogLikelihood, Scorer};
+///
+/// assert!(LogLikelihood.score(b"the quick brown fox") > LogLikelihood.score(b"THE QUICK BROWN FOX"));
+/// assert!(LogLikelihood.is_text(b"the quick brown fox"));
+/// assert!(!LogLikelihood.is_text(&[0x00, 0x9f, 0xff, 0x13]));
+/// ```
+pub struct LogLikelihood;
+
+impl Scorer for LogLikelihood {
+    fn name(&self) -> &'static str { "loglik" }
+
+    fn score(&self, data: &[u8]) -> f64 {
+        if data.is_empty() {
+            return f64::MIN;
+        }
+        Profile::English.score(data)
+    }
+
+    /// Accepts `data` when it averages above -4.5 nats per byte; English prose scores about -3.
+    fn is_text(&self, data: &[u8]) -> bool {
+        !data.is_empty() && self.score(data) > -4.5
+    }
+}
+
+/// The percentage of ASCII letters, digits and spaces, as computed by `score_text`.
+/// `is_text` is `is_valid_text`.
+pub struct AlnumRatio;
+
+impl Scorer for AlnumRatio {
+    fn name(&self) -> &'static str { "alnum" }
+
+    fn score(&self, data: &[u8]) -> f64 {
+        if data.is_empty() {
+            return f64::MIN;
+        }
+        score_text(data) as f64
+    }
+
+    fn is_text(&self, data: &[u8]) -> bool {
+        is_valid_text(data)
+    }
+}
+
+/// Every scorer, the default (`LogLikelihood`) first.
+pub fn all() -> Vec<Box<dyn Scorer>> {
+    vec![Box::new(LogLikelihood), Box::new(ChiSquared), Box::new(AlnumRatio)]
+}
+
+/// Looks up a scorer by its `name`, falling back to the plaintext profiles (e.g. `"german"`).
+pub fn by_name(name: &str) -> Option<Box<dyn Scorer>> {
+    all().into_iter()
+        .find(|scorer| scorer.name().eq_ignore_ascii_case(name))
+        .or_else(|| Profile::by_name(name).map(|profile| Box::new(profile) as Box<dyn Scorer>))
+}
@@ -0,0 +1,462 @@
+//! Incremental base64 and hex codecs as `Read`/`Write` adapters.
+//!
+//! The encoders wrap a `Write` and the decoders wrap a `Read`, so arbitrarily large inputs can
+//! be converted without holding them in memory. The output is byte-for-byte identical to
+//! `base64_encode_config`/`base64_decode_config`/`bytes_to_hexstr`/`hexstr_to_bytes`, except
+//! that encoders can optionally wrap lines and the hex decoder skips whitespace anywhere in the
+//! input (the base64 decoder does too unless its `Base64Config` is strict).
+//!
+//! Decoding errors are returned as `io::ErrorKind::InvalidData` wrapping a [`CrypError`] whose
+//! position is an offset into the whole stream.
+//!
+//! # Example
+//! ```rust
+//! use std::io::{Read, Write};
+//! use cryp::{base64_encode, bytes_to_hexstr};
+//! use cryp::stream::{Base64Decoder, Base64Encoder, HexDecoder, HexEncoder};
+//!
+//! let data: Vec<u8> = (0..=255).collect();
+//! for len in 0..data.len() {
+//!     let mut encoder = Base64Encoder::new(Vec::new());
+//!     data[..len].chunks(7).for_each(|chunk| encoder.write_all(chunk).unwrap());
+//!     let encoded = encoder.finish().unwrap();
+//!     assert_eq!(encoded, base64_encode(&data[..len]).as_bytes());
+//!
+//!     let mut decoded = Vec::new();
+//!     Base64Decoder::new(&encoded[..]).read_to_end(&mut decoded).unwrap();
+//!     assert_eq!(decoded, &data[..len]);
+//!
+//!     let mut encoder = HexEncoder::new(Vec::new());
+//!     data[..len].chunks(7).for_each(|chunk| encoder.write_all(chunk).unwrap());
+//!     let encoded = encoder.finish().unwrap();
+//!     assert_eq!(encoded, bytes_to_hexstr(&data[..len]).as_bytes());
+//!
+//!     let mut decoded = Vec::new();
+//!     HexDecoder::new(&encoded[..]).read_to_end(&mut decoded).unwrap();
+//!     assert_eq!(decoded, &data[..len]);
+//! }
+//! ```
+
+use std::io::{self, Read, Write};
+
+use crate::{base64_decode_config, base64_encode_config, bytes_to_hexstr, hexstr_to_bytes, Base64Config, CrypError};
+
+/// Line width used by PEM armor.
+pub const PEM_LINE_WIDTH: usize = 64;
+
+/// Line width used by MIME (RFC 2045).
+pub const MIME_LINE_WIDTH: usize = 76;
+
+const CHUNK_SIZE: usize = 8192;
+
+fn invalid_data(e: CrypError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Writes encoded text to the inner writer, breaking lines every `width` characters.
+struct LineWriter<W: Write> {
+    inner: W,
+    width: Option<usize>,
+    ending: &'static str,
+    column: usize,
+}
+
+impl<W: Write> LineWriter<W> {
+    fn new(inner: W) -> Self {
+        LineWriter { inner, width: None, ending: "\n", column: 0 }
+    }
+
+    fn write_text(&mut self, mut text: &[u8]) -> io::Result<()> {
+        let width = match self.width {
+            Some(width) => width,
+            None => return self.inner.write_all(text),
+        };
+
+        while !text.is_empty() {
+            if self.column == width {
+                self.inner.write_all(self.ending.as_bytes())?;
+                self.column = 0;
+            }
+            let take = (width - self.column).min(text.len());
+            self.inner.write_all(&text[..take])?;
+            self.column += take;
+            text = &text[take..];
+        }
+        Ok(())
+    }
+
+    /// Terminates the last line when wrapping is enabled.
+    fn finish_line(&mut self) -> io::Result<()> {
+        if self.width.is_some() && self.column > 0 {
+            self.inner.write_all(self.ending.as_bytes())?;
+            self.column = 0;
+        }
+        Ok(())
+    }
+}
+
+/// Base64-encodes everything written to it into the inner writer.
+///
+/// Call [`finish`](Base64Encoder::finish) to write the final (padded) quantum; dropping the
+/// encoder does the same but ignores errors.
+///
+/// # Example
+/// ```rust
+/// use std::io::Write;
+/// use cryp::stream::Base64Encoder;
+///
+/// let mut encoder = Base64Encoder::new(Vec::new());
+/// encoder.write_all(b"Hel").unwrap();
+/// encoder.write_all(b"lo").unwrap();
+/// assert_eq!(encoder.finish().unwrap(), b"SGVsbG8=");
+///
+/// let mut encoder = Base64Encoder::new(Vec::new()).wrap(4);
+/// encoder.write_all(b"Hello").unwrap();
+/// assert_eq!(encoder.finish().unwrap(), b"SGVs\nbG8=\n");
+/// ```
+pub struct Base64Encoder<W: Write> {
+    out: Option<LineWriter<W>>,
+    pending: Vec<u8>,
+    config: Base64Config,
+}
+
+impl<W: Write> Base64Encoder<W> {
+    pub fn new(inner: W) -> Self {
+        Base64Encoder::with_config(inner, Base64Config::STANDARD)
+    }
+
+    /// Encodes with the alphabet and padding of `config`.
+    pub fn with_config(inner: W, config: Base64Config) -> Self {
+        Base64Encoder { out: Some(LineWriter::new(inner)), pending: Vec::with_capacity(3), config }
+    }
+
+    /// Breaks the output into lines of `width` characters (see [`PEM_LINE_WIDTH`] and
+    /// [`MIME_LINE_WIDTH`]). A width of zero disables wrapping.
+    pub fn wrap(mut self, width: usize) -> Self {
+        if let Some(out) = self.out.as_mut() {
+            out.width = (width > 0).then_some(width);
+        }
+        self
+    }
+
+    /// Uses `"\r\n"` instead of `"\n"` between wrapped lines, as MIME requires.
+    pub fn crlf(mut self) -> Self {
+        if let Some(out) = self.out.as_mut() {
+            out.ending = "\r\n";
+        }
+        self
+    }
+
+    /// Writes the remaining input with padding and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.finish_encoding()?;
+        let mut out = self.out.take().expect("encoder already finished");
+        out.inner.flush()?;
+        Ok(out.inner)
+    }
+
+    fn finish_encoding(&mut self) -> io::Result<()> {
+        if let Some(out) = self.out.as_mut() {
+            out.write_text(base64_encode_config(&self.pending, &self.config).as_bytes())?;
+            self.pending.clear();
+            out.finish_line()?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Base64Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let out = self.out.as_mut().expect("encoder already finished");
+
+        // Only whole 3-byte groups are encoded here, so no padding is produced mid-stream.
+        let mut data = std::mem::take(&mut self.pending);
+        data.extend_from_slice(buf);
+        let whole = data.len() - data.len() % 3;
+        out.write_text(base64_encode_config(&data[..whole], &self.config).as_bytes())?;
+        self.pending = data.split_off(whole);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.out.as_mut() {
+            Some(out) => out.inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for Base64Encoder<W> {
+    fn drop(&mut self) {
+        let _ = self.finish_encoding();
+    }
+}
+
+/// Hex-encodes everything written to it into the inner writer.
+///
+/// # Example
+/// ```rust
+/// use std::io::Write;
+/// use cryp::stream::HexEncoder;
+///
+/// let mut encoder = HexEncoder::new(Vec::new());
+/// encoder.write_all(b"Hello").unwrap();
+/// assert_eq!(encoder.finish().unwrap(), b"48656c6c6f");
+/// ```
+pub struct HexEncoder<W: Write> {
+    out: LineWriter<W>,
+}
+
+impl<W: Write> HexEncoder<W> {
+    pub fn new(inner: W) -> Self {
+        HexEncoder { out: LineWriter::new(inner) }
+    }
+
+    /// Breaks the output into lines of `width` characters. A width of zero disables wrapping.
+    pub fn wrap(mut self, width: usize) -> Self {
+        self.out.width = (width > 0).then_some(width);
+        self
+    }
+
+    /// Terminates the last wrapped line and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.finish_line()?;
+        self.out.inner.flush()?;
+        Ok(self.out.inner)
+    }
+}
+
+impl<W: Write> Write for HexEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.out.write_text(bytes_to_hexstr(buf).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.inner.flush()
+    }
+}
+
+/// Reads chunks from the inner reader and keeps the non-whitespace bytes (or all of them, if
+/// `skip_whitespace` is off), together with their offsets in the stream.
+struct TextSource<R: Read> {
+    inner: R,
+    offset: usize,
+    eof: bool,
+    skip_whitespace: bool,
+}
+
+impl<R: Read> TextSource<R> {
+    fn new(inner: R) -> Self {
+        TextSource { inner, offset: 0, eof: false, skip_whitespace: true }
+    }
+
+    /// Appends the next chunk of input to `text`/`positions`.
+    fn fill(&mut self, text: &mut Vec<u8>, positions: &mut Vec<usize>) -> io::Result<()> {
+        let mut chunk = [0u8; CHUNK_SIZE];
+        let n = loop {
+            match self.inner.read(&mut chunk) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result?,
+            }
+        };
+        if n == 0 {
+            self.eof = true;
+        }
+
+        for (i, &byte) in chunk[..n].iter().enumerate() {
+            if !(self.skip_whitespace && byte.is_ascii_whitespace()) {
+                text.push(byte);
+                positions.push(self.offset + i);
+            }
+        }
+        self.offset += n;
+        Ok(())
+    }
+}
+
+/// Serves decoded bytes out of an internal buffer.
+fn serve(decoded: &mut Vec<u8>, served: &mut usize, buf: &mut [u8]) -> usize {
+    let n = (decoded.len() - *served).min(buf.len());
+    buf[..n].copy_from_slice(&decoded[*served..*served + n]);
+    *served += n;
+    if *served == decoded.len() {
+        decoded.clear();
+        *served = 0;
+    }
+    n
+}
+
+/// Decodes base64 read from the inner reader, skipping whitespace unless the config is strict.
+///
+/// # Example
+/// ```rust
+/// use std::io::Read;
+/// use cryp::stream::Base64Decoder;
+///
+/// let mut decoded = Vec::new();
+/// Base64Decoder::new(&b"SGVs\r\nbG8=\n"[..]).read_to_end(&mut decoded).unwrap();
+/// assert_eq!(decoded, b"Hello");
+///
+/// let err = Base64Decoder::new(&b"SGVs\n*G8="[..]).read_to_end(&mut Vec::new()).unwrap_err();
+/// assert_eq!(err.to_string(), "invalid base64 byte '*' at position 5");
+/// ```
+pub struct Base64Decoder<R: Read> {
+    source: TextSource<R>,
+    config: Base64Config,
+    text: Vec<u8>,
+    positions: Vec<usize>,
+    /// Stream offset of the first `=` decoded so far; nothing may follow it.
+    padding_pos: Option<usize>,
+    decoded: Vec<u8>,
+    served: usize,
+}
+
+impl<R: Read> Base64Decoder<R> {
+    pub fn new(inner: R) -> Self {
+        Base64Decoder::with_config(inner, Base64Config::STANDARD)
+    }
+
+    /// Decodes the variant described by `config`.
+    pub fn with_config(inner: R, config: Base64Config) -> Self {
+        let mut source = TextSource::new(inner);
+        source.skip_whitespace = !config.strict;
+        Base64Decoder {
+            source,
+            config,
+            text: Vec::new(),
+            positions: Vec::new(),
+            padding_pos: None,
+            decoded: Vec::new(),
+            served: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.source.inner
+    }
+
+    /// Decodes `len` bytes of buffered text, translating error positions to stream offsets.
+    fn decode(&mut self, len: usize) -> io::Result<()> {
+        if let (Some(pos), true) = (self.padding_pos, len > 0) {
+            return Err(invalid_data(CrypError::InvalidBase64Padding { pos }));
+        }
+        if let Some(i) = self.text[..len].iter().position(|byte| !byte.is_ascii()) {
+            let (pos, byte) = (self.positions[i], self.text[i]);
+            return Err(invalid_data(CrypError::InvalidBase64 { pos, byte }));
+        }
+
+        let text = std::str::from_utf8(&self.text[..len]).expect("checked to be ASCII");
+        let decoded = base64_decode_config(text, &self.config).map_err(|e| {
+            let at = |pos: usize| self.positions.get(pos).copied().unwrap_or(self.source.offset);
+            invalid_data(match e {
+                CrypError::InvalidBase64 { pos, byte } => CrypError::InvalidBase64 { pos: at(pos), byte },
+                CrypError::InvalidBase64Padding { pos } => CrypError::InvalidBase64Padding { pos: at(pos) },
+                CrypError::NonCanonicalBase64 { pos } => CrypError::NonCanonicalBase64 { pos: at(pos) },
+                other => other,
+            })
+        })?;
+
+        if let Some(i) = text.find(crate::PADDING) {
+            self.padding_pos = Some(self.positions[i]);
+        }
+        self.decoded.extend(decoded);
+        self.text.drain(..len);
+        self.positions.drain(..len);
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Base64Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.decoded.is_empty() && !(self.source.eof && self.text.is_empty()) {
+            if self.source.eof {
+                // Whatever is left is not a whole quantum; let `base64_decode` report it.
+                self.decode(self.text.len())?;
+            } else {
+                self.source.fill(&mut self.text, &mut self.positions)?;
+                let whole = self.text.len() - self.text.len() % 4;
+                self.decode(whole)?;
+            }
+        }
+        Ok(serve(&mut self.decoded, &mut self.served, buf))
+    }
+}
+
+/// Decodes hex read from the inner reader, skipping whitespace.
+///
+/// # Example
+/// ```rust
+/// use std::io::Read;
+/// use cryp::stream::HexDecoder;
+///
+/// let mut decoded = Vec::new();
+/// HexDecoder::new(&b"4865 6c6c\n6f"[..]).read_to_end(&mut decoded).unwrap();
+/// assert_eq!(decoded, b"Hello");
+/// ```
+pub struct HexDecoder<R: Read> {
+    source: TextSource<R>,
+    text: Vec<u8>,
+    positions: Vec<usize>,
+    digits: usize,
+    decoded: Vec<u8>,
+    served: usize,
+}
+
+impl<R: Read> HexDecoder<R> {
+    pub fn new(inner: R) -> Self {
+        HexDecoder {
+            source: TextSource::new(inner),
+            text: Vec::new(),
+            positions: Vec::new(),
+            digits: 0,
+            decoded: Vec::new(),
+            served: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.source.inner
+    }
+
+    fn check_digits(&self, len: usize) -> io::Result<()> {
+        match self.text[..len].iter().position(|byte| !byte.is_ascii_hexdigit()) {
+            Some(i) => {
+                // Report the full (possibly multi-byte) character, like `hexstr_to_bytes` does.
+                let rest = String::from_utf8_lossy(&self.text[i..]);
+                let char = rest.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+                Err(invalid_data(CrypError::InvalidHex { pos: self.positions[i], char }))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn decode(&mut self, len: usize) -> io::Result<()> {
+        self.check_digits(len)?;
+
+        let text = std::str::from_utf8(&self.text[..len]).expect("hex digits are ASCII");
+        self.decoded.extend(hexstr_to_bytes(text).map_err(invalid_data)?);
+        self.digits += len;
+        self.text.drain(..len);
+        self.positions.drain(..len);
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for HexDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.decoded.is_empty() && !(self.source.eof && self.text.is_empty()) {
+            if self.source.eof {
+                // A single character is left over.
+                self.check_digits(self.text.len())?;
+                let len = self.digits + self.text.len();
+                return Err(invalid_data(CrypError::OddHexLength { len }));
+            }
+            self.source.fill(&mut self.text, &mut self.positions)?;
+            let whole = self.text.len() - self.text.len() % 2;
+            self.decode(whole)?;
+        }
+        Ok(serve(&mut self.decoded, &mut self.served, buf))
+    }
+}